@@ -0,0 +1,56 @@
+//! Making a link for every utility, for `coreutils --install DIR`.
+//!
+//! Each link is named after a utility and points at the `coreutils` program, which
+//! looks at the name it was run as to pick the utility. Symbolic links are made by
+//! default. `--hardlink` makes hard links instead, which only work when `DIR` is on
+//! the same file system as the program.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use shared::error::{ExitStatus, UError};
+
+/// Make a link in `dir` for each name in `names`, pointing at `target`. Links
+/// that can't be made are reported, and the rest are still made.
+///
+/// # Arguments
+///
+/// * `dir` - Directory to put the links in.
+/// * `target` - The program the links point at.
+/// * `names` - Names of the links, without an extension like `.exe`.
+/// * `hard` - Whether to make hard links instead of symbolic links.
+/// * `force` - Whether to replace files that are already there.
+/// * `status` - Exit status to fail if a link can't be made.
+pub(crate) fn install(dir: &Path, target: &Path, names: &[&str], hard: bool, force: bool,
+    status: &mut ExitStatus) {
+    for name in names {
+        let link = dir.join(format!("{}{}", name, std::env::consts::EXE_SUFFIX));
+        if let Err(e) = make_link(target, &link, hard, force) {
+            status.report(&UError::io(&link.display().to_string(), &e));
+        }
+    }
+}
+
+/// Make one link at `link` pointing at `target`.
+fn make_link(target: &Path, link: &Path, hard: bool, force: bool) -> io::Result<()> {
+    // symlink_metadata() sees broken symbolic links too
+    if force && fs::symlink_metadata(link).is_ok() {
+        fs::remove_file(link)?;
+    }
+    if hard {
+        fs::hard_link(target, link)
+    } else {
+        symlink(target, link)
+    }
+}
+
+#[cfg(unix)]
+fn symlink(target: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn symlink(target: &Path, link: &Path) -> io::Result<()> {
+    std::os::windows::fs::symlink_file(target, link)
+}
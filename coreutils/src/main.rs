@@ -0,0 +1,137 @@
+//! All of the core utils in one program, for small installs like containers.
+//!
+//! A utility is picked by the name the program is run as, so a link named `cat`
+//! that points at `coreutils` runs cat. Otherwise, the first argument is the name:
+//! ```text
+//! :~$ coreutils wc -l notes.txt
+//! ```
+//! `coreutils --list` shows every name that can be used, and
+//! `coreutils --install DIR` makes a link named after each one in `DIR`.
+//!
+//! Each utility crate has a `UTILITY` constant with its entry point (see
+//! `shared::utility`). Which ones are built in is picked with cargo features.
+
+use std::env::{args_os, current_exe};
+use std::ffi::OsString;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+use shared::error::{parse_args, strerror, ExitStatus, UError, EXIT_FAILURE};
+use shared::output::Output;
+use shared::utility::Utility;
+
+mod install;
+
+/// Name of the program, shown at the start of error messages.
+const UTIL_NAME: &str = "coreutils";
+
+/// Every utility that's built in.
+const UTILITIES: &[Utility] = &[
+    #[cfg(feature = "cat")]
+    rcat::UTILITY,
+    #[cfg(feature = "wc")]
+    wc::UTILITY,
+];
+
+/// Struct that contains information about the command line options that were entered.
+/// Used by the `clap` library.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None, arg_required_else_help = true,
+    args_conflicts_with_subcommands = true, subcommand_value_name = "UTILITY")]
+struct Cli {
+    #[arg(long)]
+    /// List the names of the utilities that can be run, one per line
+    list: bool,
+
+    #[arg(long, value_name = "DIR")]
+    /// Make a link to this program in DIR for every utility, named after it
+    install: Option<PathBuf>,
+
+    #[arg(long, requires = "install")]
+    /// With --install, make hard links instead of symbolic links
+    hardlink: bool,
+
+    #[arg(long, requires = "install")]
+    /// With --install, replace files that are already there
+    force: bool,
+
+    #[command(subcommand)]
+    utility: Option<Command>,
+}
+
+/// The utility to run, as the first argument.
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Name of the utility, and its arguments
+    #[command(external_subcommand)]
+    Run(Vec<OsString>),
+}
+
+/// Entry point for the program.
+fn main() -> ExitCode {
+    let args: Vec<OsString> = args_os().collect();
+
+    // run through a link named after a utility
+    let program = args.first()
+        .and_then(|a| Path::new(a).file_stem())
+        .and_then(|a| a.to_str());
+    if let Some(utility) = program.and_then(find) {
+        return (utility.main)(args);
+    }
+
+    let clap_args: Cli = parse_args(&args, EXIT_FAILURE);
+    let mut status = ExitStatus::new(UTIL_NAME);
+
+    if clap_args.list {
+        list(&mut status);
+    } else if let Some(dir) = &clap_args.install {
+        match current_exe() {
+            Ok(exe) => install::install(dir, &exe, &names(), clap_args.hardlink, clap_args.force, &mut status),
+            Err(e) => status.report(&UError::new(format!("can't find this program: {}", strerror(&e)))),
+        }
+    } else if let Some(Command::Run(args)) = clap_args.utility {
+        let name = args[0].to_string_lossy();
+        match find(&name) {
+            Some(utility) => return (utility.main)(args),
+            None => status.report(&UError::new(format!("unknown program '{}'", name))),
+        }
+    }
+
+    status.into()
+}
+
+/// Find the utility that's run as `name`.
+fn find(name: &str) -> Option<&'static Utility> {
+    UTILITIES.iter().find(|u| u.is_named(name))
+}
+
+/// Every name a utility can be run as, sorted.
+fn names() -> Vec<&'static str> {
+    let mut names: Vec<_> = UTILITIES.iter().flat_map(Utility::names).collect();
+    names.sort_unstable();
+    names
+}
+
+/// Write every name a utility can be run as to standard output, one per line.
+fn list(status: &mut ExitStatus) {
+    let mut out = Output::stdout();
+    let result = names().iter().try_for_each(|name| writeln!(out, "{}", name));
+    out.finish(result, status);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_unique() {
+        let mut names = names();
+        let count = names.len();
+        names.dedup();
+        assert_eq!(names.len(), count);
+        assert!(names.iter().all(|name| find(name).is_some()));
+        assert!(find("coreutils").is_none());
+    }
+}
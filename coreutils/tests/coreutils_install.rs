@@ -0,0 +1,97 @@
+/// Test --install, and running utilities through the links it makes.
+mod test_utils;
+
+#[cfg(all(test, unix))]
+mod test {
+    use std::path::PathBuf;
+
+    use assert_cmd::prelude::*;
+    use predicates::prelude::*;
+
+    use crate::test_utils as tu;
+
+    /// Helper method to make an empty directory to install into. It's under the
+    /// target directory, so hard links to the program work.
+    fn install_dir(name: &str) -> PathBuf {
+        let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("install_{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Helper method to install links into `dir`, with any extra `args`.
+    fn install(dir: &PathBuf, args: &[&str]) -> assert_cmd::assert::Assert {
+        let mut cmd = tu::get_cmd();
+        cmd.arg("--install").arg(dir).args(args).assert()
+    }
+
+    /// Make symbolic links, and run wc through one:
+    /// ```
+    /// :~$ coreutils --install DIR
+    /// :~$ DIR/wc -w ../rwc/tests/test_files/trees.txt
+    /// ```
+    #[cfg(feature = "wc")]
+    #[test]
+    fn symbolic_links() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = install_dir("symbolic");
+        install(&dir, &[]).success().stderr(predicate::str::is_empty()).code(predicate::eq(0));
+
+        assert!(std::fs::symlink_metadata(dir.join("wc"))?.file_type().is_symlink());
+        let mut cmd = std::process::Command::new(dir.join("wc"));
+        cmd.arg("-w")
+            .arg("../rwc/tests/test_files/trees.txt")
+            .assert()
+            .success()
+            .stdout(predicate::eq(" 83 ../rwc/tests/test_files/trees.txt\n"))
+            .code(predicate::eq(0));
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    /// Make hard links, and run zcat through one:
+    /// ```
+    /// :~$ coreutils --install DIR --hardlink
+    /// :~$ DIR/zcat ../rcat/tests/test_files/trees.txt.gz
+    /// ```
+    #[cfg(feature = "cat")]
+    #[test]
+    fn hard_links() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = install_dir("hard");
+        install(&dir, &["--hardlink"]).success().code(predicate::eq(0));
+
+        assert!(std::fs::symlink_metadata(dir.join("zcat"))?.file_type().is_file());
+        let mut cmd = std::process::Command::new(dir.join("zcat"));
+        cmd.arg("../rcat/tests/test_files/trees.txt.gz")
+            .assert()
+            .success()
+            .stdout(predicate::eq(std::fs::read("../rcat/tests/test_files/trees.txt")?))
+            .code(predicate::eq(0));
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    /// Links that are already there are only replaced with --force:
+    /// ```
+    /// :~$ coreutils --install DIR
+    /// :~$ coreutils --install DIR
+    /// coreutils: DIR/cat: File exists
+    /// ```
+    #[cfg(feature = "cat")]
+    #[test]
+    fn existing_links() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = install_dir("existing");
+        install(&dir, &[]).success();
+
+        let expected = format!("coreutils: {}: File exists\n", dir.join("cat").display());
+        install(&dir, &[])
+            .failure()
+            .stderr(predicate::str::contains(expected))
+            .code(predicate::eq(1));
+        install(&dir, &["--force"]).success().stderr(predicate::str::is_empty()).code(predicate::eq(0));
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
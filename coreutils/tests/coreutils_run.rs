@@ -0,0 +1,147 @@
+/// Test picking a utility by its first argument, and --list.
+mod test_utils;
+
+#[cfg(test)]
+mod test {
+    use assert_cmd::prelude::*;
+    use predicates::prelude::*;
+
+    use crate::test_utils as tu;
+
+    /// List every name a utility can be run as:
+    /// ```
+    /// :~$ coreutils --list
+    /// ```
+    /// Output should be:
+    /// ```
+    /// bzcat
+    /// cat
+    /// wc
+    /// xzcat
+    /// zcat
+    /// zstdcat
+    /// ```
+    #[cfg(all(feature = "cat", feature = "wc"))]
+    #[test]
+    fn list() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = tu::get_cmd();
+        cmd.arg("--list")
+            .assert()
+            .success()
+            .stdout(predicate::eq("bzcat\ncat\nwc\nxzcat\nzcat\nzstdcat\n"))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Run wc with its options:
+    /// ```
+    /// :~$ coreutils wc -l ../rwc/tests/test_files/trees.txt
+    /// ```
+    /// Output should be:
+    /// ```
+    ///  21 ../rwc/tests/test_files/trees.txt
+    /// ```
+    #[cfg(feature = "wc")]
+    #[test]
+    fn run_wc() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = tu::get_cmd();
+        cmd.arg("wc")
+            .arg("-l")
+            .arg("../rwc/tests/test_files/trees.txt")
+            .assert()
+            .success()
+            .stdout(predicate::eq(" 21 ../rwc/tests/test_files/trees.txt\n"))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Run cat, and cat through one of its aliases:
+    /// ```
+    /// :~$ coreutils zcat ../rcat/tests/test_files/trees.txt.gz
+    /// ```
+    /// Output should be the same as trees.txt.
+    #[cfg(feature = "cat")]
+    #[test]
+    fn run_cat_and_zcat() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = std::fs::read("../rcat/tests/test_files/trees.txt")?;
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("cat")
+            .arg("../rcat/tests/test_files/trees.txt")
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected.clone()))
+            .code(predicate::eq(0));
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("zcat")
+            .arg("../rcat/tests/test_files/trees.txt.gz")
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Errors from the utility show its name, not coreutils:
+    /// ```
+    /// :~$ coreutils cat does_not_exist.txt
+    /// ```
+    /// Output should be:
+    /// ```
+    /// cat: does_not_exist.txt: No such file or directory
+    /// ```
+    #[cfg(all(feature = "cat", target_os = "linux"))]
+    #[test]
+    fn utility_error() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = tu::get_cmd();
+        cmd.arg("cat")
+            .arg("does_not_exist.txt")
+            .assert()
+            .failure()
+            .stderr(predicate::eq("cat: does_not_exist.txt: No such file or directory\n"))
+            .code(predicate::eq(1));
+
+        Ok(())
+    }
+
+    /// A name that isn't a utility is an error:
+    /// ```
+    /// :~$ coreutils no_such_util
+    /// ```
+    /// Output should be:
+    /// ```
+    /// coreutils: unknown program 'no_such_util'
+    /// ```
+    #[test]
+    fn unknown_program() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = tu::get_cmd();
+        cmd.arg("no_such_util")
+            .arg("-l")
+            .assert()
+            .failure()
+            .stdout(predicate::str::is_empty())
+            .stderr(predicate::eq("coreutils: unknown program 'no_such_util'\n"))
+            .code(predicate::eq(1));
+
+        Ok(())
+    }
+
+    /// With no arguments at all, show the help and fail:
+    /// ```
+    /// :~$ coreutils
+    /// ```
+    #[test]
+    fn no_arguments() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = tu::get_cmd();
+        cmd.assert()
+            .failure()
+            .stderr(predicate::str::contains("--list"))
+            .code(predicate::eq(1));
+
+        Ok(())
+    }
+}
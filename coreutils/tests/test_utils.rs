@@ -0,0 +1,7 @@
+use std::process::Command;
+
+use assert_cmd::prelude::CommandCargoExt;
+
+pub fn get_cmd() -> Command {
+    Command::cargo_bin("coreutils").unwrap()
+}
@@ -0,0 +1,252 @@
+//! Running a utility, and capturing what it did.
+//!
+//! `UCmd` holds a command line, and maybe the standard input and `Scene` to run it
+//! with. The same command can be run with the utility built by cargo, or with the
+//! system's GNU one, to compare them.
+//!
+//! Both are run with `argv[0]` set to the utility's name, so messages start with
+//! `wc:` and not with a path. GNU is run in a UTF-8 locale, and in a copy of the
+//! scene, in case the command changes it.
+
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use shared::quote::QuotingStyle;
+
+use crate::golden::{self, Golden};
+use crate::scene::Scene;
+
+/// Environment variables that change how GNU utilities work. They're removed, so
+/// the results don't depend on who runs the tests.
+const CLEARED_ENV: [&str; 5] = ["POSIXLY_CORRECT", "QUOTING_STYLE", "LANGUAGE", "LANG", "LC_ALL"];
+
+/// A command line for a utility.
+#[derive(Debug, Clone)]
+pub struct UCmd<'a> {
+    /// Name of the binary cargo builds.
+    bin: String,
+    /// Name of the GNU utility, which is also `argv[0]`.
+    name: String,
+    args: Vec<OsString>,
+    /// Standard input, or `None` for none at all.
+    stdin: Option<Vec<u8>>,
+    /// Where to run, or `None` for the current directory.
+    scene: Option<&'a Scene>,
+}
+
+/// What a program did: everything it wrote, and its exit code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capture {
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    /// The exit code, or `None` if it was killed by a signal.
+    pub code: Option<i32>,
+}
+
+impl<'a> UCmd<'a> {
+    /// Make a command for the binary `bin`, built by cargo. The GNU utility has the
+    /// same name, unless `gnu_name()` says otherwise.
+    pub fn new(bin: &str) -> UCmd<'a> {
+        UCmd { bin: bin.to_owned(), name: bin.to_owned(), args: Vec::new(), stdin: None, scene: None }
+    }
+
+    /// Name of the GNU utility, when it isn't the same as the binary, like `cat` for `rcat`.
+    pub fn gnu_name(mut self, name: &str) -> UCmd<'a> {
+        self.name = name.to_owned();
+        self
+    }
+
+    /// Add one argument.
+    pub fn arg(mut self, arg: impl AsRef<OsStr>) -> UCmd<'a> {
+        self.args.push(arg.as_ref().to_owned());
+        self
+    }
+
+    /// Add some arguments.
+    pub fn args<I: IntoIterator<Item = T>, T: AsRef<OsStr>>(mut self, args: I) -> UCmd<'a> {
+        self.args.extend(args.into_iter().map(|arg| arg.as_ref().to_owned()));
+        self
+    }
+
+    /// Pipe `data` to standard input. Without this, standard input is empty.
+    pub fn stdin(mut self, data: impl Into<Vec<u8>>) -> UCmd<'a> {
+        self.stdin = Some(data.into());
+        self
+    }
+
+    /// Run in `scene`, instead of the current directory.
+    pub fn in_scene(mut self, scene: &'a Scene) -> UCmd<'a> {
+        self.scene = Some(scene);
+        self
+    }
+
+    /// Run the utility built by cargo.
+    pub fn run(&self) -> Capture {
+        let program = assert_cmd::cargo::cargo_bin(&self.bin);
+        let dir = self.scene.map(|scene| scene.path().to_owned());
+        self.run_program(&program, dir.as_deref(), false)
+    }
+
+    /// Run the system's GNU utility, in a copy of the scene. Returns `None` if
+    /// there's no GNU utility by that name.
+    pub fn run_gnu(&self) -> Option<Capture> {
+        let program = gnu_program(&self.name)?;
+        let copy = self.scene.map(Scene::copy);
+        Some(self.run_program(&program, copy.as_ref().map(Scene::path), true))
+    }
+
+    /// Run the utility built by cargo, and check that it did what the golden file
+    /// `case` says. With `REGENERATE_GOLDEN` set, the golden file is made first,
+    /// by running GNU.
+    ///
+    /// # Panics
+    ///
+    /// If the output is different, or there's no golden file.
+    pub fn check_golden(&self, golden: &Golden, case: &str) {
+        if golden::regenerate() {
+            let expected = self.run_gnu()
+                .unwrap_or_else(|| panic!("there's no GNU {} to make {} from", self.name, case));
+            golden.save(case, &self.to_string(), &expected);
+        }
+        let expected = golden.load(case);
+        self.run().assert_eq(&expected, &format!("{} ({})", self, golden.path(case).display()));
+    }
+
+    /// Helper method to run `program` with this command line.
+    ///
+    /// # Arguments
+    ///
+    /// * `program` - Path to the program.
+    /// * `dir` - Directory to run in, or `None` for the current one.
+    /// * `gnu` - Whether it's GNU, which needs a UTF-8 locale.
+    fn run_program(&self, program: &Path, dir: Option<&Path>, gnu: bool) -> Capture {
+        let mut cmd = Command::new(program);
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::arg0(&mut cmd, &self.name);
+        cmd.args(&self.args)
+            .env("TZ", "UTC")
+            .stdin(if self.stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        for name in CLEARED_ENV {
+            cmd.env_remove(name);
+        }
+        if gnu {
+            cmd.env("LC_ALL", "C.UTF-8");
+        }
+        if let Some(dir) = dir {
+            cmd.current_dir(dir);
+        }
+
+        let mut child = cmd.spawn().unwrap_or_else(|e| panic!("can't run {}: {}", program.display(), e));
+        // write from another thread, so a big input can't fill up a pipe the other way
+        let writer = match (child.stdin.take(), self.stdin.clone()) {
+            (Some(mut pipe), Some(data)) => Some(std::thread::spawn(move || {
+                // the program can stop reading early, and that's up to it
+                let _ = pipe.write_all(&data);
+            })),
+            _ => None,
+        };
+        let output = child.wait_with_output().unwrap_or_else(|e| panic!("can't run {}: {}", program.display(), e));
+        if let Some(writer) = writer {
+            let _ = writer.join();
+        }
+        Capture { stdout: output.stdout, stderr: output.stderr, code: output.status.code() }
+    }
+}
+
+impl fmt::Display for UCmd<'_> {
+    /// Show the command line like it would be typed into a shell, with the size of
+    /// standard input if there is one.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        for arg in &self.args {
+            write!(f, " {}", String::from_utf8_lossy(&QuotingStyle::ShellEscape.quote(arg)))?;
+        }
+        if let Some(stdin) = &self.stdin {
+            write!(f, " < ({} bytes)", stdin.len())?;
+        }
+        Ok(())
+    }
+}
+
+impl Capture {
+    /// Check that this is the same as `expected`.
+    ///
+    /// # Panics
+    ///
+    /// If they're different, showing both.
+    ///
+    /// # Arguments
+    ///
+    /// * `expected` - What the program should have done.
+    /// * `what` - What was run, to show if they're different.
+    pub fn assert_eq(&self, expected: &Capture, what: &str) {
+        if self == expected {
+            return;
+        }
+        let mut message = format!("output is different for {}\n", what);
+        for (name, actual, expected) in [("stdout", &self.stdout, &expected.stdout), ("stderr", &self.stderr, &expected.stderr)] {
+            if actual != expected {
+                message += &format!("--- expected {}:\n{}--- actual {}:\n{}", name,
+                    String::from_utf8_lossy(expected), name, String::from_utf8_lossy(actual));
+            }
+        }
+        if self.code != expected.code {
+            message += &format!("--- expected exit code {:?}, actual {:?}\n", expected.code, self.code);
+        }
+        panic!("{}", message);
+    }
+}
+
+/// Find the system's GNU utility called `name`. Returns `None` if there isn't one,
+/// or it isn't from GNU coreutils.
+pub fn gnu_program(name: &str) -> Option<PathBuf> {
+    let path = ["/usr/bin", "/bin"].iter()
+        .map(|dir| Path::new(dir).join(name))
+        .find(|path| path.exists())?;
+    let version = Command::new(&path).arg("--version").output().ok()?;
+    String::from_utf8_lossy(&version.stdout).contains("GNU coreutils").then_some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn show_command_line() {
+        let cmd = UCmd::new("rcat").gnu_name("cat").args(["-n", "a b", "it's"]).arg("x").stdin("abc");
+        assert_eq!(cmd.to_string(), "cat -n 'a b' \"it's\" x < (3 bytes)");
+    }
+
+    #[test]
+    fn same_capture() {
+        let capture = Capture { stdout: b"1\n".to_vec(), stderr: Vec::new(), code: Some(0) };
+        capture.assert_eq(&capture.clone(), "test");
+    }
+
+    #[test]
+    #[should_panic(expected = "--- expected stdout:\n1\n--- actual stdout:\n2\n--- expected exit code Some(0), actual Some(1)")]
+    fn different_capture() {
+        let expected = Capture { stdout: b"1\n".to_vec(), stderr: Vec::new(), code: Some(0) };
+        let actual = Capture { stdout: b"2\n".to_vec(), stderr: Vec::new(), code: Some(1) };
+        actual.assert_eq(&expected, "test");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_gnu_in_a_copy() {
+        let scene = Scene::new().file("a.txt", "one\n");
+        let Some(capture) = UCmd::new("cat").args(["a.txt", "-"]).stdin("two\n").in_scene(&scene).run_gnu() else {
+            return;
+        };
+        assert_eq!(capture, Capture { stdout: b"one\ntwo\n".to_vec(), stderr: Vec::new(), code: Some(0) });
+
+        let capture = UCmd::new("cat").arg("nope").in_scene(&scene).run_gnu().unwrap();
+        assert_eq!(String::from_utf8_lossy(&capture.stderr), "cat: nope: No such file or directory\n");
+        assert_eq!(capture.code, Some(1));
+    }
+}
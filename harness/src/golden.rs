@@ -0,0 +1,183 @@
+//! Golden files: the expected output of a test, kept next to the test.
+//!
+//! Each case is one file, `<case>.golden`, in a data directory like `tests/golden`.
+//! It has the command line in a comment, then the exit code, standard output, and
+//! standard error. Each output has its length in bytes in front of it, so it can be
+//! anything at all, and still be read back exactly:
+//!
+//! ```text
+//! # wc -l trees.txt
+//! status 0
+//! stdout 13
+//! 21 trees.txt
+//!
+//! stderr 0
+//!
+//! ```
+//!
+//! Set `REGENERATE_GOLDEN` to make the files again by running the system's GNU
+//! utility. Check the differences before committing them: GNU is the reference,
+//! but a difference can be on purpose, and then the golden file is edited by hand.
+
+use std::fs;
+use std::path::PathBuf;
+
+use crate::cmd::Capture;
+
+/// Environment variable that makes `UCmd::check_golden()` write the golden files
+/// from GNU, instead of only reading them.
+pub const REGENERATE: &str = "REGENERATE_GOLDEN";
+
+/// A directory of golden files.
+#[derive(Debug, Clone)]
+pub struct Golden {
+    dir: PathBuf,
+}
+
+impl Golden {
+    /// Use the golden files in `dir`, which is relative to the crate when run by
+    /// `cargo test`.
+    pub fn new(dir: impl Into<PathBuf>) -> Golden {
+        Golden { dir: dir.into() }
+    }
+
+    /// Path of the golden file for `case`.
+    pub fn path(&self, case: &str) -> PathBuf {
+        self.dir.join(format!("{}.golden", case))
+    }
+
+    /// Read the golden file for `case`.
+    ///
+    /// # Panics
+    ///
+    /// If it isn't there, or can't be read.
+    pub fn load(&self, case: &str) -> Capture {
+        let path = self.path(case);
+        let data = fs::read(&path).unwrap_or_else(|e| panic!("can't read {}: {}. Set {} to make it from GNU.",
+            path.display(), e, REGENERATE));
+        parse(&data).unwrap_or_else(|e| panic!("{} is broken: {}", path.display(), e))
+    }
+
+    /// Write the golden file for `case`.
+    ///
+    /// # Arguments
+    ///
+    /// * `case` - Name of the case.
+    /// * `command` - The command line, for the comment at the top.
+    /// * `capture` - What the command did.
+    pub fn save(&self, case: &str, command: &str, capture: &Capture) {
+        let path = self.path(case);
+        let written = fs::create_dir_all(&self.dir).and_then(|_| fs::write(&path, format(command, capture)));
+        if let Err(e) = written {
+            panic!("can't write {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Check whether `REGENERATE_GOLDEN` is set to something other than `0`.
+pub fn regenerate() -> bool {
+    std::env::var_os(REGENERATE).is_some_and(|value| !value.is_empty() && value != "0")
+}
+
+/// Make the contents of a golden file.
+///
+/// # Arguments
+///
+/// * `command` - The command line, for the comment at the top. Newlines in it
+///   are left out, so it stays one line.
+/// * `capture` - What the command did.
+fn format(command: &str, capture: &Capture) -> Vec<u8> {
+    let status = capture.code.map_or("signal".to_owned(), |code| code.to_string());
+    let mut data = format!("# {}\nstatus {}\n", command.replace('\n', " "), status).into_bytes();
+    for (name, output) in [("stdout", &capture.stdout), ("stderr", &capture.stderr)] {
+        data.extend_from_slice(format!("{} {}\n", name, output.len()).as_bytes());
+        data.extend_from_slice(output);
+        data.push(b'\n');
+    }
+    data
+}
+
+/// Read the contents of a golden file. Returns what's wrong with it, if it can't.
+fn parse(mut data: &[u8]) -> Result<Capture, String> {
+    // comments are only at the top, since the outputs can have anything in them
+    while data.starts_with(b"#") {
+        data = split_line(data).1;
+    }
+
+    let (status, rest) = field(data, "status")?;
+    let code = match status {
+        "signal" => None,
+        code => Some(code.parse().map_err(|_| format!("bad status: {}", code))?),
+    };
+    let (stdout, rest) = output(rest, "stdout")?;
+    let (stderr, rest) = output(rest, "stderr")?;
+    if !rest.is_empty() {
+        return Err("there's more after stderr".to_owned());
+    }
+    Ok(Capture { stdout: stdout.to_vec(), stderr: stderr.to_vec(), code })
+}
+
+/// Helper method to split `data` after the first newline.
+fn split_line(data: &[u8]) -> (&[u8], &[u8]) {
+    match data.iter().position(|&b| b == b'\n') {
+        Some(i) => (&data[..i], &data[i + 1..]),
+        None => (data, &[]),
+    }
+}
+
+/// Helper method to read a `name value` line from the start of `data`. Returns the
+/// value, and what's after the line.
+fn field<'a>(data: &'a [u8], name: &str) -> Result<(&'a str, &'a [u8]), String> {
+    let (line, rest) = split_line(data);
+    let line = std::str::from_utf8(line).map_err(|_| format!("no {} line", name))?;
+    match line.split_once(' ') {
+        Some((found, value)) if found == name => Ok((value, rest)),
+        _ => Err(format!("no {} line", name)),
+    }
+}
+
+/// Helper method to read one output from the start of `data`: a `name length` line,
+/// then that many bytes, and a newline. Returns the output, and what's after it.
+fn output<'a>(data: &'a [u8], name: &str) -> Result<(&'a [u8], &'a [u8]), String> {
+    let (length, rest) = field(data, name)?;
+    let length: usize = length.parse().map_err(|_| format!("bad {} length: {}", name, length))?;
+    match (rest.get(..length), rest.get(length)) {
+        (Some(output), Some(b'\n')) => Ok((output, &rest[length + 1..])),
+        _ => Err(format!("{} isn't {} bytes long", name, length)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_and_parse() {
+        let capture = Capture { stdout: b"21 trees.txt\n".to_vec(), stderr: Vec::new(), code: Some(0) };
+        let data = format("wc -l trees.txt", &capture);
+        assert_eq!(String::from_utf8_lossy(&data),
+            "# wc -l trees.txt\nstatus 0\nstdout 13\n21 trees.txt\n\nstderr 0\n\n");
+        assert_eq!(parse(&data), Ok(capture));
+
+        // anything can be in the output, even what looks like the format itself
+        let capture = Capture { stdout: b"no newline".to_vec(), stderr: b"\nstderr 3\n\xff".to_vec(), code: None };
+        assert_eq!(parse(&format("odd\ncommand", &capture)), Ok(capture));
+    }
+
+    #[test]
+    fn broken() {
+        assert_eq!(parse(b""), Err("no status line".to_owned()));
+        assert_eq!(parse(b"status x\n"), Err("bad status: x".to_owned()));
+        assert_eq!(parse(b"status 0\nstdout 5\nab\n"), Err("stdout isn't 5 bytes long".to_owned()));
+        assert_eq!(parse(b"status 0\nstdout 0\n\nstderr 0\n\nmore"), Err("there's more after stderr".to_owned()));
+    }
+
+    #[test]
+    fn save_and_load() {
+        let golden = Golden::new(std::env::temp_dir().join(format!("harness_golden_{}", std::process::id())));
+        let capture = Capture { stdout: b"out\n".to_vec(), stderr: b"err\n".to_vec(), code: Some(1) };
+        golden.save("case", "cmd", &capture);
+        assert_eq!(golden.load("case"), capture);
+        fs::remove_dir_all(&golden.dir).unwrap();
+    }
+}
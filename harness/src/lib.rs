@@ -0,0 +1,32 @@
+//! Test harness for the utilities.
+//!
+//! Integration tests add this as a dev-dependency, and use it to run the utility
+//! and check what it did. Unit tests that need files use its scenes too, so the
+//! files are removed even when the test fails:
+//!
+//! * `scene` - A temporary directory with files, directories, and links in it.
+//! * `cmd` - Running the utility, or the system's GNU one, and capturing its output.
+//! * `golden` - Expected output kept in files, which can be made by running GNU.
+//!
+//! A test looks like this:
+//!
+//! ```no_run
+//! use harness::{Golden, Scene, UCmd};
+//!
+//! let scene = Scene::new().file("a.txt", "one two\n");
+//! UCmd::new("wc").args(["-w", "a.txt"]).in_scene(&scene)
+//!     .check_golden(&Golden::new("tests/golden"), "words");
+//! ```
+//!
+//! The first time, make the golden file from GNU wc with:
+//! ```text
+//! :~$ REGENERATE_GOLDEN=1 cargo test -p wc --test wc_golden
+//! ```
+
+pub mod cmd;
+pub mod golden;
+pub mod scene;
+
+pub use cmd::{Capture, UCmd};
+pub use golden::Golden;
+pub use scene::Scene;
@@ -0,0 +1,219 @@
+//! Temporary directories for utilities to run in.
+//!
+//! A `Scene` is an empty directory under the system's temp directory, with files,
+//! directories, symbolic links, and permissions added to it one at a time. It's
+//! removed when the `Scene` is dropped.
+//!
+//! Everything added is remembered, so `Scene::copy()` can set up the same thing
+//! again from scratch, for running a second program that shouldn't see what the
+//! first one changed.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Number for the next scene, so tests running at the same time get their own.
+static NEXT_SCENE: AtomicUsize = AtomicUsize::new(0);
+
+/// A temporary directory with things in it.
+#[derive(Debug)]
+pub struct Scene {
+    dir: PathBuf,
+    /// Everything that was added, in order.
+    entries: Vec<Entry>,
+}
+
+/// One thing added to a scene. Paths are inside the scene.
+#[derive(Debug, Clone)]
+enum Entry {
+    File(PathBuf, Vec<u8>),
+    Dir(PathBuf),
+    /// A link, and what it points at.
+    Symlink(PathBuf, PathBuf),
+    /// Permission bits, like `0o644`.
+    #[cfg(unix)]
+    Mode(PathBuf, u32),
+}
+
+impl Scene {
+    /// Make an empty scene.
+    ///
+    /// # Panics
+    ///
+    /// If the directory can't be made. It's a test, so there's nothing else to do.
+    pub fn new() -> Scene {
+        let number = NEXT_SCENE.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!("harness_{}_{}", std::process::id(), number));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap_or_else(|e| panic!("can't make {}: {}", dir.display(), e));
+        Scene { dir, entries: Vec::new() }
+    }
+
+    /// Add a file. Directories it's in are made if they aren't there yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Where to put it, inside the scene.
+    /// * `contents` - What to put in it.
+    pub fn file(self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Scene {
+        self.add(Entry::File(path.as_ref().to_owned(), contents.as_ref().to_vec()))
+    }
+
+    /// Add a copy of a file from somewhere else, usually one of the crate's test files.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Where to put it, inside the scene.
+    /// * `source` - The file to copy, relative to the crate when run by `cargo test`.
+    pub fn file_from(self, path: impl AsRef<Path>, source: impl AsRef<Path>) -> Scene {
+        let source = source.as_ref();
+        let contents = fs::read(source).unwrap_or_else(|e| panic!("can't read {}: {}", source.display(), e));
+        self.file(path, contents)
+    }
+
+    /// Add a directory, and any directories it's in.
+    pub fn dir(self, path: impl AsRef<Path>) -> Scene {
+        self.add(Entry::Dir(path.as_ref().to_owned()))
+    }
+
+    /// Add a symbolic link. It can point at something that isn't there.
+    ///
+    /// # Arguments
+    ///
+    /// * `link` - Where to put the link, inside the scene.
+    /// * `target` - What the link points at, as it's stored in the link.
+    pub fn symlink(self, link: impl AsRef<Path>, target: impl AsRef<Path>) -> Scene {
+        self.add(Entry::Symlink(link.as_ref().to_owned(), target.as_ref().to_owned()))
+    }
+
+    /// Set the permission bits of something already in the scene, like `0o000`.
+    ///
+    /// Tests that check a file can't be read shouldn't count on it when they run as
+    /// root, since root can read it anyway.
+    #[cfg(unix)]
+    pub fn mode(self, path: impl AsRef<Path>, mode: u32) -> Scene {
+        self.add(Entry::Mode(path.as_ref().to_owned(), mode))
+    }
+
+    /// The scene's directory.
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Path of something in the scene.
+    pub fn join(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.dir.join(path)
+    }
+
+    /// Read a file in the scene, like one a utility wrote.
+    pub fn read(&self, path: impl AsRef<Path>) -> Vec<u8> {
+        let path = self.join(path);
+        fs::read(&path).unwrap_or_else(|e| panic!("can't read {}: {}", path.display(), e))
+    }
+
+    /// Make a new scene with everything that was added to this one, but nothing
+    /// that was changed in it since.
+    pub fn copy(&self) -> Scene {
+        self.entries.iter().cloned().fold(Scene::new(), Scene::add)
+    }
+
+    /// Helper method to make `entry` in the directory, and remember it.
+    fn add(mut self, entry: Entry) -> Scene {
+        let made = match &entry {
+            Entry::File(path, contents) => self.make_parent(path).and_then(|_| fs::write(self.join(path), contents)),
+            Entry::Dir(path) => fs::create_dir_all(self.join(path)),
+            Entry::Symlink(link, target) => self.make_parent(link).and_then(|_| symlink(target, &self.join(link))),
+            #[cfg(unix)]
+            Entry::Mode(path, mode) => {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(self.join(path), fs::Permissions::from_mode(*mode))
+            },
+        };
+        if let Err(e) = made {
+            panic!("can't set up {:?} in {}: {}", entry, self.dir.display(), e);
+        }
+        self.entries.push(entry);
+        self
+    }
+
+    /// Helper method to make the directories `path` is in.
+    fn make_parent(&self, path: &Path) -> std::io::Result<()> {
+        match self.join(path).parent() {
+            Some(parent) => fs::create_dir_all(parent),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for Scene {
+    fn default() -> Scene {
+        Scene::new()
+    }
+}
+
+impl Drop for Scene {
+    fn drop(&mut self) {
+        // a directory without write or search permission can't be emptied
+        #[cfg(unix)]
+        for entry in &self.entries {
+            if let Entry::Mode(path, _) = entry {
+                use std::os::unix::fs::PermissionsExt;
+                let _ = fs::set_permissions(self.join(path), fs::Permissions::from_mode(0o700));
+            }
+        }
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[cfg(unix)]
+fn symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_file(target, link)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_and_copy() {
+        let scene = Scene::new()
+            .file("a.txt", "one\n")
+            .file("sub/deeper/b.txt", b"two\n")
+            .dir("empty/dir")
+            .symlink("link", "a.txt")
+            .symlink("sub/broken", "nowhere");
+        assert_eq!(scene.read("a.txt"), b"one\n");
+        assert_eq!(scene.read("sub/deeper/b.txt"), b"two\n");
+        assert!(scene.join("empty/dir").is_dir());
+        assert_eq!(scene.read("link"), b"one\n");
+        assert!(fs::symlink_metadata(scene.join("sub/broken")).is_ok());
+
+        fs::write(scene.join("a.txt"), "changed\n").unwrap();
+        let copy = scene.copy();
+        assert_ne!(copy.path(), scene.path());
+        assert_eq!(copy.read("a.txt"), b"one\n");
+        assert_eq!(copy.read("link"), b"one\n");
+
+        let dir = scene.path().to_owned();
+        drop(scene);
+        assert!(!dir.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let scene = Scene::new().file("locked/secret", "x").mode("locked/secret", 0o400).mode("locked", 0o000);
+        let mode = fs::symlink_metadata(scene.join("locked")).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0);
+        let dir = scene.path().to_owned();
+        drop(scene);
+        assert!(!dir.exists());
+    }
+}
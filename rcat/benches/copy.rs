@@ -0,0 +1,67 @@
+//! Compare the throughput of `zero_copy()` with the plain read/write loop.
+//!
+//! Run with `cargo bench -p rcat`. Each benchmark copies a 64 MiB file, either to
+//! another file or to `/dev/null`.
+
+use std::fs::{File, OpenOptions};
+use std::io::{Seek, SeekFrom};
+use std::path::PathBuf;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+use rcat::copy;
+
+/// Size of the file that gets copied.
+const FILE_SIZE: usize = 64 * 1024 * 1024;
+
+/// Make the file to copy, and return its path.
+fn input_file() -> PathBuf {
+    let path = std::env::temp_dir().join(format!("rcat_bench_{}.dat", std::process::id()));
+    let data: Vec<u8> = (0..FILE_SIZE).map(|i| (i % 251) as u8).collect();
+    std::fs::write(&path, data).unwrap();
+    path
+}
+
+/// Copy `input` to a fresh `output` each time, first with the plain loop and then
+/// (on Linux) with `zero_copy()`.
+fn bench_copy_to(c: &mut Criterion, name: &str, input: &PathBuf, output: &PathBuf) {
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Bytes(FILE_SIZE as u64));
+    group.sample_size(20);
+
+    let open_both = || {
+        let input = File::open(input).unwrap();
+        let mut output = OpenOptions::new().write(true).create(true).truncate(true).open(output).unwrap();
+        output.seek(SeekFrom::Start(0)).unwrap();
+        (input, output)
+    };
+
+    group.bench_function("read_write", |b| b.iter(|| {
+        let (mut input, mut output) = open_both();
+        copy::read_write(&mut input, &mut output).unwrap();
+    }));
+
+    #[cfg(target_os = "linux")]
+    group.bench_function("zero_copy", |b| b.iter(|| {
+        use std::os::fd::AsFd;
+
+        let (input, output) = open_both();
+        assert!(copy::zero_copy(input.as_fd(), output.as_fd()));
+    }));
+
+    group.finish();
+}
+
+fn bench_copy(c: &mut Criterion) {
+    let input = input_file();
+    let output = input.with_extension("out");
+
+    bench_copy_to(c, "file_to_file", &input, &output);
+    bench_copy_to(c, "file_to_dev_null", &input, &PathBuf::from("/dev/null"));
+
+    std::fs::remove_file(&input).unwrap();
+    let _ = std::fs::remove_file(&output);
+}
+
+criterion_group!(benches, bench_copy);
+criterion_main!(benches);
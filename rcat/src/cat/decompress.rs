@@ -0,0 +1,169 @@
+//! Reading compressed input, for `--decompress` (and when cat is run as `zcat`).
+//!
+//! The first few bytes of each input are checked for the magic number of gzip,
+//! zstd, bzip2, or xz. Compressed input is decompressed as it's read. Anything
+//! else is copied as it is, so plain and compressed files can be mixed.
+//!
+//! Each format is behind a cargo feature of the same name. All of them are on by
+//! default.
+//!
+//! An input can hold more than one compressed stream one after another, like
+//! `cat a.gz b.gz > both.gz` makes. All of them are decompressed, the same as zcat does.
+
+use std::io::{self, Cursor, ErrorKind, Read};
+
+/// Program names that turn on `--decompress`, when cat is run through a link with that name.
+pub(crate) const ALIASES: [&str; 4] = ["zcat", "zstdcat", "bzcat", "xzcat"];
+
+/// Compressed formats that can be recognized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Format {
+    Gzip,
+    Zstd,
+    Bzip2,
+    Xz,
+}
+
+/// Magic numbers at the start of each format.
+const MAGIC: [(Format, &[u8]); 4] = [
+    (Format::Gzip, b"\x1f\x8b"),
+    (Format::Zstd, b"\x28\xb5\x2f\xfd"),
+    (Format::Bzip2, b"BZh"),
+    (Format::Xz, b"\xfd7zXZ\x00"),
+];
+
+/// Length of the longest magic number.
+const MAGIC_LEN: usize = 6;
+
+impl Format {
+    /// Find the format from the first bytes of an input. Returns `None` if it
+    /// doesn't look compressed.
+    pub(crate) fn sniff(start: &[u8]) -> Option<Format> {
+        MAGIC.iter().find(|(_, magic)| start.starts_with(magic)).map(|(format, _)| *format)
+    }
+
+    /// Name of the cargo feature that reads this format.
+    fn feature(&self) -> &'static str {
+        match self {
+            Format::Gzip => "gzip",
+            Format::Zstd => "zstd",
+            Format::Bzip2 => "bzip2",
+            Format::Xz => "xz",
+        }
+    }
+}
+
+/// Check the start of `input` and return a reader that gives the decompressed data,
+/// or the data as it is if it isn't compressed.
+///
+/// # Arguments
+///
+/// * `input` - The input, at its start.
+pub(crate) fn open<'a>(mut input: Box<dyn Read + 'a>) -> io::Result<Box<dyn Read + 'a>> {
+    // Read the magic number first. A pipe can give less than was asked for, so keep
+    // reading until there's enough, or there's no more.
+    let mut start = Vec::with_capacity(MAGIC_LEN);
+    while start.len() < MAGIC_LEN {
+        let mut byte = [0u8; MAGIC_LEN];
+        let want = MAGIC_LEN - start.len();
+        match input.read(&mut byte[..want]) {
+            Ok(0) => break,
+            Ok(n) => start.extend_from_slice(&byte[..n]),
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    let format = Format::sniff(&start);
+    // put the magic number back in front of the rest of the input
+    let whole: Box<dyn Read + 'a> = Box::new(Cursor::new(start).chain(input));
+    match format {
+        None => Ok(whole),
+        Some(format) => decoder(format, whole),
+    }
+}
+
+/// Wrap `input` in a decoder for `format`.
+fn decoder<'a>(format: Format, input: Box<dyn Read + 'a>) -> io::Result<Box<dyn Read + 'a>> {
+    match format {
+        #[cfg(feature = "gzip")]
+        Format::Gzip => Ok(Box::new(flate2::read::MultiGzDecoder::new(input))),
+        #[cfg(feature = "zstd")]
+        Format::Zstd => Ok(Box::new(zstd::stream::read::Decoder::new(input)?)),
+        #[cfg(feature = "bzip2")]
+        Format::Bzip2 => Ok(Box::new(bzip2::read::MultiBzDecoder::new(input))),
+        #[cfg(feature = "xz")]
+        Format::Xz => Ok(Box::new(liblzma::read::XzDecoder::new_multi_decoder(input))),
+        #[allow(unreachable_patterns)]
+        _ => {
+            drop(input);
+            Err(io::Error::new(ErrorKind::Unsupported,
+                format!("compressed with {}, which this cat was built without", format.feature())))
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A reader that hands out one byte at a time, like a slow pipe.
+    struct OneByte<'a>(&'a [u8]);
+
+    impl Read for OneByte<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.0.split_first() {
+                Some((b, rest)) if !buf.is_empty() => {
+                    buf[0] = *b;
+                    self.0 = rest;
+                    Ok(1)
+                },
+                _ => Ok(0),
+            }
+        }
+    }
+
+    fn read_all(input: Box<dyn Read + '_>) -> io::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        open(input)?.read_to_end(&mut out)?;
+        Ok(out)
+    }
+
+    #[test]
+    fn sniff_magic_numbers() {
+        assert_eq!(Format::sniff(b"\x1f\x8b\x08\x00"), Some(Format::Gzip));
+        assert_eq!(Format::sniff(b"\x28\xb5\x2f\xfd\x04"), Some(Format::Zstd));
+        assert_eq!(Format::sniff(b"BZh91AY"), Some(Format::Bzip2));
+        assert_eq!(Format::sniff(b"\xfd7zXZ\x00\x00"), Some(Format::Xz));
+        assert_eq!(Format::sniff(b"\xfd7zXY"), None);
+        assert_eq!(Format::sniff(b"BZ"), None);
+        assert_eq!(Format::sniff(b""), None);
+    }
+
+    #[test]
+    fn plain_input_is_unchanged() {
+        for data in [&b""[..], b"B", b"BZ", b"plain text\n"] {
+            assert_eq!(read_all(Box::new(OneByte(data))).unwrap(), data);
+        }
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip_members_from_a_slow_pipe() {
+        use std::io::Write;
+
+        let mut data = Vec::new();
+        for text in [&b"first\n"[..], b"second\n"] {
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(text).unwrap();
+            data.extend(encoder.finish().unwrap());
+        }
+        assert_eq!(read_all(Box::new(OneByte(&data))).unwrap(), b"first\nsecond\n");
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn corrupt_gzip_is_an_error() {
+        assert!(read_all(Box::new(&b"\x1f\x8b\x08\x00garbage"[..])).is_err());
+    }
+}
@@ -0,0 +1,228 @@
+//! Changing the output for the display options: `-n`, `-b`, `-s`, `-E`, `-T`, and `-v`.
+//!
+//! Everything works on raw bytes, a buffer at a time, so files don't need to be
+//! text and are never read into memory all at once. A `Formatter` keeps its state
+//! from one file to the next, so line numbers and blank lines carry on across files
+//! the same way they do in the standard cat.
+
+use crate::Cli;
+
+/// Display options, taken from the command line.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Options {
+    /// Number every line (`-n`).
+    pub(crate) number: bool,
+    /// Number lines that aren't empty (`-b`). Overrides `number`.
+    pub(crate) number_nonblank: bool,
+    /// Leave out repeated empty lines (`-s`).
+    pub(crate) squeeze_blank: bool,
+    /// Show `$` at the end of each line (`-E`).
+    pub(crate) show_ends: bool,
+    /// Show tabs as `^I` (`-T`).
+    pub(crate) show_tabs: bool,
+    /// Show other non-printing bytes with `^` and `M-` notation (`-v`).
+    pub(crate) show_nonprinting: bool,
+}
+
+impl Options {
+    /// Get the display options from the command line arguments.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - Command line arguments. `-A`, `-e`, and `-t` should already have
+    ///   been turned into the switches they stand for.
+    pub(crate) fn from_args(args: &Cli) -> Options {
+        Options {
+            number: args.number,
+            number_nonblank: args.number_nonblank,
+            squeeze_blank: args.squeeze_blank,
+            show_ends: args.show_ends,
+            show_tabs: args.show_tabs,
+            show_nonprinting: args.show_nonprinting,
+        }
+    }
+
+    /// Check whether any option changes the output. If none do, files can be
+    /// copied without looking at them.
+    pub(crate) fn any(&self) -> bool {
+        *self != Options::default()
+    }
+}
+
+/// Turns input into display output, a buffer at a time.
+#[derive(Debug)]
+pub(crate) struct Formatter {
+    options: Options,
+    /// Number of the last line that was numbered.
+    line_number: u64,
+    /// Whether the next byte starts a new line.
+    at_line_start: bool,
+    /// Number of empty lines in a row, just before this point.
+    empty_lines: usize,
+    /// Whether a `\r` is being held back, to see if it's part of a `\r\n` line
+    /// ending. With `-E`, those are shown as `^M$`.
+    pending_cr: bool,
+}
+
+impl Formatter {
+    pub(crate) fn new(options: Options) -> Formatter {
+        Formatter {
+            options,
+            line_number: 0,
+            at_line_start: true,
+            empty_lines: 0,
+            pending_cr: false,
+        }
+    }
+
+    /// Format `input` and add the result to `out`.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The next piece of input. It can end anywhere, even in the middle of a line.
+    /// * `out` - Where to add the formatted output.
+    pub(crate) fn format(&mut self, input: &[u8], out: &mut Vec<u8>) {
+        for &b in input {
+            if self.pending_cr {
+                self.pending_cr = false;
+                if b == b'\n' {
+                    out.extend_from_slice(b"^M");
+                } else {
+                    out.push(b'\r');
+                }
+            }
+
+            if b == b'\n' {
+                if self.at_line_start {
+                    self.empty_lines += 1;
+                    if self.options.squeeze_blank && self.empty_lines > 1 {
+                        continue;
+                    }
+                    if self.options.number && !self.options.number_nonblank {
+                        self.push_line_number(out);
+                    }
+                }
+                if self.options.show_ends {
+                    out.push(b'$');
+                }
+                out.push(b'\n');
+                self.at_line_start = true;
+                continue;
+            }
+
+            if self.at_line_start {
+                if self.options.number || self.options.number_nonblank {
+                    self.push_line_number(out);
+                }
+                self.at_line_start = false;
+                self.empty_lines = 0;
+            }
+
+            if b == b'\r' && self.options.show_ends && !self.options.show_nonprinting {
+                self.pending_cr = true;
+            } else {
+                self.push_byte(b, out);
+            }
+        }
+    }
+
+    /// Add anything still held back to `out`. Call this after the last input.
+    pub(crate) fn finish(&mut self, out: &mut Vec<u8>) {
+        if self.pending_cr {
+            self.pending_cr = false;
+            out.push(b'\r');
+        }
+    }
+
+    /// Add the next line number, right aligned in 6 columns and followed by a tab.
+    fn push_line_number(&mut self, out: &mut Vec<u8>) {
+        self.line_number += 1;
+        out.extend_from_slice(format!("{:>6}\t", self.line_number).as_bytes());
+    }
+
+    /// Add one byte that isn't a newline, using `^` and `M-` notation if needed.
+    fn push_byte(&self, b: u8, out: &mut Vec<u8>) {
+        if b == b'\t' {
+            if self.options.show_tabs {
+                out.extend_from_slice(b"^I");
+            } else {
+                out.push(b'\t');
+            }
+        } else if self.options.show_nonprinting {
+            push_visible(b, out);
+        } else {
+            out.push(b);
+        }
+    }
+}
+
+/// Add `b` to `out` in the notation the standard cat uses for `-v`: `^X` for control
+/// characters, `^?` for delete, and `M-` in front of bytes with the high bit set.
+pub(crate) fn push_visible(b: u8, out: &mut Vec<u8>) {
+    let low = if b >= 128 {
+        out.extend_from_slice(b"M-");
+        b - 128
+    } else {
+        b
+    };
+    match low {
+        // tab and newline are only left alone without the high bit
+        b'\t' | b'\n' if b < 128 => out.push(low),
+        0..=31 => out.extend_from_slice(&[b'^', low + 64]),
+        127 => out.extend_from_slice(b"^?"),
+        _ => out.push(low),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper method to format some pieces of input with one `Formatter`.
+    fn format(options: Options, pieces: &[&[u8]]) -> String {
+        let mut formatter = Formatter::new(options);
+        let mut out = Vec::new();
+        pieces.iter().for_each(|p| formatter.format(p, &mut out));
+        formatter.finish(&mut out);
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn visible_notation() {
+        let mut out = Vec::new();
+        [0u8, 1, 27, 31, b' ', b'~', 127, 128, 137, 160, 255].iter().for_each(|b| push_visible(*b, &mut out));
+        assert_eq!(String::from_utf8(out).unwrap(), "^@^A^[^_ ~^?M-^@M-^IM- M-^?");
+    }
+
+    #[test]
+    fn number_continues_across_pieces() {
+        let number = Options { number: true, ..Default::default() };
+        assert_eq!(format(number, &[b"a\nb", b"c\n\nd"]), "     1\ta\n     2\tbc\n     3\t\n     4\td");
+    }
+
+    #[test]
+    fn number_nonblank_overrides_number() {
+        let options = Options { number: true, number_nonblank: true, ..Default::default() };
+        assert_eq!(format(options, &[b"a\n\nb\n"]), "     1\ta\n\n     2\tb\n");
+    }
+
+    #[test]
+    fn squeeze_across_pieces() {
+        let squeeze = Options { squeeze_blank: true, ..Default::default() };
+        assert_eq!(format(squeeze, &[b"\n\n\nx\n\n", b"\n\ny\n"]), "\nx\n\ny\n");
+    }
+
+    #[test]
+    fn show_ends_with_crlf() {
+        let ends = Options { show_ends: true, ..Default::default() };
+        assert_eq!(format(ends, &[b"a\r", b"\nb\r\r\nc\r"]), "a^M$\nb\r^M$\nc\r");
+    }
+
+    #[test]
+    fn show_tabs_and_nonprinting() {
+        let tabs = Options { show_tabs: true, ..Default::default() };
+        assert_eq!(format(tabs, &[b"\ta\x01\n"]), "^Ia\x01\n");
+        let nonprinting = Options { show_nonprinting: true, ..Default::default() };
+        assert_eq!(format(nonprinting, &[b"\ta\x01\r\n"]), "\ta^A^M\n");
+    }
+}
@@ -0,0 +1,424 @@
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::process::ExitCode;
+
+use shared::error::{ExitStatus, UError};
+use shared::input::{Input, Operands};
+use shared::output::write_error;
+use shared::quote::quote_name;
+
+use crate::copy::{self, CopyError, BUFFER_SIZE};
+use crate::Cli;
+use display::{Formatter, Options};
+use pretty::Pretty;
+use range::{Selection, Unit};
+
+pub(crate) use decompress::ALIASES;
+pub(crate) use pretty::When as PrettyWhen;
+pub(crate) use range::{parse_span, Span};
+
+mod decompress;
+mod display;
+mod pretty;
+mod range;
+
+/// Ways that copying one operand can fail.
+#[derive(Debug)]
+enum CatError {
+    /// The operand couldn't be read. Reported, then cat goes on to the next operand.
+    Input(io::Error),
+    /// The operand is the same file that standard output writes to, so copying it
+    /// would never end.
+    InputIsOutput,
+    /// Standard output couldn't be written. There's no point going on after this.
+    Output(io::Error),
+}
+
+/// How input is changed on its way to standard output.
+enum Transform {
+    /// Not at all.
+    None,
+    /// By the display options, like `-n` and `-v`.
+    Display(Formatter),
+    /// By `--pretty`.
+    Pretty(Pretty),
+}
+
+impl From<CopyError> for CatError {
+    fn from(e: CopyError) -> CatError {
+        match e {
+            CopyError::Read(e) => CatError::Input(e),
+            CopyError::Write(e) => CatError::Output(e),
+        }
+    }
+}
+
+/// Main entry point for cat. Copy each file in `args.files` to standard output, in order.
+/// With no files, copy standard input. A file named `-` also means standard input.
+///
+/// With any of the display options, the output is changed as it's copied (see `display`).
+/// `--pretty` takes the place of the display options when it's on (see `pretty`).
+/// `--lines` and `--bytes` pick out part of the input before any of that (see `range`).
+/// Files that can't be read are reported on standard error, and the rest are still copied.
+/// Returns the exit code: success only if everything was copied.
+///
+/// If standard output is a pipe that gets closed early, like `cat big_file | head`,
+/// cat stops without an error message, the same as the standard cat does when
+/// `SIGPIPE` ends it (see `shared::output`).
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments.
+pub(crate) fn cat(args: &Cli) -> ExitCode {
+    let mut status = ExitStatus::new(&args.util_name);
+
+    let options = Options::from_args(args);
+    let mut transform = if args.pretty.is_some_and(PrettyWhen::enabled) {
+        Transform::Pretty(Pretty::default())
+    } else if options.any() {
+        Transform::Display(Formatter::new(options))
+    } else {
+        Transform::None
+    };
+    let mut selection = match (args.lines, args.bytes) {
+        (Some(span), _) => Some(Selection::new(Unit::Lines, span)),
+        (None, Some(span)) => Some(Selection::new(Unit::Bytes, span)),
+        (None, None) => None,
+    };
+    let output = OutputFile::stdout();
+    // not `shared::output::Output`, whose big buffer would hold back lines read from a terminal
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    let mut unbuffered = Unbuffered(&mut stdout);
+    let out: &mut dyn Write = if args.unbuffered { &mut unbuffered } else { unbuffered.0 };
+
+    // once writing fails, stop, since there's nowhere left to copy to
+    let mut result = Ok(());
+    for input in Operands::new(args.files.iter().cloned()) {
+        if selection.as_ref().is_some_and(Selection::is_done) {
+            break;
+        }
+        let error = match input {
+            Ok(input) => {
+                let label = quote_name(input.label());
+                match cat_input(input, args.decompress, &output, &mut transform, selection.as_mut(), out) {
+                    Ok(()) => continue,
+                    Err(CatError::Input(e)) => UError::io(&label, &e),
+                    Err(CatError::InputIsOutput) => UError::new("input file is output file").with_context(label),
+                    Err(CatError::Output(e)) => {
+                        result = Err(e);
+                        break;
+                    },
+                }
+            },
+            Err(e) => UError::from(e),
+        };
+        // keep the output before the error in front of it, if both go to a terminal
+        result = out.flush();
+        status.report(&error);
+        if result.is_err() {
+            break;
+        }
+    }
+
+    if let (Ok(()), Transform::Display(f)) = (&result, &mut transform) {
+        let mut rest = Vec::new();
+        f.finish(&mut rest);
+        result = out.write_all(&rest);
+    }
+    if let Some(e) = result.and_then(|_| out.flush()).err().and_then(|e| write_error(&e)) {
+        status.report(&e);
+    }
+    status.into()
+}
+
+/// Output for `-u`. Everything written is flushed straight away, so a partial line
+/// shows up as soon as it's read, instead of waiting for the end of the line.
+struct Unbuffered<'a>(&'a mut dyn Write);
+
+impl Write for Unbuffered<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.0.write(buf)?;
+        self.0.flush()?;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// Copy one operand, already opened, to `out`.
+///
+/// # Arguments
+///
+/// * `input` - The operand, from the command line.
+/// * `decompress` - Whether to decompress the operand if it's compressed.
+/// * `output` - What standard output is connected to, used to catch a file being
+///   copied onto itself.
+/// * `transform` - How to change the input on the way out.
+/// * `selection` - The part of the input to copy, if not all of it.
+/// * `out` - Where to write.
+fn cat_input(input: Input, decompress: bool, output: &OutputFile, transform: &mut Transform,
+    mut selection: Option<&mut Selection>, out: &mut dyn Write) -> Result<(), CatError> {
+    let plain = !decompress && matches!(transform, Transform::None) && selection.is_none();
+    // nothing has been read from `input` yet, so its file can be used directly
+    match input.file() {
+        None => {
+            if output.is_stdin() {
+                return Err(CatError::InputIsOutput);
+            }
+            if plain && zero_copy(&io::stdin(), out)? {
+                return Ok(());
+            }
+        },
+        Some(file) => {
+            if output.is_same_as(file) {
+                return Err(CatError::InputIsOutput);
+            }
+            // byte ranges count compressed files by what they decompress to, so they can't seek
+            if let Some(selection) = selection.as_mut().filter(|_| !decompress) {
+                selection.seek(file).map_err(CatError::Input)?;
+            }
+            if plain && zero_copy(file, out)? {
+                return Ok(());
+            }
+        },
+    }
+    let label = input.label().to_owned();
+    let input: Box<dyn Read> = Box::new(input);
+
+    let mut input = if decompress {
+        decompress::open(input).map_err(CatError::Input)?
+    } else {
+        input
+    };
+    let mut selected;
+    let input: &mut dyn Read = match selection {
+        Some(selection) => {
+            selected = selection.reader(&mut input);
+            &mut selected
+        },
+        None => &mut input,
+    };
+    match transform {
+        Transform::None => copy(input, None, out),
+        Transform::Display(formatter) => copy(input, Some(formatter), out),
+        Transform::Pretty(pretty) => Ok(pretty.write_file(&label, input, out)?),
+    }
+}
+
+/// Try to copy `input` to standard output without reading it into a buffer (see
+/// `copy::zero_copy()`). Returns `Ok(true)` if that worked, or `Ok(false)` if the
+/// rest of `input` still needs to be copied the usual way.
+///
+/// # Arguments
+///
+/// * `input` - What to copy.
+/// * `out` - Standard output. It's flushed first, so nothing written to it before is out of order.
+#[cfg(target_os = "linux")]
+fn zero_copy(input: &impl std::os::fd::AsFd, out: &mut dyn Write) -> Result<bool, CatError> {
+    use std::os::fd::AsFd;
+
+    out.flush().map_err(CatError::Output)?;
+    Ok(copy::zero_copy(input.as_fd(), io::stdout().as_fd()))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn zero_copy<T>(_input: &T, _out: &mut dyn Write) -> Result<bool, CatError> {
+    Ok(false)
+}
+
+/// Copy everything from `input` to `out`, a buffer at a time, through `formatter` if
+/// there is one. Interrupted reads are tried again.
+fn copy(input: &mut dyn Read, formatter: Option<&mut Formatter>, out: &mut dyn Write)
+    -> Result<(), CatError> {
+    let Some(formatter) = formatter else {
+        return Ok(copy::read_write(input, out)?);
+    };
+
+    let mut buffer = vec![0; BUFFER_SIZE];
+    let mut formatted = Vec::new();
+    loop {
+        let n = copy::read_retry(input, &mut buffer).map_err(CatError::Input)?;
+        if n == 0 {
+            return Ok(());
+        }
+        formatted.clear();
+        formatter.format(&buffer[..n], &mut formatted);
+        out.write_all(&formatted).map_err(CatError::Output)?;
+    }
+}
+
+/// The file that standard output writes to, if it's a regular file.
+///
+/// Copying a file onto itself (`cat file >> file`) would keep reading what it just
+/// wrote and never finish, so the standard cat refuses to do it. Files are compared
+/// by device and inode, so this only works on Unix. Elsewhere the check is skipped.
+struct OutputFile {
+    #[cfg(unix)]
+    id: Option<(u64, u64)>,
+}
+
+impl OutputFile {
+    #[cfg(unix)]
+    fn stdout() -> OutputFile {
+        use std::os::fd::AsFd;
+        use std::os::unix::fs::MetadataExt;
+
+        let id = io::stdout().as_fd().try_clone_to_owned()
+            .and_then(|fd| File::from(fd).metadata())
+            .ok()
+            .filter(|m| m.is_file())
+            .map(|m| (m.dev(), m.ino()));
+        OutputFile { id }
+    }
+
+    #[cfg(not(unix))]
+    fn stdout() -> OutputFile {
+        OutputFile {}
+    }
+
+    /// Check whether `input` is the output file and still has data left to read.
+    /// An input that's already at its end, like an empty file, can safely be copied.
+    #[cfg(unix)]
+    fn is_same_as(&self, input: &File) -> bool {
+        use std::io::Seek;
+        use std::os::unix::fs::MetadataExt;
+
+        let Some(id) = self.id else {
+            return false;
+        };
+        let Ok(meta) = input.metadata() else {
+            return false;
+        };
+        let mut input = input;
+        (meta.dev(), meta.ino()) == id
+            && input.stream_position().is_ok_and(|pos| pos < meta.len())
+    }
+
+    #[cfg(not(unix))]
+    fn is_same_as(&self, _input: &File) -> bool {
+        false
+    }
+
+    /// Check whether standard input is the output file, like `cat < file >> file`.
+    #[cfg(unix)]
+    fn is_stdin(&self) -> bool {
+        use std::os::fd::AsFd;
+
+        self.id.is_some()
+            && io::stdin().as_fd().try_clone_to_owned()
+                .is_ok_and(|fd| self.is_same_as(&File::from(fd)))
+    }
+
+    #[cfg(not(unix))]
+    fn is_stdin(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::ErrorKind;
+
+    /// A reader that fails after handing out some data.
+    struct FailingReader {
+        data: &'static [u8],
+    }
+
+    impl Read for FailingReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.data.is_empty() {
+                return Err(io::Error::other("read failed"));
+            }
+            let n = self.data.len().min(buf.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    /// A reader and writer that get interrupted every other call, and only move a
+    /// few bytes at a time when they aren't.
+    struct Interrupted<T> {
+        inner: T,
+        calls: usize,
+    }
+
+    impl<T> Interrupted<T> {
+        fn new(inner: T) -> Interrupted<T> {
+            Interrupted { inner, calls: 0 }
+        }
+
+        fn interrupt(&mut self) -> bool {
+            self.calls += 1;
+            self.calls % 2 == 1
+        }
+    }
+
+    impl<T: Read> Read for Interrupted<T> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.interrupt() {
+                return Err(ErrorKind::Interrupted.into());
+            }
+            let n = buf.len().min(5);
+            self.inner.read(&mut buf[..n])
+        }
+    }
+
+    impl<T: Write> Write for Interrupted<T> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.interrupt() {
+                return Err(ErrorKind::Interrupted.into());
+            }
+            let n = buf.len().min(3);
+            self.inner.write(&buf[..n])
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.inner.flush()
+        }
+    }
+
+    #[test]
+    fn copy_everything() {
+        let data: Vec<u8> = (0..=255u8).cycle().take(BUFFER_SIZE * 2 + 7).collect();
+        let mut out = Vec::new();
+        copy(&mut &data[..], None, &mut out).unwrap();
+        assert_eq!(out, data);
+    }
+
+    #[test]
+    fn copy_keeps_data_read_before_an_error() {
+        let mut out = Vec::new();
+        let result = copy(&mut FailingReader { data: b"some text\n" }, None, &mut out);
+        assert!(matches!(result, Err(CatError::Input(_))));
+        assert_eq!(out, b"some text\n");
+    }
+
+    #[test]
+    fn copy_with_interrupts_and_short_writes() {
+        let data = b"one\ttwo\n\nthree\x01\n".repeat(50);
+
+        let mut out = Interrupted::new(Vec::new());
+        copy(&mut Interrupted::new(&data[..]), None, &mut out).unwrap();
+        assert_eq!(out.inner, data);
+
+        let mut out = Interrupted::new(Vec::new());
+        let mut formatter = Formatter::new(Options { number: true, show_nonprinting: true, ..Default::default() });
+        copy(&mut Interrupted::new(&data[..]), Some(&mut formatter), &mut out).unwrap();
+        let mut expected = Vec::new();
+        Formatter::new(Options { number: true, show_nonprinting: true, ..Default::default() })
+            .format(&data, &mut expected);
+        assert_eq!(out.inner, expected);
+    }
+
+    #[test]
+    fn unbuffered_flushes_partial_lines() {
+        let mut line_writer = io::LineWriter::new(Vec::new());
+        Unbuffered(&mut line_writer).write_all(b"no newline yet").unwrap();
+        assert_eq!(line_writer.get_ref(), b"no newline yet");
+    }
+}
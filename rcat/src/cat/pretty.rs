@@ -0,0 +1,639 @@
+//! Output for people, not programs, for `--pretty`. A bit like `bat`.
+//!
+//! Each file gets a header with its name, every line gets a number, characters that
+//! can't be printed are shown with the same `^` and `M-` notation as `-v`, and a few
+//! languages (Rust, TOML, JSON, and Markdown) get simple syntax highlighting.
+//!
+//! This is only meant for a terminal. With `--pretty` (or `--pretty=auto`) it's turned
+//! off when standard output isn't one, so pipelines still get the files exactly as
+//! they are. `--pretty=always` turns it on anyway.
+
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::io::{self, BufRead, BufReader, IsTerminal, Read, Write};
+use std::path::Path;
+
+use clap::ValueEnum;
+use shared::quote::quote_name;
+
+use crate::copy::{CopyError, BUFFER_SIZE};
+
+/// When to use pretty output. Set with `--pretty=WHEN`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum When {
+    /// Only when standard output is a terminal
+    Auto,
+    /// Always, even in a pipeline
+    Always,
+    /// Never
+    Never,
+}
+
+impl When {
+    /// Check whether pretty output should be used.
+    pub(crate) fn enabled(self) -> bool {
+        match self {
+            When::Auto => io::stdout().is_terminal(),
+            When::Always => true,
+            When::Never => false,
+        }
+    }
+}
+
+/// ANSI escape codes used for the output.
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const GUTTER: &str = "\x1b[90m";
+const REVERSE: &str = "\x1b[7m";
+
+/// Writes files in the pretty format. Keeps track of whether a file has been
+/// written yet, so there's a blank line between files.
+#[derive(Debug, Default)]
+pub(crate) struct Pretty {
+    files: usize,
+}
+
+impl Pretty {
+    /// Write one file: the header, then each line with its number.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the operand. Its extension picks the language, if any, and
+    ///   it's quoted in the header if it needs to be.
+    /// * `input` - What to write.
+    /// * `out` - Where to write it.
+    pub(crate) fn write_file(&mut self, name: impl AsRef<OsStr>, input: &mut dyn Read, out: &mut dyn Write)
+        -> Result<(), CopyError> {
+        let name = name.as_ref();
+        let separator = if self.files > 0 { "\n" } else { "" };
+        self.files += 1;
+        // quote the name, so control characters in it can't reach the terminal
+        let title = if name == "-" { "STDIN".to_owned() } else { quote_name(name) };
+        writeln!(out, "{}{}{}{}", separator, BOLD, title, RESET).map_err(CopyError::Write)?;
+
+        let mut highlighter = Highlighter::new(Language::from_name(name));
+        let mut reader = BufReader::with_capacity(BUFFER_SIZE, input);
+        let mut line = Vec::new();
+        let mut rendered = Vec::new();
+        let mut number = 0;
+        loop {
+            line.clear();
+            if reader.read_until(b'\n', &mut line).map_err(CopyError::Read)? == 0 {
+                return Ok(());
+            }
+            if line.last() == Some(&b'\n') {
+                line.pop();
+            }
+            number += 1;
+
+            rendered.clear();
+            write!(rendered, "{}{:>6} │{} ", GUTTER, number, RESET).map_err(CopyError::Write)?;
+            render_line(&line, &mut highlighter, &mut rendered);
+            rendered.push(b'\n');
+            out.write_all(&rendered).map_err(CopyError::Write)?;
+        }
+    }
+}
+
+/// Add one line, without its newline, to `out` with highlighting and visible non-printables.
+fn render_line(line: &[u8], highlighter: &mut Highlighter, out: &mut Vec<u8>) {
+    // Invalid UTF-8 bytes become U+FFFD so the line can be highlighted as a `str`,
+    // but the bytes are kept to be shown in `M-` notation.
+    let mut text = String::with_capacity(line.len());
+    let mut invalid = BTreeMap::new();
+    for chunk in line.utf8_chunks() {
+        text.push_str(chunk.valid());
+        for b in chunk.invalid() {
+            invalid.insert(text.len(), *b);
+            text.push(char::REPLACEMENT_CHARACTER);
+        }
+    }
+
+    let styles = highlighter.line(&text);
+    let mut current = Style::Plain;
+    for (i, c) in text.char_indices() {
+        if styles[i] != current {
+            if current != Style::Plain {
+                out.extend_from_slice(RESET.as_bytes());
+            }
+            current = styles[i];
+            out.extend_from_slice(current.code().as_bytes());
+        }
+
+        let byte = invalid.get(&i).copied()
+            .or_else(|| (c != '\t' && (c.is_ascii_control())).then_some(c as u8));
+        match byte {
+            Some(b) => {
+                out.extend_from_slice(REVERSE.as_bytes());
+                super::display::push_visible(b, out);
+                out.extend_from_slice(RESET.as_bytes());
+                out.extend_from_slice(current.code().as_bytes());
+            },
+            None => {
+                let mut buf = [0u8; 4];
+                out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            },
+        }
+    }
+    if current != Style::Plain {
+        out.extend_from_slice(RESET.as_bytes());
+    }
+}
+
+/// Languages that get highlighted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Language {
+    Rust,
+    Toml,
+    Json,
+    Markdown,
+}
+
+impl Language {
+    /// Pick the language from a file name's extension.
+    pub(crate) fn from_name(name: impl AsRef<OsStr>) -> Option<Language> {
+        let path = Path::new(name.as_ref());
+        if path.file_name().is_some_and(|n| n == "Cargo.lock") {
+            return Some(Language::Toml);
+        }
+        match path.extension()?.to_str()? {
+            "rs" => Some(Language::Rust),
+            "toml" => Some(Language::Toml),
+            "json" => Some(Language::Json),
+            "md" | "markdown" => Some(Language::Markdown),
+            _ => None,
+        }
+    }
+}
+
+/// What a piece of text is, for highlighting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Style {
+    Plain,
+    Keyword,
+    Type,
+    Str,
+    Comment,
+    Number,
+    Key,
+    Heading,
+    Code,
+}
+
+impl Style {
+    /// ANSI escape code to start this style.
+    fn code(self) -> &'static str {
+        match self {
+            Style::Plain => "",
+            Style::Keyword => "\x1b[35m",
+            Style::Type => "\x1b[33m",
+            Style::Str => "\x1b[32m",
+            Style::Comment => "\x1b[90m",
+            Style::Number => "\x1b[36m",
+            Style::Key => "\x1b[34m",
+            Style::Heading => "\x1b[1;34m",
+            Style::Code => "\x1b[33m",
+        }
+    }
+}
+
+/// Rust keywords, and a few words that look like them.
+const RUST_KEYWORDS: [&str; 38] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+    "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod",
+    "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super",
+    "trait", "true", "type", "unsafe", "use", "where", "while",
+];
+
+/// Something that carries on from one line to the next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Normal,
+    /// In a Rust block comment, nested this deep.
+    BlockComment(usize),
+    /// In a string that didn't end on its line, with this closing quote.
+    Str(&'static str),
+    /// In a Markdown code block.
+    CodeBlock,
+}
+
+/// Highlights a file one line at a time.
+#[derive(Debug)]
+pub(crate) struct Highlighter {
+    language: Option<Language>,
+    state: State,
+}
+
+impl Highlighter {
+    pub(crate) fn new(language: Option<Language>) -> Highlighter {
+        Highlighter { language, state: State::Normal }
+    }
+
+    /// Get the style of each byte of `line`.
+    pub(crate) fn line(&mut self, line: &str) -> Vec<Style> {
+        let mut styles = vec![Style::Plain; line.len()];
+        match self.language {
+            Some(Language::Rust) => self.rust(line, &mut styles),
+            Some(Language::Toml) => self.toml(line, &mut styles),
+            Some(Language::Json) => json(line, &mut styles),
+            Some(Language::Markdown) => self.markdown(line, &mut styles),
+            None => {},
+        }
+        styles
+    }
+
+    /// Finish a string or comment carried over from the last line. Returns where
+    /// normal text starts again.
+    fn carry_on(&mut self, line: &str, styles: &mut [Style]) -> usize {
+        match self.state {
+            State::Str(quote) => {
+                let (end, closed) = string_end(line, 0, quote);
+                styles[..end].fill(Style::Str);
+                if closed {
+                    self.state = State::Normal;
+                }
+                end
+            },
+            State::BlockComment(depth) => {
+                let end = self.block_comment(line, 0, depth);
+                styles[..end].fill(Style::Comment);
+                end
+            },
+            _ => 0,
+        }
+    }
+
+    /// Find the end of a block comment starting at `start`, `depth` levels deep.
+    /// Updates the state if it doesn't end on this line.
+    fn block_comment(&mut self, line: &str, start: usize, mut depth: usize) -> usize {
+        let bytes = line.as_bytes();
+        let mut i = start;
+        while i < bytes.len() {
+            if bytes[i..].starts_with(b"/*") {
+                depth += 1;
+                i += 2;
+            } else if bytes[i..].starts_with(b"*/") {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    self.state = State::Normal;
+                    return i;
+                }
+            } else {
+                i += 1;
+            }
+        }
+        self.state = State::BlockComment(depth);
+        line.len()
+    }
+
+    fn rust(&mut self, line: &str, styles: &mut [Style]) {
+        let bytes = line.as_bytes();
+        let mut i = self.carry_on(line, styles);
+        while i < bytes.len() {
+            let rest = &bytes[i..];
+            let start = i;
+            let style = if rest.starts_with(b"//") {
+                i = bytes.len();
+                Style::Comment
+            } else if rest.starts_with(b"/*") {
+                i = self.block_comment(line, i + 2, 1);
+                Style::Comment
+            } else if rest[0] == b'"' {
+                let (end, closed) = string_end(line, i + 1, "\"");
+                if !closed {
+                    self.state = State::Str("\"");
+                }
+                i = end;
+                Style::Str
+            } else if rest[0] == b'\'' && is_char_literal(rest) {
+                i = string_end(line, i + 1, "'").0;
+                Style::Str
+            } else if rest[0].is_ascii_digit() {
+                i += word_len(rest);
+                Style::Number
+            } else if is_word_start(rest[0]) {
+                i += word_len(rest);
+                let word = &line[start..i];
+                if RUST_KEYWORDS.contains(&word) {
+                    Style::Keyword
+                } else if bytes.get(i) == Some(&b'!') {
+                    i += 1;
+                    Style::Keyword
+                } else if word.starts_with(|c: char| c.is_ascii_uppercase()) {
+                    Style::Type
+                } else {
+                    Style::Plain
+                }
+            } else {
+                i += utf8_len(rest[0]);
+                Style::Plain
+            };
+            styles[start..i].fill(style);
+        }
+    }
+
+    fn toml(&mut self, line: &str, styles: &mut [Style]) {
+        let bytes = line.as_bytes();
+        let mut i = self.carry_on(line, styles);
+        if i == 0 && line.trim_start().starts_with('[') {
+            // a table header, up to any comment
+            let end = line.find('#').unwrap_or(line.len());
+            styles[..end].fill(Style::Heading);
+            i = end;
+        }
+        // a key is followed by = (or by . in a dotted key), even in an inline table
+        let is_key = |end: usize| line[end..].trim_start().starts_with(['=', '.']);
+
+        while i < bytes.len() {
+            let rest = &bytes[i..];
+            let start = i;
+            let style = if rest[0] == b'#' {
+                i = bytes.len();
+                Style::Comment
+            } else if rest[0] == b'"' || rest[0] == b'\'' {
+                let quote = match (rest.starts_with(b"\"\"\""), rest.starts_with(b"'''"), rest[0]) {
+                    (true, _, _) => "\"\"\"",
+                    (_, true, _) => "'''",
+                    (_, _, b'"') => "\"",
+                    _ => "'",
+                };
+                let (end, closed) = string_end(line, i + quote.len(), quote);
+                if !closed && quote.len() == 3 {
+                    self.state = State::Str(quote);
+                }
+                i = end;
+                if closed && is_key(i) { Style::Key } else { Style::Str }
+            } else if rest[0] == b'.' && start > 0 && styles[start - 1] == Style::Key {
+                i += 1;
+                Style::Key
+            } else if is_word_start(rest[0]) || rest[0].is_ascii_digit() || rest[0] == b'-' || rest[0] == b'+' {
+                i += word_len(rest);
+                let word = &line[start..i];
+                if is_key(i) {
+                    Style::Key
+                } else if word == "true" || word == "false" {
+                    Style::Keyword
+                } else if word.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+') {
+                    Style::Number
+                } else {
+                    Style::Plain
+                }
+            } else {
+                i += utf8_len(rest[0]);
+                Style::Plain
+            };
+            styles[start..i].fill(style);
+        }
+    }
+
+    fn markdown(&mut self, line: &str, styles: &mut [Style]) {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            self.state = match self.state {
+                State::CodeBlock => State::Normal,
+                _ => State::CodeBlock,
+            };
+            styles.fill(Style::Code);
+            return;
+        }
+        if self.state == State::CodeBlock {
+            styles.fill(Style::Code);
+            return;
+        }
+        if trimmed.starts_with('#') {
+            styles.fill(Style::Heading);
+            return;
+        }
+        if trimmed.starts_with('>') {
+            styles.fill(Style::Comment);
+            return;
+        }
+
+        let indent = line.len() - trimmed.len();
+        let bullet = if trimmed.starts_with("- ") || trimmed.starts_with("* ") || trimmed.starts_with("+ ") {
+            1
+        } else {
+            let digits = trimmed.bytes().take_while(u8::is_ascii_digit).count();
+            if digits > 0 && trimmed[digits..].starts_with(". ") { digits + 1 } else { 0 }
+        };
+        styles[indent..indent + bullet].fill(Style::Keyword);
+
+        // inline `code`
+        let mut i = indent + bullet;
+        while let Some(open) = line[i..].find('`') {
+            let start = i + open;
+            match line[start + 1..].find('`') {
+                Some(close) => {
+                    i = start + close + 2;
+                    styles[start..i].fill(Style::Code);
+                },
+                None => break,
+            }
+        }
+    }
+}
+
+fn json(line: &str, styles: &mut [Style]) {
+    let bytes = line.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let rest = &bytes[i..];
+        let start = i;
+        let style = if rest[0] == b'"' {
+            i = string_end(line, i + 1, "\"").0;
+            // a string followed by a colon is a key
+            if line[i..].trim_start().starts_with(':') { Style::Key } else { Style::Str }
+        } else if rest[0].is_ascii_digit() || rest[0] == b'-' {
+            i += word_len(rest).max(1);
+            Style::Number
+        } else if is_word_start(rest[0]) {
+            i += word_len(rest);
+            match &line[start..i] {
+                "true" | "false" | "null" => Style::Keyword,
+                _ => Style::Plain,
+            }
+        } else {
+            i += utf8_len(rest[0]);
+            Style::Plain
+        };
+        styles[start..i].fill(style);
+    }
+}
+
+/// Find the end of a string that started just before `start`. Backslash escapes
+/// are skipped. Returns the index just past the closing quote, or the end of the
+/// line, and whether the string was closed.
+fn string_end(line: &str, start: usize, quote: &str) -> (usize, bool) {
+    let bytes = line.as_bytes();
+    let mut i = start;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && quote != "'''" {
+            i += 2;
+        } else if bytes[i..].starts_with(quote.as_bytes()) {
+            return (i + quote.len(), true);
+        } else {
+            i += 1;
+        }
+    }
+    (bytes.len(), false)
+}
+
+/// Check whether a `'` starts a Rust character literal, not a lifetime like `'a`.
+fn is_char_literal(rest: &[u8]) -> bool {
+    match rest.get(1) {
+        Some(b'\\') => true,
+        Some(b) => rest.get(1 + utf8_len(*b)) == Some(&b'\''),
+        None => false,
+    }
+}
+
+fn is_word_start(b: u8) -> bool {
+    b.is_ascii_alphabetic() || b == b'_'
+}
+
+/// Length of the word (letters, digits, `_`, and `.` for numbers) at the start of `rest`.
+fn word_len(rest: &[u8]) -> usize {
+    let number = rest[0].is_ascii_digit() || rest[0] == b'-' || rest[0] == b'+';
+    rest.iter().skip(1)
+        .take_while(|b| b.is_ascii_alphanumeric() || **b == b'_' || (number && matches!(b, b'.' | b'-' | b'+')))
+        .count() + 1
+}
+
+/// Length of the UTF-8 character that starts with `b`, so highlighting never splits one.
+fn utf8_len(b: u8) -> usize {
+    match b.leading_ones() {
+        2 => 2,
+        3 => 3,
+        4 => 4,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper method to get the text with each style, ignoring plain text.
+    fn styled(language: Language, lines: &[&str]) -> Vec<(Style, String)> {
+        let mut highlighter = Highlighter::new(Some(language));
+        let mut found = Vec::new();
+        for line in lines {
+            let styles = highlighter.line(line);
+            let mut start = 0;
+            for i in 1..=line.len() {
+                if i == line.len() || styles[i] != styles[start] {
+                    if styles[start] != Style::Plain {
+                        found.push((styles[start], line[start..i].to_owned()));
+                    }
+                    start = i;
+                }
+            }
+        }
+        found
+    }
+
+    fn pairs(expected: &[(Style, &str)]) -> Vec<(Style, String)> {
+        expected.iter().map(|(s, t)| (*s, t.to_string())).collect()
+    }
+
+    #[test]
+    fn language_from_name() {
+        assert_eq!(Language::from_name("src/main.rs"), Some(Language::Rust));
+        assert_eq!(Language::from_name("Cargo.toml"), Some(Language::Toml));
+        assert_eq!(Language::from_name("Cargo.lock"), Some(Language::Toml));
+        assert_eq!(Language::from_name("data.json"), Some(Language::Json));
+        assert_eq!(Language::from_name("README.md"), Some(Language::Markdown));
+        assert_eq!(Language::from_name("notes.txt"), None);
+        assert_eq!(Language::from_name("-"), None);
+    }
+
+    #[test]
+    fn rust_highlighting() {
+        let found = styled(Language::Rust, &[
+            "pub fn f<'a>(s: &'a str) -> Option<char> { /* start",
+            "   end */ println!(\"{}\", 'x'); 42 // done",
+        ]);
+        assert_eq!(found, pairs(&[
+            (Style::Keyword, "pub"), (Style::Keyword, "fn"), (Style::Type, "Option"),
+            (Style::Comment, "/* start"), (Style::Comment, "   end */"), (Style::Keyword, "println!"),
+            (Style::Str, "\"{}\""), (Style::Str, "'x'"), (Style::Number, "42"), (Style::Comment, "// done"),
+        ]));
+    }
+
+    #[test]
+    fn rust_string_over_lines() {
+        let found = styled(Language::Rust, &["let s = \"one", "two\\\" still\";"]);
+        assert_eq!(found, pairs(&[
+            (Style::Keyword, "let"), (Style::Str, "\"one"), (Style::Str, "two\\\" still\""),
+        ]));
+    }
+
+    #[test]
+    fn toml_highlighting() {
+        let found = styled(Language::Toml, &[
+            "[package] # the package",
+            "name = \"rcat\"",
+            "version.workspace = true",
+            "size = 1_000",
+            "clap = { version = \"4\", features = [\"derive\"] }",
+        ]);
+        assert_eq!(found, pairs(&[
+            (Style::Heading, "[package] "), (Style::Comment, "# the package"),
+            (Style::Key, "name"), (Style::Str, "\"rcat\""),
+            (Style::Key, "version.workspace"), (Style::Keyword, "true"),
+            (Style::Key, "size"), (Style::Number, "1_000"),
+            (Style::Key, "clap"), (Style::Key, "version"), (Style::Str, "\"4\""),
+            (Style::Key, "features"), (Style::Str, "\"derive\""),
+        ]));
+    }
+
+    #[test]
+    fn json_highlighting() {
+        let found = styled(Language::Json, &["{\"name\": \"rcat\", \"size\": -1.5e3, \"ok\": null}"]);
+        assert_eq!(found, pairs(&[
+            (Style::Key, "\"name\""), (Style::Str, "\"rcat\""), (Style::Key, "\"size\""),
+            (Style::Number, "-1.5e3"), (Style::Key, "\"ok\""), (Style::Keyword, "null"),
+        ]));
+    }
+
+    #[test]
+    fn markdown_highlighting() {
+        let found = styled(Language::Markdown, &[
+            "# Title", "- item with `code`", "```rust", "let x = 1;", "```", "> quote",
+        ]);
+        assert_eq!(found, pairs(&[
+            (Style::Heading, "# Title"), (Style::Keyword, "-"), (Style::Code, "`code`"),
+            (Style::Code, "```rust"), (Style::Code, "let x = 1;"), (Style::Code, "```"),
+            (Style::Comment, "> quote"),
+        ]));
+    }
+
+    #[test]
+    fn render_non_printables() {
+        let mut out = Vec::new();
+        render_line(b"a\tb\x01c\xffd\r", &mut Highlighter::new(None), &mut out);
+        let expected = format!("a\tb{r}^A{0}c{r}M-^?{0}d{r}^M{0}", RESET, r = REVERSE);
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+    }
+
+    #[test]
+    fn write_files_with_headers() {
+        let mut pretty = Pretty::default();
+        let mut out = Vec::new();
+        pretty.write_file("a.txt", &mut &b"one\ntwo"[..], &mut out).unwrap();
+        pretty.write_file("-", &mut &b""[..], &mut out).unwrap();
+        let expected = format!(
+            "{b}a.txt{r}\n{g}     1 │{r} one\n{g}     2 │{r} two\n\n{b}STDIN{r}\n",
+            b = BOLD, g = GUTTER, r = RESET);
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+    }
+
+    #[test]
+    fn quote_control_characters_in_header() {
+        let mut out = Vec::new();
+        Pretty::default().write_file("a\x1b[2Jb", &mut &b""[..], &mut out).unwrap();
+        let expected = format!("{}'a'$'\\033''[2Jb'{}\n", BOLD, RESET);
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+    }
+}
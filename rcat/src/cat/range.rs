@@ -0,0 +1,246 @@
+//! Picking out part of the output, for `--lines=A:B` and `--bytes=A:B`.
+//!
+//! A range counts from 1 and includes both ends, like the ranges `cut` takes, so
+//! `--lines=100:200` is lines 100 through 200. Either end can be left out: `A:` goes
+//! to the end, and `:B` starts at the beginning. A single number `N` is just `N:N`.
+//! Numbers can have the same suffixes as other sizes, read by `shared::units`:
+//! `K` and `KiB` are 1024, `KB` is 1000, and so on for `M`, `G`, `T` and up.
+//!
+//! The range is over everything cat reads, one operand after another, so
+//! `cat --lines=3:4 a b` gives lines 3 and 4 of `cat a b`. A byte range skips over
+//! regular files without reading them, by their size, and seeks to where the range
+//! starts. Anything else is read and thrown away up to the start. Once the range is
+//! done, nothing more is read.
+
+use std::fs::File;
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom};
+
+use shared::units::{parse_size, UnitError};
+
+/// What a range counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Unit {
+    Bytes,
+    Lines,
+}
+
+/// The range given on the command line, turned into a 0-based start and an end
+/// that isn't included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Span {
+    start: u64,
+    end: Option<u64>,
+}
+
+/// Parse a range like `A:B`, `A:`, `:B`, or `N`. Used by `clap` for `--lines` and `--bytes`.
+///
+/// # Arguments
+///
+/// * `s` - The range, as it was typed.
+pub(crate) fn parse_span(s: &str) -> Result<Span, String> {
+    if s.is_empty() {
+        return Err("the range is empty".to_owned());
+    }
+    let (first, last) = match s.split_once(':') {
+        Some((first, last)) => (first, last),
+        None => (s, s),
+    };
+    let first = if first.is_empty() { 1 } else { parse_number(first)? };
+    let last = if last.is_empty() { None } else { Some(parse_number(last)?) };
+
+    if first == 0 {
+        return Err("ranges start at 1".to_owned());
+    }
+    match last {
+        Some(last) if last < first => Err(format!("{} is before {}", last, first)),
+        _ => Ok(Span { start: first - 1, end: last }),
+    }
+}
+
+/// Parse one end of a range, which is a size like `10`, `1M` or `2KB`.
+fn parse_number(s: &str) -> Result<u64, String> {
+    parse_size(s).map_err(|e| match e {
+        UnitError::TooLarge => format!("'{}' is too big", s),
+        _ => format!("'{}' isn't a number", s),
+    })
+}
+
+/// Keeps track of where the output is in the range, from one operand to the next.
+#[derive(Debug)]
+pub(crate) struct Selection {
+    unit: Unit,
+    span: Span,
+    /// Bytes, or whole lines, read so far.
+    seen: u64,
+}
+
+impl Selection {
+    pub(crate) fn new(unit: Unit, span: Span) -> Selection {
+        Selection { unit, span, seen: 0 }
+    }
+
+    /// Check whether everything in the range has been read.
+    pub(crate) fn is_done(&self) -> bool {
+        self.span.end.is_some_and(|end| self.seen >= end)
+    }
+
+    /// For a byte range, move `file` past anything before the start of the range, so
+    /// it doesn't have to be read. A regular file that ends before the range starts
+    /// is skipped over completely. Other files are left alone.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - A file that was just opened, at its start.
+    pub(crate) fn seek(&mut self, mut file: &File) -> io::Result<()> {
+        if self.unit != Unit::Bytes || self.seen >= self.span.start {
+            return Ok(());
+        }
+        let metadata = file.metadata()?;
+        if !metadata.is_file() {
+            return Ok(());
+        }
+        let skip = (self.span.start - self.seen).min(metadata.len());
+        file.seek(SeekFrom::Start(skip))?;
+        self.seen += skip;
+        Ok(())
+    }
+
+    /// Count the bytes or lines in the next piece of input, and return the part of
+    /// it that's in the range, as a start and an end index.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The next piece of input. It can end anywhere, even in the middle of a line.
+    fn select(&mut self, data: &[u8]) -> (usize, usize) {
+        match self.unit {
+            Unit::Bytes => {
+                let len = data.len() as u64;
+                let index = |n: u64| n.saturating_sub(self.seen).min(len) as usize;
+                let range = (index(self.span.start), self.span.end.map_or(data.len(), index));
+                self.seen += len;
+                range
+            },
+            Unit::Lines => {
+                let mut start = (self.seen >= self.span.start).then_some(0);
+                let mut end = data.len();
+                let mut i = 0;
+                while let Some(newline) = data[i..].iter().position(|&b| b == b'\n') {
+                    i += newline + 1;
+                    self.seen += 1;
+                    if self.is_done() {
+                        end = i;
+                        break;
+                    }
+                    if self.seen == self.span.start {
+                        start = Some(i);
+                    }
+                }
+                match start {
+                    Some(start) if start < end => (start, end),
+                    _ => (0, 0),
+                }
+            },
+        }
+    }
+
+    /// Wrap `input` in a reader that only gives what's in the range.
+    pub(crate) fn reader<'a>(&'a mut self, input: &'a mut dyn Read) -> Selected<'a> {
+        Selected { input, selection: self }
+    }
+}
+
+/// A reader that only gives the part of its input that's in a `Selection`.
+pub(crate) struct Selected<'a> {
+    input: &'a mut dyn Read,
+    selection: &'a mut Selection,
+}
+
+impl Read for Selected<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // pieces before the range give nothing, so keep reading until there's
+        // something to give, or the end of the input or the range
+        loop {
+            if buf.is_empty() || self.selection.is_done() {
+                return Ok(0);
+            }
+            let n = match self.input.read(buf) {
+                Ok(0) => return Ok(0),
+                Ok(n) => n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            let (start, end) = self.selection.select(&buf[..n]);
+            if start < end {
+                buf.copy_within(start..end, 0);
+                return Ok(end - start);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use harness::Scene;
+
+    /// Helper method to run some pieces of input through one `Selection`.
+    fn select(unit: Unit, range: &str, pieces: &[&[u8]]) -> String {
+        let mut selection = Selection::new(unit, parse_span(range).unwrap());
+        let mut out = Vec::new();
+        for piece in pieces {
+            selection.reader(&mut &piece[..]).read_to_end(&mut out).unwrap();
+        }
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn parse_ranges() {
+        assert_eq!(parse_span("3:5"), Ok(Span { start: 2, end: Some(5) }));
+        assert_eq!(parse_span("3:"), Ok(Span { start: 2, end: None }));
+        assert_eq!(parse_span(":5"), Ok(Span { start: 0, end: Some(5) }));
+        assert_eq!(parse_span("7"), Ok(Span { start: 6, end: Some(7) }));
+        assert_eq!(parse_span("1M:2M"), Ok(Span { start: (1 << 20) - 1, end: Some(2 << 20) }));
+        assert!(parse_span("0:5").is_err());
+        assert!(parse_span("5:3").is_err());
+        assert!(parse_span("a:b").is_err());
+        assert!(parse_span("-1:").is_err());
+        assert_eq!(parse_span("M"), Ok(Span { start: (1 << 20) - 1, end: Some(1 << 20) }));
+        assert_eq!(parse_span("1KiB:2kB"), Ok(Span { start: 1023, end: Some(2000) }));
+        assert!(parse_span("1X").is_err());
+        assert!(parse_span("").is_err());
+        assert!(parse_span("99999999999T").is_err());
+    }
+
+    #[test]
+    fn bytes_across_pieces() {
+        assert_eq!(select(Unit::Bytes, "3:7", &[b"abcd", b"efghij"]), "cdefg");
+        assert_eq!(select(Unit::Bytes, "5:", &[b"abcd", b"efghij"]), "efghij");
+        assert_eq!(select(Unit::Bytes, ":2", &[b"abcd", b"efghij"]), "ab");
+        assert_eq!(select(Unit::Bytes, "20:", &[b"abcd", b"efghij"]), "");
+    }
+
+    #[test]
+    fn lines_across_pieces() {
+        let pieces: [&[u8]; 3] = [b"1\n2\n3", b"\n4\n", b"5\n6"];
+        assert_eq!(select(Unit::Lines, "2:4", &pieces), "2\n3\n4\n");
+        assert_eq!(select(Unit::Lines, "3", &pieces), "3\n");
+        assert_eq!(select(Unit::Lines, "5:", &pieces), "5\n6");
+        assert_eq!(select(Unit::Lines, ":1", &pieces), "1\n");
+        assert_eq!(select(Unit::Lines, "7:", &pieces), "");
+    }
+
+    #[test]
+    fn seek_past_files() {
+        let scene = Scene::new().file("digits", b"0123456789");
+        let mut selection = Selection::new(Unit::Bytes, parse_span("14:16").unwrap());
+        let mut out = Vec::new();
+
+        for _ in 0..2 {
+            let mut file = File::open(scene.join("digits")).unwrap();
+            selection.seek(&file).unwrap();
+            selection.reader(&mut file).read_to_end(&mut out).unwrap();
+        }
+        assert_eq!(out, b"345");
+        assert!(selection.is_done());
+    }
+}
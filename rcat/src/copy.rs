@@ -0,0 +1,197 @@
+//! Copying bytes from an input to an output, as fast as the system allows.
+//!
+//! When no display options are set, cat doesn't need to look at the data at all.
+//! On Linux, `zero_copy()` asks the kernel to move it straight from one file
+//! descriptor to the other, without passing through a buffer in this program:
+//!
+//! * `copy_file_range` from a regular file to a regular file. Some file systems can
+//!   share the blocks and not copy anything.
+//! * `splice` when the input or output is a pipe.
+//! * `sendfile` from a regular file to anything else, like a socket or `/dev/null`.
+//!
+//! If none of those work, `read_write()` copies through a big buffer instead.
+
+use std::io::{self, ErrorKind, Read, Write};
+
+/// Size of the buffer used to copy input to output.
+pub const BUFFER_SIZE: usize = 128 * 1024;
+
+/// Ways that copying can fail.
+#[derive(Debug)]
+pub enum CopyError {
+    /// The input couldn't be read.
+    Read(io::Error),
+    /// The output couldn't be written.
+    Write(io::Error),
+}
+
+/// Read from `input` into `buffer`, trying again if the read is interrupted.
+/// Returns the number of bytes read, which is 0 at the end of the input.
+pub fn read_retry(input: &mut dyn Read, buffer: &mut [u8]) -> io::Result<usize> {
+    loop {
+        match input.read(buffer) {
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            result => return result,
+        }
+    }
+}
+
+/// Copy everything from `input` to `out` through a buffer of `BUFFER_SIZE` bytes.
+/// This works for any input and output, and is used when `zero_copy()` can't be.
+pub fn read_write(input: &mut dyn Read, out: &mut dyn Write) -> Result<(), CopyError> {
+    let mut buffer = vec![0; BUFFER_SIZE];
+    loop {
+        let n = read_retry(input, &mut buffer).map_err(CopyError::Read)?;
+        if n == 0 {
+            return Ok(());
+        }
+        out.write_all(&buffer[..n]).map_err(CopyError::Write)?;
+    }
+}
+
+/// Copy everything from `input` to `output` inside the kernel, if it can be done.
+///
+/// Returns `true` if everything was copied. Returns `false` if none of the system
+/// calls work for these two file descriptors, or if one of them fails partway. Both
+/// file descriptors are used at their current offsets, and those offsets move as
+/// data is copied, so `read_write()` can carry on from wherever this stopped.
+/// It will also report any error again, as a read or a write error.
+///
+/// # Arguments
+///
+/// * `input` - File descriptor to copy from.
+/// * `output` - File descriptor to copy to. Anything buffered for it should already be flushed.
+#[cfg(target_os = "linux")]
+pub fn zero_copy(input: std::os::fd::BorrowedFd, output: std::os::fd::BorrowedFd) -> bool {
+    use std::os::fd::AsRawFd;
+
+    /// Most that's asked for in one system call. The kernel can copy less.
+    const CHUNK: usize = 1 << 30;
+    /// Pipes only hold so much, so ask `splice` for less at a time.
+    const SPLICE_CHUNK: usize = 1 << 20;
+
+    let (Some(in_type), Some(out_type)) = (file_type(input), file_type(output)) else {
+        return false;
+    };
+    let (in_fd, out_fd) = (input.as_raw_fd(), output.as_raw_fd());
+    let is_file = |t| t == libc::S_IFREG;
+    let is_pipe = |t| t == libc::S_IFIFO;
+    let null = std::ptr::null_mut();
+
+    // SAFETY: both file descriptors are borrowed, so they stay open for these calls.
+    // Null offsets mean the kernel uses and moves the file descriptors' own offsets.
+    if is_file(in_type) && is_file(out_type)
+        && transfer(|| unsafe { libc::copy_file_range(in_fd, null, out_fd, null, CHUNK, 0) }) {
+        return true;
+    }
+    if (is_pipe(in_type) || is_pipe(out_type))
+        && transfer(|| unsafe { libc::splice(in_fd, null, out_fd, null, SPLICE_CHUNK, libc::SPLICE_F_MOVE) }) {
+        return true;
+    }
+    is_file(in_type)
+        && transfer(|| unsafe { libc::sendfile(out_fd, in_fd, null, CHUNK) })
+}
+
+/// Get the file type bits (`S_IFREG`, `S_IFIFO`, and so on) of a file descriptor.
+#[cfg(target_os = "linux")]
+fn file_type(fd: std::os::fd::BorrowedFd) -> Option<libc::mode_t> {
+    use std::os::fd::AsRawFd;
+
+    let mut stat = std::mem::MaybeUninit::<libc::stat>::uninit();
+    // SAFETY: fd is open, and fstat fills in stat when it returns 0.
+    match unsafe { libc::fstat(fd.as_raw_fd(), stat.as_mut_ptr()) } {
+        0 => Some(unsafe { stat.assume_init() }.st_mode & libc::S_IFMT),
+        _ => None,
+    }
+}
+
+/// Call `copy` until it gets to the end of the input. It should return the number of
+/// bytes copied, 0 at the end, or -1 for an error, like the copying system calls do.
+///
+/// Returns `false` on any error other than `EINTR`. Also returns `false` if nothing
+/// was copied at all, because some files (like the ones in `/proc`) say they're
+/// empty to these system calls but still have data when they're read.
+#[cfg(target_os = "linux")]
+fn transfer(mut copy: impl FnMut() -> isize) -> bool {
+    let mut copied = false;
+    loop {
+        match copy() {
+            0 => return copied,
+            n if n > 0 => copied = true,
+            _ if io::Error::last_os_error().kind() == ErrorKind::Interrupted => continue,
+            _ => return false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use harness::Scene;
+
+    /// Data bigger than the buffer, that doesn't repeat at the same place in each buffer.
+    fn test_data() -> Vec<u8> {
+        (0..BUFFER_SIZE * 3 + 13).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn read_write_copies_everything() {
+        let data = test_data();
+        let mut out = Vec::new();
+        read_write(&mut &data[..], &mut out).unwrap();
+        assert_eq!(out, data);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn zero_copy_file_to_file() {
+        use std::io::Seek;
+        use std::os::fd::AsFd;
+
+        let data = test_data();
+        let scene = Scene::new().file("in", &data).file("out", b"already here\n");
+        let input = std::fs::File::open(scene.join("in")).unwrap();
+        // copy_file_range and sendfile don't work on files opened for appending,
+        // so seek to the end instead
+        let mut output = std::fs::OpenOptions::new().write(true).open(scene.join("out")).unwrap();
+        output.seek(io::SeekFrom::End(0)).unwrap();
+
+        assert!(zero_copy(input.as_fd(), output.as_fd()));
+        assert_eq!(scene.read("out"), [&b"already here\n"[..], &data].concat());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn zero_copy_file_to_pipe_and_pipe_to_file() {
+        use std::os::fd::AsFd;
+
+        let data = test_data();
+        let scene = Scene::new().file("in", &data);
+        let input = std::fs::File::open(scene.join("in")).unwrap();
+        let (mut reader, writer) = io::pipe().unwrap();
+
+        let sender = std::thread::spawn(move || zero_copy(input.as_fd(), writer.as_fd()));
+        let output = std::fs::File::create(scene.join("out")).unwrap();
+        let received = zero_copy(reader.as_fd(), output.as_fd());
+        // anything zero_copy() couldn't move is still in the pipe
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+
+        assert!(sender.join().unwrap());
+        assert!(received);
+        assert!(rest.is_empty());
+        assert_eq!(scene.read("out"), data);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn zero_copy_falls_back_for_empty_input() {
+        use std::os::fd::AsFd;
+
+        let scene = Scene::new().file("in", b"").file("out", b"");
+        let input = std::fs::File::open(scene.join("in")).unwrap();
+        let output = std::fs::OpenOptions::new().write(true).open(scene.join("out")).unwrap();
+
+        assert!(!zero_copy(input.as_fd(), output.as_fd()));
+    }
+}
@@ -0,0 +1,132 @@
+//! Rust version of the classic Linux cat program.
+//!
+//! Concatenates files, or the pipeline, to standard output.
+//!
+//! The program itself is in `main.rs`, which just calls `run()`. `UTILITY` lets the
+//! `coreutils` binary run it too. The copy routines are public so the benchmarks in
+//! `benches/` can use them.
+
+use std::ffi::OsString;
+use std::path::Path;
+use std::process::ExitCode;
+
+use clap::{Parser};
+use shared::error::{parse_args, EXIT_FAILURE};
+use shared::utility::Utility;
+
+mod cat;
+pub mod copy;
+
+/// cat, for the `coreutils` binary. It can also be run as zcat and the other
+/// decompressing aliases.
+pub const UTILITY: Utility = Utility { name: "cat", aliases: &cat::ALIASES, main: run::<Vec<OsString>, OsString> };
+
+/// Struct that contains information about the command line options that were entered.
+/// Used by the `clap` library.
+#[derive(Parser, Debug)]
+#[command(name = "cat", author, version, about, long_about = None)]
+struct Cli {
+    #[arg(short = 'A', long)]
+    /// Equivalent to -vET
+    show_all: bool,
+
+    #[arg(short = 'b', long)]
+    /// Number nonempty output lines, overrides -n
+    number_nonblank: bool,
+
+    #[arg(short = 'e')]
+    /// Equivalent to -vE
+    e: bool,
+
+    #[arg(short = 'E', long)]
+    /// Display $ at end of each line
+    show_ends: bool,
+
+    #[arg(short = 'n', long)]
+    /// Number all output lines
+    number: bool,
+
+    #[arg(short = 's', long)]
+    /// Suppress repeated empty output lines
+    squeeze_blank: bool,
+
+    #[arg(short = 't')]
+    /// Equivalent to -vT
+    t: bool,
+
+    #[arg(short = 'T', long)]
+    /// Display TAB characters as ^I
+    show_tabs: bool,
+
+    #[arg(short = 'u')]
+    /// Write output as soon as it is read, without buffering
+    unbuffered: bool,
+
+    #[arg(short = 'v', long)]
+    /// Use ^ and M- notation, except for LFD and TAB
+    show_nonprinting: bool,
+
+    #[arg(long)]
+    /// Decompress gzip, zstd, bzip2, and xz input. Other input is copied as it is
+    decompress: bool,
+
+    #[arg(long, value_enum, value_name = "WHEN", num_args = 0..=1, require_equals = true, default_missing_value = "auto")]
+    /// Show a header for each file, line numbers, and syntax highlighting. With auto (the default), only on a terminal
+    pretty: Option<cat::PrettyWhen>,
+
+    #[arg(long, value_name = "A:B", value_parser = cat::parse_span, conflicts_with = "bytes")]
+    /// Only output lines A to B, counting from 1. Either end can be left out
+    lines: Option<cat::Span>,
+
+    #[arg(long, value_name = "A:B", value_parser = cat::parse_span)]
+    /// Only output bytes A to B, counting from 1. Sizes like 1M can be used
+    bytes: Option<cat::Span>,
+
+    #[arg(skip)]
+    /// Name used in error messages: cat, or the alias (like zcat) it was run as
+    util_name: String,
+
+    /// List of files to concatenate. With no files, or when a file is -, read standard input
+    files: Vec<OsString>,
+}
+
+/// Run cat with the given command line, including the program name, and return its exit code.
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments, starting with the program name.
+pub fn run<I, T>(args: I) -> ExitCode
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    let args: Vec<OsString> = args.into_iter().map(Into::into).collect();
+    let mut clap_args: Cli = parse_args(&args, EXIT_FAILURE);
+
+    // run through a link named zcat (or one of the others), act like it
+    let alias = args.first()
+        .and_then(|a| Path::new(a).file_stem())
+        .and_then(|a| a.to_str())
+        .filter(|a| cat::ALIASES.contains(a));
+    clap_args.util_name = alias.unwrap_or("cat").to_owned();
+    if alias.is_some() {
+        clap_args.decompress = true;
+    }
+
+    // -A, -e, and -t are short ways to turn on more than one switch
+    if clap_args.show_all {
+        clap_args.show_nonprinting = true;
+        clap_args.show_ends = true;
+        clap_args.show_tabs = true;
+    }
+    if clap_args.e {
+        clap_args.show_nonprinting = true;
+        clap_args.show_ends = true;
+    }
+    if clap_args.t {
+        clap_args.show_nonprinting = true;
+        clap_args.show_tabs = true;
+    }
+
+    cat::cat(&clap_args)
+}
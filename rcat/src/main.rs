@@ -1,3 +1,10 @@
-fn main() {
-    println!("Hello, world!");
+//! Rust version of the classic Linux cat program.
+//!
+//! Concatenates files, or the pipeline, to standard output.
+
+use std::process::ExitCode;
+
+/// Entry point for the program.
+fn main() -> ExitCode {
+    rcat::run(std::env::args_os())
 }
@@ -0,0 +1,180 @@
+/// Test the --decompress switch, and running as zcat.
+///
+/// The compressed test files were made with the gzip, zstd, bzip2, and xz programs.
+/// fire_and_trees.* are two compressed files joined together, like this:
+/// ```
+/// :~$ (gzip -c fire_and_ice.txt; gzip -c trees.txt) > fire_and_trees.gz
+/// ```
+mod test_utils;
+
+#[cfg(test)]
+mod test {
+    use assert_cmd::prelude::*;
+    use predicates::prelude::*;
+
+    use crate::test_utils as tu;
+
+    /// Helper method to get what fire_and_ice.txt and trees.txt look like together.
+    fn fire_and_trees() -> Vec<u8> {
+        let mut expected = std::fs::read("tests/test_files/fire_and_ice.txt").unwrap();
+        expected.extend(std::fs::read("tests/test_files/trees.txt").unwrap());
+        expected
+    }
+
+    /// Decompress one file of each format:
+    /// ```
+    /// :~$ cat --decompress tests/test_files/trees.txt.gz
+    /// ```
+    /// Output should be the same as tests/test_files/trees.txt.
+    #[test]
+    fn each_format() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = std::fs::read("tests/test_files/trees.txt")?;
+        let formats = [
+            (cfg!(feature = "gzip"), "gz"),
+            (cfg!(feature = "zstd"), "zst"),
+            (cfg!(feature = "bzip2"), "bz2"),
+            (cfg!(feature = "xz"), "xz"),
+        ];
+
+        for (_, extension) in formats.iter().filter(|f| f.0) {
+            let mut cmd = tu::get_cmd();
+            cmd.arg("--decompress")
+                .arg(format!("tests/test_files/trees.txt.{}", extension))
+                .assert()
+                .success()
+                .stdout(predicate::eq(expected.clone()))
+                .code(predicate::eq(0));
+        }
+
+        Ok(())
+    }
+
+    /// Every compressed stream in a file should be decompressed, not just the first:
+    /// ```
+    /// :~$ cat --decompress tests/test_files/fire_and_trees.gz
+    /// ```
+    /// Output should be fire_and_ice.txt and then trees.txt.
+    #[test]
+    fn joined_streams() -> Result<(), Box<dyn std::error::Error>> {
+        let formats = [
+            (cfg!(feature = "gzip"), "gz"),
+            (cfg!(feature = "zstd"), "zst"),
+            (cfg!(feature = "bzip2"), "bz2"),
+            (cfg!(feature = "xz"), "xz"),
+        ];
+
+        for (_, extension) in formats.iter().filter(|f| f.0) {
+            let mut cmd = tu::get_cmd();
+            cmd.arg("--decompress")
+                .arg(format!("tests/test_files/fire_and_trees.{}", extension))
+                .assert()
+                .success()
+                .stdout(predicate::eq(fire_and_trees()))
+                .code(predicate::eq(0));
+        }
+
+        Ok(())
+    }
+
+    /// Plain and compressed files can be mixed, and standard input is decompressed too:
+    /// ```
+    /// :~$ cat tests/test_files/trees.txt.gz | cat --decompress -n tests/test_files/fire_and_ice.txt -
+    /// ```
+    /// Line numbers should carry on from the plain file into the compressed one.
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn mixed_with_stdin() -> Result<(), Box<dyn std::error::Error>> {
+        let expected: String = String::from_utf8(fire_and_trees())?
+            .lines()
+            .enumerate()
+            .map(|(i, line)| format!("{:>6}\t{}\n", i + 1, line))
+            .collect();
+
+        let mut cmd = assert_cmd::Command::from_std(tu::get_cmd());
+        cmd.arg("--decompress")
+            .arg("-n")
+            .arg("tests/test_files/fire_and_ice.txt")
+            .arg("-")
+            .write_stdin(std::fs::read("tests/test_files/trees.txt.gz")?)
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Without --decompress, compressed files are copied as they are:
+    /// ```
+    /// :~$ cat tests/test_files/trees.txt.gz
+    /// ```
+    #[test]
+    fn not_decompressed_by_default() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = std::fs::read("tests/test_files/trees.txt.gz")?;
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("tests/test_files/trees.txt.gz")
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// A file that's cut off partway should be reported, and the next file still copied:
+    /// ```
+    /// :~$ cat --decompress tests/test_files/truncated.gz tests/test_files/trees.txt
+    /// ```
+    /// The error from cat looks like this:
+    /// ```
+    /// cat: tests/test_files/truncated.gz: incomplete deflate stream
+    /// ```
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn truncated() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = tu::get_cmd();
+        cmd.arg("--decompress")
+            .arg("tests/test_files/truncated.gz")
+            .arg("tests/test_files/trees.txt")
+            .assert()
+            .failure()
+            .stdout(predicate::str::ends_with(std::fs::read_to_string("tests/test_files/trees.txt")?))
+            .stderr(predicate::str::starts_with("cat: tests/test_files/truncated.gz: "))
+            .code(predicate::eq(1));
+
+        Ok(())
+    }
+
+    /// Run through a link named zcat, cat should decompress without being asked,
+    /// and use that name in error messages:
+    /// ```
+    /// :~$ ln -s rcat zcat
+    /// :~$ zcat tests/test_files/fire_and_trees.gz tests/test_files/does_not_exist.gz
+    /// ```
+    /// The error looks like this:
+    /// ```
+    /// zcat: tests/test_files/does_not_exist.gz: No such file or directory
+    /// ```
+    #[cfg(all(unix, feature = "gzip"))]
+    #[test]
+    fn zcat_alias() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = std::env::temp_dir().join(format!("rcat_zcat_alias_{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let zcat = dir.join("zcat");
+        std::os::unix::fs::symlink(assert_cmd::cargo::cargo_bin("rcat"), &zcat)?;
+
+        let mut cmd = std::process::Command::new(&zcat);
+        let assert = cmd.arg("tests/test_files/fire_and_trees.gz")
+            .arg("tests/test_files/does_not_exist.gz")
+            .assert();
+        std::fs::remove_dir_all(&dir)?;
+
+        assert.failure()
+            .stdout(predicate::eq(fire_and_trees()))
+            .stderr(predicate::eq("zcat: tests/test_files/does_not_exist.gz: No such file or directory\n"))
+            .code(predicate::eq(1));
+
+        Ok(())
+    }
+}
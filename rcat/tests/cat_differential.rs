@@ -0,0 +1,48 @@
+/// Compare output with the system's GNU cat, if there is one.
+mod test_utils;
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+    use std::process::Command;
+
+    use crate::test_utils as tu;
+
+    /// Helper method to get a `Command` for the system cat. Returns `None` if there is no GNU cat.
+    fn system_cat() -> Option<Command> {
+        let path = ["/usr/bin/cat", "/bin/cat"].into_iter().find(|p| Path::new(p).exists())?;
+        let version = Command::new(path).arg("--version").output().ok()?;
+        if !String::from_utf8_lossy(&version.stdout).contains("GNU coreutils") {
+            return None;
+        }
+        Some(Command::new(path))
+    }
+
+    /// Run every display option, alone and in a few mixes, on one file and on several.
+    #[test]
+    fn same_as_system_cat() {
+        if system_cat().is_none() {
+            eprintln!("GNU cat not found, skipping differential test");
+            return;
+        }
+
+        let file_lists = [
+            vec!["tests/test_files/nonprinting.txt"],
+            vec!["tests/test_files/nonprinting.txt", "tests/test_files/fire_and_ice.txt",
+                "tests/test_files/nonprinting.txt", "tests/test_files/trees.txt"],
+        ];
+        let option_lists = [
+            "", "-n", "-b", "-s", "-E", "-T", "-v", "-A", "-e", "-t",
+            "-ns", "-bs", "-nb", "-nA", "-sE", "-vT",
+        ];
+
+        for files in &file_lists {
+            for options in option_lists {
+                let args: Vec<&str> = options.split_whitespace().chain(files.iter().copied()).collect();
+                let ours = tu::get_cmd().args(&args).output().unwrap();
+                let theirs = system_cat().unwrap().args(&args).output().unwrap();
+                assert!(ours.stdout == theirs.stdout, "output is different with arguments {:?}", args);
+            }
+        }
+    }
+}
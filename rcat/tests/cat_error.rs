@@ -0,0 +1,158 @@
+/// Test error handling.
+mod test_utils;
+
+#[cfg(test)]
+mod test {
+    use assert_cmd::prelude::*;
+    use predicates::prelude::*;
+
+    use crate::test_utils as tu;
+
+    /// Run cat with a file that does not exist between two that do:
+    /// ```
+    /// :~$ cat tests/test_files/fire_and_ice.txt tests/test_files/does_not_exist.txt tests/test_files/trees.txt
+    /// ```
+    /// Both files that exist should still be printed, and the error should look like this:
+    /// ```
+    /// cat: tests/test_files/does_not_exist.txt: No such file or directory
+    /// ```
+    /// The exit code should be 1.
+    #[test]
+    fn missing_file() -> Result<(), Box<dyn std::error::Error>> {
+        let expected_linux = "cat: tests/test_files/does_not_exist.txt: No such file or directory\n";
+        let expected_windows = "cat: tests/test_files/does_not_exist.txt: The system cannot find the file specified.\n";
+        let expected_err = match std::env::consts::OS {
+            "linux" => expected_linux,
+            "windows" => expected_windows,
+            _ => panic!("Not tested on this operating system: {}", std::env::consts::OS),
+        };
+        let mut expected = std::fs::read("tests/test_files/fire_and_ice.txt")?;
+        expected.extend(std::fs::read("tests/test_files/trees.txt")?);
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("tests/test_files/fire_and_ice.txt")
+            .arg("tests/test_files/does_not_exist.txt")
+            .arg("tests/test_files/trees.txt")
+            .assert()
+            .failure()
+            .stdout(predicate::eq(expected))
+            .stderr(predicate::eq(expected_err))
+            .code(predicate::eq(1));
+
+        Ok(())
+    }
+
+    /// Run cat with a directory:
+    /// ```
+    /// :~$ cat tests/test_files
+    /// ```
+    /// Output from cat looks like this:
+    /// ```
+    /// cat: tests/test_files: Is a directory
+    /// ```
+    #[cfg(unix)]
+    #[test]
+    fn directory() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = tu::get_cmd();
+        cmd.arg("tests/test_files")
+            .assert()
+            .failure()
+            .stdout(predicate::str::is_empty())
+            .stderr(predicate::eq("cat: tests/test_files: Is a directory\n"))
+            .code(predicate::eq(1));
+
+        Ok(())
+    }
+
+    /// Append a file to itself:
+    /// ```
+    /// :~$ cat file >> file
+    /// ```
+    /// That would never finish, so cat should refuse, like this:
+    /// ```
+    /// cat: file: input file is output file
+    /// ```
+    /// The file should not change.
+    #[cfg(unix)]
+    #[test]
+    fn input_is_output() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = std::env::temp_dir().join(format!("rcat_input_is_output_{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("file.txt");
+        std::fs::write(&path, "some text\n")?;
+        let out = std::fs::OpenOptions::new().append(true).open(&path)?;
+
+        let mut cmd = tu::get_cmd();
+        let assert = cmd.arg(&path)
+            .stdout(out)
+            .assert();
+        let contents = std::fs::read_to_string(&path)?;
+        std::fs::remove_dir_all(&dir)?;
+
+        assert.failure()
+            .stderr(predicate::eq(format!("cat: {}: input file is output file\n", path.display())))
+            .code(predicate::eq(1));
+        assert_eq!(contents, "some text\n");
+
+        Ok(())
+    }
+
+    /// Standard input can be the output file too:
+    /// ```
+    /// :~$ cat < file >> file
+    /// ```
+    /// Output from cat looks like this:
+    /// ```
+    /// cat: -: input file is output file
+    /// ```
+    #[cfg(unix)]
+    #[test]
+    fn stdin_is_output() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = std::env::temp_dir().join(format!("rcat_stdin_is_output_{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("file.txt");
+        std::fs::write(&path, "some text\n")?;
+        let input = std::fs::File::open(&path)?;
+        let out = std::fs::OpenOptions::new().append(true).open(&path)?;
+
+        let mut cmd = tu::get_cmd();
+        let assert = cmd.stdin(input)
+            .stdout(out)
+            .assert();
+        let contents = std::fs::read_to_string(&path)?;
+        std::fs::remove_dir_all(&dir)?;
+
+        assert.failure()
+            .stderr(predicate::eq("cat: -: input file is output file\n"))
+            .code(predicate::eq(1));
+        assert_eq!(contents, "some text\n");
+
+        Ok(())
+    }
+
+    /// An empty file can be appended to itself, since there's nothing to copy:
+    /// ```
+    /// :~$ cat empty >> empty
+    /// ```
+    #[cfg(unix)]
+    #[test]
+    fn empty_input_is_output() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = std::env::temp_dir().join(format!("rcat_empty_input_is_output_{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("empty.txt");
+        std::fs::write(&path, "")?;
+        let out = std::fs::OpenOptions::new().append(true).open(&path)?;
+
+        let mut cmd = tu::get_cmd();
+        let assert = cmd.arg(&path)
+            .stdout(out)
+            .assert();
+        std::fs::remove_dir_all(&dir)?;
+
+        assert.success()
+            .stderr(predicate::str::is_empty())
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+/// Test copying files to standard output.
+mod test_utils;
+
+#[cfg(test)]
+mod test {
+    use assert_cmd::prelude::*;
+    use predicates::prelude::*;
+
+    use crate::test_utils as tu;
+
+    /// Run cat with one small file:
+    /// ```
+    /// :~$ cat tests/test_files/trees.txt
+    /// ```
+    /// Output should be the file, exactly as it is.
+    #[test]
+    fn one_file() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = std::fs::read("tests/test_files/trees.txt")?;
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("tests/test_files/trees.txt")
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected))
+            .stderr(predicate::str::is_empty())
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Run cat with two files:
+    /// ```
+    /// :~$ cat tests/test_files/fire_and_ice.txt tests/test_files/trees.txt
+    /// ```
+    /// Output should be the first file, then the second.
+    #[test]
+    fn two_files_in_order() -> Result<(), Box<dyn std::error::Error>> {
+        let mut expected = std::fs::read("tests/test_files/fire_and_ice.txt")?;
+        expected.extend(std::fs::read("tests/test_files/trees.txt")?);
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("tests/test_files/fire_and_ice.txt")
+            .arg("tests/test_files/trees.txt")
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Run cat with the same file twice:
+    /// ```
+    /// :~$ cat tests/test_files/trees.txt tests/test_files/trees.txt
+    /// ```
+    /// Output should be the file, twice.
+    #[test]
+    fn same_file_twice() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = std::fs::read("tests/test_files/trees.txt")?.repeat(2);
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("tests/test_files/trees.txt")
+            .arg("tests/test_files/trees.txt")
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+/// Test copying inputs that are bigger than the copy buffer, to pipes and to files.
+///
+/// Without display options, these go through the kernel's copy system calls on
+/// Linux (see `copy::zero_copy()`), so this checks that nothing is lost or repeated
+/// when they're used, or when cat falls back to reading and writing.
+mod test_utils;
+
+#[cfg(test)]
+mod test {
+    use std::fs::{File, OpenOptions};
+    use std::path::PathBuf;
+
+    use assert_cmd::prelude::*;
+    use predicates::prelude::*;
+
+    use crate::test_utils as tu;
+
+    /// Size of the test file: a few copy buffers, plus a bit.
+    const SIZE: usize = 3 * 1024 * 1024 + 17;
+
+    /// Helper method to make a temp directory with a big file in it.
+    /// Returns the directory, the file, and what's in the file.
+    fn big_file(name: &str) -> (PathBuf, PathBuf, Vec<u8>) {
+        let dir = std::env::temp_dir().join(format!("rcat_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("big.dat");
+        let data: Vec<u8> = (0..SIZE).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+        (dir, path, data)
+    }
+
+    /// Copy a big file to a pipe:
+    /// ```
+    /// :~$ cat big.dat | ...
+    /// ```
+    #[test]
+    fn file_to_pipe() -> Result<(), Box<dyn std::error::Error>> {
+        let (dir, path, data) = big_file("file_to_pipe");
+
+        let mut cmd = tu::get_cmd();
+        let assert = cmd.arg(&path).assert();
+        std::fs::remove_dir_all(&dir)?;
+
+        assert.success()
+            .stdout(predicate::eq(data))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Copy a big file from standard input to a pipe:
+    /// ```
+    /// :~$ cat < big.dat | ...
+    /// ```
+    #[test]
+    fn stdin_file_to_pipe() -> Result<(), Box<dyn std::error::Error>> {
+        let (dir, path, data) = big_file("stdin_file_to_pipe");
+
+        let mut cmd = tu::get_cmd();
+        let assert = cmd.stdin(File::open(&path)?).assert();
+        std::fs::remove_dir_all(&dir)?;
+
+        assert.success()
+            .stdout(predicate::eq(data))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Copy a big file to another file, then append it again:
+    /// ```
+    /// :~$ cat big.dat big.dat > copy.dat
+    /// :~$ cat big.dat >> copy.dat
+    /// ```
+    /// The copy should have the big file in it three times.
+    #[test]
+    fn file_to_file() -> Result<(), Box<dyn std::error::Error>> {
+        let (dir, path, data) = big_file("file_to_file");
+        let copy = dir.join("copy.dat");
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg(&path)
+            .arg(&path)
+            .stdout(File::create(&copy)?)
+            .assert()
+            .success();
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg(&path)
+            .stdout(OpenOptions::new().append(true).open(&copy)?)
+            .assert()
+            .success();
+
+        let copied = std::fs::read(&copy)?;
+        std::fs::remove_dir_all(&dir)?;
+        assert!(copied == data.repeat(3), "copy should be the file three times");
+
+        Ok(())
+    }
+
+    /// Pipe a big input through, with a file before it:
+    /// ```
+    /// :~$ ... | cat tests/test_files/trees.txt -
+    /// ```
+    #[test]
+    fn file_then_pipe() -> Result<(), Box<dyn std::error::Error>> {
+        let data: Vec<u8> = (0..SIZE).map(|i| (i % 13) as u8).collect();
+        let mut expected = std::fs::read("tests/test_files/trees.txt")?;
+        expected.extend(&data);
+
+        let mut cmd = assert_cmd::Command::from_std(tu::get_cmd());
+        cmd.arg("tests/test_files/trees.txt")
+            .arg("-")
+            .write_stdin(data)
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+}
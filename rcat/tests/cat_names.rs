@@ -0,0 +1,31 @@
+/// Test file names that aren't UTF-8. They're read like any other file, and quoted
+/// in error messages.
+#[cfg(test)]
+#[cfg(unix)]
+mod test {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    use harness::{Scene, UCmd};
+
+    /// Copy a file with a Latin-1 name, and one that isn't there:
+    /// ```
+    /// :~$ cat $'caf\xe9.txt' $'nope\xff'
+    /// ```
+    /// Output from cat looks like this:
+    /// ```
+    /// one two
+    /// cat: 'nope'$'\377': No such file or directory
+    /// ```
+    /// The exit code should be 1.
+    #[test]
+    fn latin1_names() {
+        let name = OsStr::from_bytes(b"caf\xe9.txt");
+        let scene = Scene::new().file(name, "one two\n");
+
+        let capture = UCmd::new("rcat").arg(name).arg(OsStr::from_bytes(b"nope\xff")).in_scene(&scene).run();
+        assert_eq!(capture.stdout, b"one two\n");
+        assert_eq!(capture.stderr, b"cat: 'nope'$'\\377': No such file or directory\n");
+        assert_eq!(capture.code, Some(1));
+    }
+}
@@ -0,0 +1,197 @@
+/// Test the display options: -n, -b, -s, -E, -T, -v, -A, -e, and -t.
+///
+/// Most of these use tests/test_files/nonprinting.txt, which has tabs, \r\n line
+/// endings, control characters, Latin-1 bytes, a run of empty lines, and no
+/// newline at the end.
+mod test_utils;
+
+#[cfg(test)]
+mod test {
+    use assert_cmd::prelude::*;
+    use predicates::prelude::*;
+
+    use crate::test_utils as tu;
+
+    /// Show everything:
+    /// ```
+    /// :~$ cat -A tests/test_files/nonprinting.txt
+    /// ```
+    /// Output should look like this:
+    /// ```
+    /// Tabs^Iand^Ispaces ^M$
+    /// $
+    /// $
+    /// $
+    /// Control ^A^[[0m and delete ^?^M$
+    /// Latin-1 cafM-i, high M-^@M-^IM-^?$
+    /// $
+    /// ^Ilast line, no newline
+    /// ```
+    #[test]
+    fn show_all() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = concat!(
+            "Tabs^Iand^Ispaces ^M$\n",
+            "$\n",
+            "$\n",
+            "$\n",
+            "Control ^A^[[0m and delete ^?^M$\n",
+            "Latin-1 cafM-i, high M-^@M-^IM-^?$\n",
+            "$\n",
+            "^Ilast line, no newline");
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("-A")
+            .arg("tests/test_files/nonprinting.txt")
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// -e is the same as -vE, and -t is the same as -vT:
+    /// ```
+    /// :~$ cat -e -t tests/test_files/nonprinting.txt
+    /// ```
+    /// Output should be the same as -A.
+    #[test]
+    fn e_and_t_same_as_show_all() -> Result<(), Box<dyn std::error::Error>> {
+        let show_all = tu::get_cmd().arg("-A").arg("tests/test_files/nonprinting.txt").output()?;
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("-e")
+            .arg("-t")
+            .arg("tests/test_files/nonprinting.txt")
+            .assert()
+            .success()
+            .stdout(predicate::eq(show_all.stdout))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Number lines that aren't empty, and squeeze the empty lines:
+    /// ```
+    /// :~$ cat -bs tests/test_files/nonprinting.txt
+    /// ```
+    /// Output should look like this, with a tab after each number. The \r and the control
+    /// characters are left as they are:
+    /// ```
+    ///      1  Tabs  and  spaces
+    ///
+    ///      2  Control  and delete
+    ///      3  Latin-1 caf, high
+    ///
+    ///      4    last line, no newline
+    /// ```
+    #[test]
+    fn number_nonblank_squeeze() -> Result<(), Box<dyn std::error::Error>> {
+        let expected: &[u8] = concat!(
+            "     1\tTabs\tand\tspaces \r\n",
+            "\n",
+            "     2\tControl \x01\x1b[0m and delete \x7f\r\n").as_bytes();
+        let mut expected = expected.to_vec();
+        expected.extend(b"     3\tLatin-1 caf\xe9, high \x80\x89\xff\n");
+        expected.extend(b"\n");
+        expected.extend(b"     4\t\tlast line, no newline");
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("-bs")
+            .arg("tests/test_files/nonprinting.txt")
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Line numbers carry on from one file to the next. The first input has no newline
+    /// at the end, so the first line of the second file is joined to its last line:
+    /// ```
+    /// :~$ printf 'one\nno newline' | cat -n - tests/test_files/fire_and_ice.txt
+    /// ```
+    /// The start of the output should look like this, with a tab after each number:
+    /// ```
+    ///      1  one
+    ///      2  no newlineFire & Ice
+    ///      3
+    ///      4  Some say the world will end in fire,
+    /// ```
+    #[test]
+    fn number_across_files() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = concat!(
+            "     1\tone\n",
+            "     2\tno newlineFire & Ice\n",
+            "     3\t\n",
+            "     4\tSome say the world will end in fire,\n");
+
+        let mut cmd = assert_cmd::Command::from_std(tu::get_cmd());
+        cmd.arg("-n")
+            .arg("-")
+            .arg("tests/test_files/fire_and_ice.txt")
+            .write_stdin("one\nno newline")
+            .assert()
+            .success()
+            .stdout(predicate::str::starts_with(expected))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Empty lines are squeezed across files too:
+    /// ```
+    /// :~$ printf 'x\n\n\n' > a; printf '\n\ny\n' > b
+    /// :~$ cat -s a - < b
+    /// ```
+    /// Output should look like this:
+    /// ```
+    /// x
+    ///
+    /// y
+    /// ```
+    #[test]
+    fn squeeze_across_files() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = std::env::temp_dir().join(format!("rcat_squeeze_across_files_{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("a.txt");
+        std::fs::write(&path, "x\n\n\n")?;
+
+        let mut cmd = assert_cmd::Command::from_std(tu::get_cmd());
+        let assert = cmd.arg("-s")
+            .arg(&path)
+            .arg("-")
+            .write_stdin("\n\ny\n")
+            .assert();
+        std::fs::remove_dir_all(&dir)?;
+
+        assert.success()
+            .stdout(predicate::eq("x\n\ny\n"))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Show tabs and line ends, but leave other bytes alone:
+    /// ```
+    /// :~$ printf 'a\tb\r\nc\r\r\n' | cat -ET
+    /// ```
+    /// Output should look like this. A \r is only shown as ^M when it's part of a \r\n line ending:
+    /// ```
+    /// a^Ib^M$
+    /// c\r^M$
+    /// ```
+    #[test]
+    fn show_ends_and_tabs() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = assert_cmd::Command::from_std(tu::get_cmd());
+        cmd.arg("-ET")
+            .write_stdin("a\tb\r\nc\r\r\n")
+            .assert()
+            .success()
+            .stdout(predicate::eq("a^Ib^M$\nc\r^M$\n"))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+/// Test writing to pipes that close early, to full devices, and the -u switch.
+mod test_utils;
+
+#[cfg(all(test, unix))]
+mod test {
+    use std::io::{Read, Write};
+    use std::process::{Child, Stdio};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    use crate::test_utils as tu;
+
+    /// How long to wait for cat before deciding it's stuck.
+    const TIMEOUT: Duration = Duration::from_secs(20);
+
+    /// Helper method to wait for `child` to finish, and get its exit code and standard error.
+    /// Kills it and fails the test if it takes longer than `TIMEOUT`.
+    fn finish(mut child: Child) -> (Option<i32>, String) {
+        let mut stderr = child.stderr.take().unwrap();
+        let (send, receive) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut text = String::new();
+            stderr.read_to_string(&mut text).unwrap();
+            send.send(text).unwrap();
+        });
+
+        match receive.recv_timeout(TIMEOUT) {
+            Ok(text) => (child.wait().unwrap().code(), text),
+            Err(_) => {
+                child.kill().unwrap();
+                panic!("cat did not finish");
+            },
+        }
+    }
+
+    /// Helper method to start cat with `args`, read a little of its output, and then
+    /// close the pipe, like this:
+    /// ```
+    /// :~$ cat /dev/zero | head -c 10
+    /// ```
+    /// Returns the exit code and standard error, once cat finishes.
+    fn close_early(args: &[&str]) -> (Option<i32>, String) {
+        let mut child = tu::get_cmd()
+            .args(args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let mut stdout = child.stdout.take().unwrap();
+        let mut start = [0u8; 10];
+        stdout.read_exact(&mut start).unwrap();
+        drop(stdout);
+
+        finish(child)
+    }
+
+    /// Copying a never-ending input to a pipe that gets closed should stop quietly:
+    /// ```
+    /// :~$ cat /dev/zero | head -c 10
+    /// ```
+    #[test]
+    fn closed_pipe_endless_input() {
+        assert_eq!(close_early(&["/dev/zero"]), (Some(0), String::new()));
+    }
+
+    /// The same, with a display option, so the output goes through a buffer:
+    /// ```
+    /// :~$ cat -v /dev/zero | head -c 10
+    /// ```
+    #[test]
+    fn closed_pipe_formatted() {
+        assert_eq!(close_early(&["-v", "/dev/zero"]), (Some(0), String::new()));
+        assert_eq!(close_early(&["-n", "-u", "/dev/zero"]), (Some(0), String::new()));
+    }
+
+    /// A pipe closed before anything is read should also stop quietly, even with
+    /// more files still to go:
+    /// ```
+    /// :~$ cat tests/test_files/trees.txt /dev/zero tests/test_files/trees.txt | true
+    /// ```
+    #[test]
+    fn closed_pipe_before_reading() {
+        let mut child = tu::get_cmd()
+            .args(["tests/test_files/trees.txt", "/dev/zero", "tests/test_files/trees.txt"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        drop(child.stdout.take());
+
+        assert_eq!(finish(child), (Some(0), String::new()));
+    }
+
+    /// Other write errors are still reported:
+    /// ```
+    /// :~$ cat tests/test_files/trees.txt > /dev/full
+    /// ```
+    /// Output from cat looks like this:
+    /// ```
+    /// cat: write error: No space left on device
+    /// ```
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn full_device() {
+        for args in [vec!["tests/test_files/trees.txt"], vec!["-n", "tests/test_files/trees.txt"]] {
+            let child = tu::get_cmd()
+                .args(&args)
+                .stdout(std::fs::OpenOptions::new().write(true).open("/dev/full").unwrap())
+                .stderr(Stdio::piped())
+                .spawn()
+                .unwrap();
+
+            assert_eq!(finish(child), (Some(1), "cat: write error: No space left on device\n".to_owned()),
+                "arguments {:?}", args);
+        }
+    }
+
+    /// With -u, a partial line is written as soon as it's read, without waiting for
+    /// the rest of the line or the end of the input:
+    /// ```
+    /// :~$ cat -u -n
+    /// Name: 
+    /// ```
+    #[test]
+    fn unbuffered_partial_line() {
+        let mut child = tu::get_cmd()
+            .args(["-u", "-n"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let mut stdin = child.stdin.take().unwrap();
+        stdin.write_all(b"Name: ").unwrap();
+        stdin.flush().unwrap();
+
+        let mut stdout = child.stdout.take().unwrap();
+        let (send, receive) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut start = [0u8; 13];
+            stdout.read_exact(&mut start).unwrap();
+            send.send(start).unwrap();
+        });
+        let start = receive.recv_timeout(TIMEOUT);
+        drop(stdin);
+        assert_eq!(finish(child), (Some(0), String::new()));
+        assert_eq!(start.expect("partial line should be written right away"), *b"     1\tName: ");
+    }
+}
@@ -0,0 +1,118 @@
+/// Test the --pretty switch.
+///
+/// Colors are ANSI escape codes: bold (`1`) for headers, dark gray (`90`) for the
+/// line number gutter, and reverse video (`7`) for bytes that can't be shown.
+mod test_utils;
+
+#[cfg(test)]
+mod test {
+    use assert_cmd::prelude::*;
+    use predicates::prelude::*;
+
+    use crate::test_utils as tu;
+
+    /// Helper method to make the gutter that goes in front of line `n`.
+    fn gutter(n: usize) -> String {
+        format!("\x1b[90m{:>6} │\x1b[0m ", n)
+    }
+
+    /// Without a terminal to write to, --pretty leaves the output alone:
+    /// ```
+    /// :~$ cat --pretty tests/test_files/sample.json | cat
+    /// ```
+    /// Output should be the same as tests/test_files/sample.json.
+    #[test]
+    fn auto_without_terminal() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = std::fs::read("tests/test_files/sample.json")?;
+        for when in ["--pretty", "--pretty=auto", "--pretty=never"] {
+            let mut cmd = tu::get_cmd();
+            cmd.arg(when)
+                .arg("tests/test_files/sample.json")
+                .assert()
+                .success()
+                .stdout(predicate::eq(expected.clone()))
+                .code(predicate::eq(0));
+        }
+
+        Ok(())
+    }
+
+    /// A header, line numbers, and colors for JSON:
+    /// ```
+    /// :~$ cat --pretty=always tests/test_files/sample.json
+    /// ```
+    /// Output should have the file name in bold, then each line with its number,
+    /// with keys in blue, strings in green, numbers in cyan, and `true` in magenta.
+    #[test]
+    fn always_highlights_json() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = [
+            "\x1b[1mtests/test_files/sample.json\x1b[0m\n".to_string(),
+            format!("{}{{\n", gutter(1)),
+            format!("{}  \x1b[34m\"name\"\x1b[0m: \x1b[32m\"rcat\"\x1b[0m,\n", gutter(2)),
+            format!("{}  \x1b[34m\"tabs\"\x1b[0m: \x1b[36m4\x1b[0m,\n", gutter(3)),
+            format!("{}  \x1b[34m\"pretty\"\x1b[0m: \x1b[35mtrue\x1b[0m\n", gutter(4)),
+            format!("{}}}\n", gutter(5)),
+        ].concat();
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("--pretty=always")
+            .arg("tests/test_files/sample.json")
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected.as_bytes()))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Standard input is called STDIN, and control characters are shown in reverse video:
+    /// ```
+    /// :~$ printf 'a\x01b\n' | cat --pretty=always
+    /// ```
+    /// Output should be:
+    /// ```
+    /// STDIN
+    ///      1 │ a^Ab
+    /// ```
+    #[test]
+    fn always_from_stdin() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = format!("\x1b[1mSTDIN\x1b[0m\n{}a\x1b[7m^A\x1b[0mb\n", gutter(1));
+
+        let mut cmd = assert_cmd::Command::from_std(tu::get_cmd());
+        cmd.arg("--pretty=always")
+            .write_stdin("a\x01b\n")
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected.as_bytes()))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Each file gets its own header and line numbers, with a blank line between files,
+    /// and the display options are left out:
+    /// ```
+    /// :~$ cat --pretty=always -n tests/test_files/trees.txt tests/test_files/trees.txt
+    /// ```
+    #[test]
+    fn always_with_more_files() -> Result<(), Box<dyn std::error::Error>> {
+        let trees = std::fs::read_to_string("tests/test_files/trees.txt")?;
+        let mut file = "\x1b[1mtests/test_files/trees.txt\x1b[0m\n".to_string();
+        for (i, line) in trees.lines().enumerate() {
+            file.push_str(&format!("{}{}\n", gutter(i + 1), line));
+        }
+        let expected = format!("{}\n{}", file, file);
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("--pretty=always")
+            .arg("-n")
+            .arg("tests/test_files/trees.txt")
+            .arg("tests/test_files/trees.txt")
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected.as_bytes()))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,158 @@
+/// Test the --lines and --bytes switches.
+mod test_utils;
+
+#[cfg(test)]
+mod test {
+    use assert_cmd::prelude::*;
+    use predicates::prelude::*;
+
+    use crate::test_utils as tu;
+
+    /// Helper method to get what fire_and_ice.txt and trees.txt look like together.
+    fn fire_and_trees() -> Vec<u8> {
+        let mut expected = std::fs::read("tests/test_files/fire_and_ice.txt").unwrap();
+        expected.extend(std::fs::read("tests/test_files/trees.txt").unwrap());
+        expected
+    }
+
+    /// Lines from one file:
+    /// ```
+    /// :~$ cat --lines=2:4 tests/test_files/fire_and_ice.txt
+    /// ```
+    /// Output should be lines 2, 3, and 4.
+    #[test]
+    fn lines_from_file() -> Result<(), Box<dyn std::error::Error>> {
+        let file = std::fs::read_to_string("tests/test_files/fire_and_ice.txt")?;
+        let expected: String = file.split_inclusive('\n').skip(1).take(3).collect();
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("--lines=2:4")
+            .arg("tests/test_files/fire_and_ice.txt")
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected.as_bytes()))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Lines are counted across files, and a range can go from one file into the next:
+    /// ```
+    /// :~$ cat --lines=12: tests/test_files/fire_and_ice.txt tests/test_files/trees.txt
+    /// ```
+    /// Output should be the last 2 lines of fire_and_ice.txt, then all of trees.txt.
+    #[test]
+    fn lines_across_files() -> Result<(), Box<dyn std::error::Error>> {
+        let both = String::from_utf8(fire_and_trees())?;
+        let expected: String = both.split_inclusive('\n').skip(11).collect();
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("--lines=12:")
+            .arg("tests/test_files/fire_and_ice.txt")
+            .arg("tests/test_files/trees.txt")
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected.as_bytes()))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Bytes from a file and standard input, with the range starting in the file
+    /// (which is skipped by seeking) and ending in standard input:
+    /// ```
+    /// :~$ cat tests/test_files/trees.txt | cat --bytes=300:420 tests/test_files/fire_and_ice.txt -
+    /// ```
+    /// Output should be bytes 300 to 420 of both files together.
+    #[test]
+    fn bytes_across_file_and_stdin() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = fire_and_trees()[299..420].to_vec();
+
+        let mut cmd = assert_cmd::Command::from_std(tu::get_cmd());
+        cmd.arg("--bytes=300:420")
+            .arg("tests/test_files/fire_and_ice.txt")
+            .arg("-")
+            .write_stdin(std::fs::read("tests/test_files/trees.txt")?)
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// A byte range past the end of the input gives nothing, and files after the
+    /// end of a range aren't opened:
+    /// ```
+    /// :~$ cat --bytes=:10 tests/test_files/trees.txt tests/test_files/no_file.txt
+    /// ```
+    /// Output should be the first 10 bytes of trees.txt, with no error.
+    #[test]
+    fn bytes_end_early() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = std::fs::read("tests/test_files/trees.txt")?[..10].to_vec();
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("--bytes=:10")
+            .arg("tests/test_files/trees.txt")
+            .arg("tests/test_files/no_file.txt")
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected))
+            .code(predicate::eq(0));
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("--bytes=1M:")
+            .arg("tests/test_files/trees.txt")
+            .assert()
+            .success()
+            .stdout(predicate::str::is_empty())
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// The range is picked out of compressed files after they're decompressed,
+    /// and the display options work on what's picked out:
+    /// ```
+    /// :~$ cat --decompress -n --lines=3:4 tests/test_files/trees.txt.gz
+    /// ```
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn lines_with_decompress_and_number() -> Result<(), Box<dyn std::error::Error>> {
+        let file = std::fs::read_to_string("tests/test_files/trees.txt")?;
+        let expected: String = file.split_inclusive('\n').skip(2).take(2).enumerate()
+            .map(|(i, line)| format!("{:>6}\t{}", i + 1, line))
+            .collect();
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("--decompress")
+            .arg("-n")
+            .arg("--lines=3:4")
+            .arg("tests/test_files/trees.txt.gz")
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected.as_bytes()))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Ranges that don't make sense are usage errors:
+    /// ```
+    /// :~$ cat --lines=5:3 tests/test_files/trees.txt
+    /// ```
+    #[test]
+    fn bad_ranges() -> Result<(), Box<dyn std::error::Error>> {
+        for range in ["--lines=5:3", "--lines=0:3", "--bytes=x", "--bytes="] {
+            let mut cmd = tu::get_cmd();
+            cmd.arg(range)
+                .arg("tests/test_files/trees.txt")
+                .assert()
+                .failure()
+                .stdout(predicate::str::is_empty())
+                .stderr(predicate::str::contains("invalid value"));
+        }
+
+        Ok(())
+    }
+}
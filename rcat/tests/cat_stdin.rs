@@ -0,0 +1,72 @@
+/// Test reading from standard input.
+mod test_utils;
+
+#[cfg(test)]
+mod test {
+    use predicates::prelude::*;
+
+    use crate::test_utils as tu;
+
+    /// Run cat with no file names, reading from the pipeline:
+    /// ```
+    /// :~$ echo "from the pipeline" | cat
+    /// ```
+    /// Output should look like this:
+    /// ```
+    /// from the pipeline
+    /// ```
+    #[test]
+    fn no_args() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = assert_cmd::Command::from_std(tu::get_cmd());
+        cmd.write_stdin("from the pipeline\n")
+            .assert()
+            .success()
+            .stdout(predicate::eq("from the pipeline\n"))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// A file named - is standard input, so it can go between other files:
+    /// ```
+    /// :~$ echo "in the middle" | cat tests/test_files/fire_and_ice.txt - tests/test_files/trees.txt
+    /// ```
+    /// Output should be the first file, then the pipeline, then the second file.
+    #[test]
+    fn dash_between_files() -> Result<(), Box<dyn std::error::Error>> {
+        let mut expected = std::fs::read("tests/test_files/fire_and_ice.txt")?;
+        expected.extend(b"in the middle\n");
+        expected.extend(std::fs::read("tests/test_files/trees.txt")?);
+
+        let mut cmd = assert_cmd::Command::from_std(tu::get_cmd());
+        cmd.arg("tests/test_files/fire_and_ice.txt")
+            .arg("-")
+            .arg("tests/test_files/trees.txt")
+            .write_stdin("in the middle\n")
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Binary data from the pipeline should come out unchanged, even if it isn't UTF-8:
+    /// ```
+    /// :~$ printf '\xff\x00\xfe\n' | cat -
+    /// ```
+    #[test]
+    fn binary_stdin() -> Result<(), Box<dyn std::error::Error>> {
+        let data: Vec<u8> = vec![0xff, 0x00, 0xfe, b'\n'];
+
+        let mut cmd = assert_cmd::Command::from_std(tu::get_cmd());
+        cmd.arg("-")
+            .write_stdin(data.clone())
+            .assert()
+            .success()
+            .stdout(predicate::eq(data))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+//! Rust version of the classic Linux wc program.
+//! 
+//! Counts words, bytes, and lines from a file or from the pipeline.
+//!
+//! The program itself is in `main.rs`, which just calls `run()`. `UTILITY` lets the
+//! `coreutils` binary run it too.
+
+use std::ffi::OsString;
+use std::process::ExitCode;
+
+use clap::{Parser};
+use shared::error::{parse_args, EXIT_FAILURE};
+use shared::utility::Utility;
+
+mod wc; 
+
+/// wc, for the `coreutils` binary.
+pub const UTILITY: Utility = Utility { name: "wc", aliases: &[], main: run::<Vec<OsString>, OsString> };
+
+/// Struct that contains information about the command line options that were entered.
+/// Used by the `clap` library.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    #[arg(short = 'l', long)]
+    /// Print the newline count
+    lines: bool,
+
+    #[arg(short = 'c', long)]
+    /// Print the byte counts
+    bytes: bool,
+
+    #[arg(short = 'm', long)]
+    /// Print the character counts
+    chars: bool,
+
+    #[arg(short = 'w', long)]
+    /// Print the word counts
+    words: bool,
+
+    #[arg(short = 'L', long = "max-line-length")]
+    /// Print the maximum display width
+    max_line_length: bool,
+
+    #[arg(long, value_name = "KEY[,reverse]", value_parser = wc::table::parse_sort_spec)]
+    /// Sort rows by lines, words, chars, bytes, or name. Add ",reverse" to sort largest first
+    sort: Option<wc::table::SortSpec>,
+
+    #[arg(long, value_name = "N")]
+    /// Only print the first N rows, after sorting and filtering
+    top: Option<usize>,
+
+    #[arg(long, value_name = "N")]
+    /// Hide files with fewer than N lines
+    min_lines: Option<usize>,
+
+    #[arg(long, value_name = "N")]
+    /// Hide files with more than N lines
+    max_lines: Option<usize>,
+
+    #[arg(long, value_name = "N")]
+    /// Hide files with fewer than N words
+    min_words: Option<usize>,
+
+    #[arg(long, value_name = "N")]
+    /// Hide files with more than N words
+    max_words: Option<usize>,
+
+    #[arg(long, value_name = "N")]
+    /// Hide files with fewer than N characters
+    min_chars: Option<usize>,
+
+    #[arg(long, value_name = "N")]
+    /// Hide files with more than N characters
+    max_chars: Option<usize>,
+
+    #[arg(long, value_name = "N")]
+    /// Hide files with fewer than N bytes
+    min_bytes: Option<usize>,
+
+    #[arg(long, value_name = "N")]
+    /// Hide files with more than N bytes
+    max_bytes: Option<usize>,
+
+    #[arg(long, value_enum, default_value_t = wc::encoding::Encoding::Auto)]
+    /// Encoding used to count characters, words, lines, and line widths. Byte counts are always raw bytes
+    encoding: wc::encoding::Encoding,
+
+    #[arg(long)]
+    /// Count files that are given more than once (through symlinks or different paths) only once in the total
+    unique: bool,
+
+    #[arg(long)]
+    /// Print the min, mean, median, 95th percentile, and max line length of each file
+    line_stats: bool,
+
+    #[arg(long, value_enum, value_name = "UNIT", num_args = 0..=1, require_equals = true, default_missing_value = "chars")]
+    /// Print a histogram of line lengths in chars (the default) or bytes
+    histogram: Option<wc::line_stats::LengthUnit>,
+
+    /// List of files to process
+    files: Option<Vec<OsString>>,
+}
+
+/// Run wc with the given command line, including the program name, and return its exit code.
+/// A bad command line exits with 1, like the standard wc.
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments, starting with the program name.
+pub fn run<I, T>(args: I) -> ExitCode
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    let args: Vec<OsString> = args.into_iter().map(Into::into).collect();
+    let mut clap_args: Cli = parse_args(&args, EXIT_FAILURE);
+
+    // if all are set to false, then none were set on the command line
+    // set all but chars and max line length to true
+    if !clap_args.lines
+        && !clap_args.bytes
+        && !clap_args.words
+        && !clap_args.chars
+        && !clap_args.max_line_length {
+        clap_args.lines = true;
+        clap_args.bytes = true;
+        clap_args.words = true;
+    }
+
+    wc::wc(clap_args)
+}
@@ -0,0 +1,178 @@
+//! Counting lines, words, characters, and line widths in one pass over decoded text.
+
+use unicode_width::UnicodeWidthChar;
+
+use super::encoding::Decoded;
+use super::line_stats::LineStats;
+use super::FileSummary;
+
+/// Keeps running counts while a file is decoded. Call `push()` for each
+/// unit of decoded text, then `finish()` to get the `FileSummary`.
+#[derive(Debug, Default)]
+pub(crate) struct Counter {
+    summary: FileSummary,
+    /// Whether the last unit was part of a word.
+    in_word: bool,
+    /// Whether nothing has been seen since the last newline.
+    at_line_start: bool,
+    /// Display width of the current line so far.
+    line_width: usize,
+    /// Length of the current line so far, in bytes and characters. Used for line stats.
+    line_bytes: usize,
+    line_chars: usize,
+    /// Size in bytes of the last unit, if it was a `\r`. Used to leave `\r\n` line
+    /// endings out of line lengths.
+    last_cr: Option<usize>,
+}
+
+impl Counter {
+    /// Make a new counter. If `line_stats` is true, the length of every line is
+    /// kept in `FileSummary.line_stats`.
+    pub(crate) fn new(line_stats: bool) -> Counter {
+        Counter {
+            at_line_start: true,
+            summary: FileSummary {
+                line_stats: line_stats.then(LineStats::default),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Count one unit of decoded text.
+    pub(crate) fn push(&mut self, d: Decoded) {
+        let fs = &mut self.summary;
+        let (c, size) = match d {
+            Decoded::Char(c, size) => {
+                fs.chars += 1;
+                (c, size)
+            },
+            Decoded::Invalid(size) => {
+                // not a character, and doesn't start or end a word
+                self.at_line_start = false;
+                self.line_bytes += size;
+                self.last_cr = None;
+                return;
+            },
+        };
+
+        // Words are counted when they start. Like the standard wc, characters that
+        // can't be printed don't start or end a word.
+        if is_word_separator(c) {
+            self.in_word = false;
+        } else if is_printable(c) && !self.in_word {
+            fs.words += 1;
+            self.in_word = true;
+        }
+
+        // Line widths follow the standard wc: tabs go to the next multiple of 8,
+        // carriage returns and form feeds start over at 0.
+        match c {
+            '\n' | '\r' | '\x0c' => {
+                fs.max_line_length = fs.max_line_length.max(self.line_width);
+                self.line_width = 0;
+            },
+            '\t' => self.line_width += 8 - (self.line_width % 8),
+            // characters that can't be printed take up no room
+            _ if is_printable(c) => self.line_width += c.width().unwrap_or(0),
+            _ => {},
+        }
+
+        if c == '\n' {
+            fs.lines += 1;
+            self.at_line_start = true;
+            if let Some(stats) = &mut fs.line_stats {
+                // leave the \r of a \r\n line ending out of the line length
+                match self.last_cr {
+                    Some(cr_size) => stats.record(self.line_bytes - cr_size, self.line_chars - 1),
+                    None => stats.record(self.line_bytes, self.line_chars),
+                }
+            }
+            self.line_bytes = 0;
+            self.line_chars = 0;
+        } else {
+            self.at_line_start = false;
+            self.line_bytes += size;
+            self.line_chars += 1;
+        }
+        self.last_cr = (c == '\r').then_some(size);
+    }
+
+    /// Finish counting and return the totals. `bytes` and `label` are left for the caller to fill in.
+    ///
+    /// A last line with no line ending is still counted as a line, the same way
+    /// `str::lines()` counts it. GNU wc only counts newlines, so this is a known
+    /// difference from it.
+    pub(crate) fn finish(mut self) -> FileSummary {
+        if !self.at_line_start {
+            self.summary.lines += 1;
+            if let Some(stats) = &mut self.summary.line_stats {
+                stats.record(self.line_bytes, self.line_chars);
+            }
+        }
+        self.summary.max_line_length = self.summary.max_line_length.max(self.line_width);
+        self.summary
+    }
+}
+
+/// Check whether `c` separates words. This is the same list of characters the
+/// standard wc uses in a UTF-8 locale: ASCII whitespace (including vertical tab),
+/// Unicode spaces, and the non-breaking spaces.
+fn is_word_separator(c: char) -> bool {
+    matches!(c,
+        '\t' | '\n' | '\x0b' | '\x0c' | '\r' | ' '
+        | '\u{A0}' | '\u{1680}' | '\u{2000}'..='\u{200A}'
+        | '\u{202F}' | '\u{205F}' | '\u{2060}' | '\u{3000}')
+}
+
+/// Check whether `c` is a printable character, meaning it can start a word.
+///
+/// Control characters and the Unicode line and paragraph separators are not
+/// printable. The standard wc also treats unassigned code points as not printable,
+/// but there's no table of those here, so they count as printable.
+fn is_printable(c: char) -> bool {
+    !c.is_control() && c != '\u{2028}' && c != '\u{2029}'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper method to count a string, one character at a time.
+    fn count(s: &str) -> FileSummary {
+        let mut counter = Counter::new(false);
+        s.chars().for_each(|c| counter.push(Decoded::Char(c, c.len_utf8())));
+        counter.finish()
+    }
+
+    #[test]
+    fn unicode_spaces_separate_words() {
+        assert_eq!(count("a\u{A0}b\u{2003}c\u{3000}d\x0be").words, 5);
+        // U+200B ZERO WIDTH SPACE is not a separator in the standard wc
+        assert_eq!(count("a\u{200B}b").words, 1);
+    }
+
+    #[test]
+    fn non_printable_chars_are_not_words() {
+        assert_eq!(count("\x01").words, 0);
+        assert_eq!(count("a \x01 b").words, 2);
+        assert_eq!(count("a\x01b").words, 1);
+        assert_eq!(count("\u{FEFF}").words, 1);
+    }
+
+    #[test]
+    fn invalid_bytes_are_not_words() {
+        let mut counter = Counter::new(false);
+        [Decoded::Invalid(1), Decoded::Char('\n', 1)].into_iter().for_each(|d| counter.push(d));
+        let fs = counter.finish();
+        assert_eq!((fs.lines, fs.words, fs.chars), (1, 0, 1));
+    }
+
+    #[test]
+    fn max_line_length_tabs_and_wide_chars() {
+        assert_eq!(count("a\tb\n").max_line_length, 9);
+        assert_eq!(count("漢字\nab").max_line_length, 4);
+        assert_eq!(count("long line\rab\n").max_line_length, 9);
+        assert_eq!(count("\u{2028}\x01\n").max_line_length, 0);
+    }
+}
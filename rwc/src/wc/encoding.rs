@@ -0,0 +1,258 @@
+//! Decoding file contents into characters, for the `--encoding` option.
+//!
+//! The byte count (`-c`) is always the raw number of bytes in the file. Every other
+//! count is done on the characters that come out of the decoder here.
+//!
+//! The decoder can be fed a file in chunks of any size. Multi-byte characters that
+//! are split between two chunks are held back until the rest of the character shows up.
+
+use clap::ValueEnum;
+
+/// Text encodings that wc knows how to read. Set with `--encoding`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Encoding {
+    /// Look for a byte order mark. Use UTF-16 if one is found, otherwise UTF-8
+    Auto,
+    /// UTF-8
+    Utf8,
+    /// UTF-16, little endian
+    Utf16le,
+    /// UTF-16, big endian
+    Utf16be,
+    /// ISO-8859-1. Every byte is one character
+    Latin1,
+}
+
+/// One unit of decoded text. Either a character, or some bytes that are not valid
+/// in the encoding. Both have the number of raw bytes they were decoded from.
+///
+/// Invalid bytes are not counted as characters, and they don't start or end a
+/// word, the same way the standard wc does it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Decoded {
+    Char(char, usize),
+    Invalid(usize),
+}
+
+/// Turns bytes into `Decoded` units, one chunk at a time.
+pub(crate) struct Decoder {
+    /// Encoding to use. `Auto` is replaced by the real encoding once enough bytes
+    /// have been seen to check for a byte order mark.
+    encoding: Encoding,
+    /// Bytes from the end of the last chunk that might be the start of a character.
+    pending: Vec<u8>,
+}
+
+impl Decoder {
+    /// Make a new decoder for the given encoding.
+    pub(crate) fn new(encoding: Encoding) -> Decoder {
+        Decoder { encoding, pending: Vec::new() }
+    }
+
+    /// Decode the next chunk of bytes, calling `out` for each unit of text.
+    ///
+    /// Anything left over at the end of the chunk is kept until the next call
+    /// to `feed()` or `finish()`.
+    pub(crate) fn feed(&mut self, bytes: &[u8], out: &mut dyn FnMut(Decoded)) {
+        if self.pending.is_empty() {
+            self.decode(bytes, false, out);
+        } else {
+            let mut data = std::mem::take(&mut self.pending);
+            data.extend_from_slice(bytes);
+            self.decode(&data, false, out);
+        }
+    }
+
+    /// Decode anything left over from the last chunk. Call this once, at the end of the file.
+    pub(crate) fn finish(&mut self, out: &mut dyn FnMut(Decoded)) {
+        let data = std::mem::take(&mut self.pending);
+        self.decode(&data, true, out);
+    }
+
+    /// Decode `data`, which is the leftover bytes plus the new chunk.
+    ///
+    /// If `last` is false, an incomplete character at the end is saved in `self.pending`.
+    fn decode(&mut self, data: &[u8], last: bool, out: &mut dyn FnMut(Decoded)) {
+        if self.encoding == Encoding::Auto {
+            match detect_bom(data) {
+                Some(encoding) => self.encoding = encoding,
+                // can't tell yet, wait for more bytes
+                None if !last => {
+                    self.pending.extend_from_slice(data);
+                    return;
+                },
+                None => self.encoding = Encoding::Utf8,
+            }
+        }
+
+        match self.encoding {
+            Encoding::Auto => unreachable!("encoding is detected above"),
+            Encoding::Utf8 => self.decode_utf8(data, last, out),
+            Encoding::Utf16le => self.decode_utf16(data, last, u16::from_le_bytes, out),
+            Encoding::Utf16be => self.decode_utf16(data, last, u16::from_be_bytes, out),
+            Encoding::Latin1 => data.iter().for_each(|b| out(Decoded::Char(char::from(*b), 1))),
+        }
+    }
+
+    fn decode_utf8(&mut self, data: &[u8], last: bool, out: &mut dyn FnMut(Decoded)) {
+        let mut chunks = data.utf8_chunks().peekable();
+        while let Some(chunk) = chunks.next() {
+            chunk.valid().chars().for_each(|c| out(Decoded::Char(c, c.len_utf8())));
+
+            let invalid = chunk.invalid();
+            if invalid.is_empty() {
+                continue;
+            }
+
+            // The last invalid piece might just be a character that continues in the next chunk.
+            let incomplete = chunks.peek().is_none()
+                && matches!(std::str::from_utf8(invalid), Err(e) if e.error_len().is_none());
+            if incomplete && !last {
+                self.pending.extend_from_slice(invalid);
+            } else {
+                out(Decoded::Invalid(invalid.len()));
+            }
+        }
+    }
+
+    fn decode_utf16(&mut self, data: &[u8], last: bool, to_u16: fn([u8; 2]) -> u16, out: &mut dyn FnMut(Decoded)) {
+        let pairs = data.chunks_exact(2);
+        let odd_byte = pairs.remainder();
+        let mut units: Vec<u16> = pairs.map(|p| to_u16([p[0], p[1]])).collect();
+
+        let mut keep = odd_byte.to_vec();
+        if !last {
+            // a high surrogate at the end needs the low surrogate from the next chunk
+            if let Some(unit) = units.last() {
+                if (0xD800..=0xDBFF).contains(unit) {
+                    units.pop();
+                    let start = units.len() * 2;
+                    keep = data[start..].to_vec();
+                }
+            }
+        }
+
+        char::decode_utf16(units).for_each(|r| match r {
+            Ok(c) => out(Decoded::Char(c, c.len_utf16() * 2)),
+            Err(_) => out(Decoded::Invalid(2)),
+        });
+
+        if last {
+            if !keep.is_empty() {
+                out(Decoded::Invalid(keep.len()));
+            }
+        } else {
+            self.pending = keep;
+        }
+    }
+}
+
+/// Look for a byte order mark at the start of `data`.
+///
+/// Returns `None` if there aren't enough bytes to tell yet. The byte order mark
+/// itself is not removed. It's decoded as the character U+FEFF, which the standard
+/// wc also counts as a character in UTF-8 files.
+fn detect_bom(data: &[u8]) -> Option<Encoding> {
+    match data {
+        [0xFF, 0xFE, ..] => Some(Encoding::Utf16le),
+        [0xFE, 0xFF, ..] => Some(Encoding::Utf16be),
+        [0xEF, 0xBB, 0xBF, ..] => Some(Encoding::Utf8),
+        [0xEF, 0xBB] | [0xEF] | [0xFF] | [0xFE] | [] => None,
+        _ => Some(Encoding::Utf8),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper method to decode `data`, fed to the decoder `chunk_size` bytes at a time.
+    fn decode_all(data: &[u8], encoding: Encoding, chunk_size: usize) -> Vec<Decoded> {
+        let mut found = Vec::new();
+        let mut decoder = Decoder::new(encoding);
+        for chunk in data.chunks(chunk_size) {
+            decoder.feed(chunk, &mut |d| found.push(d));
+        }
+        decoder.finish(&mut |d| found.push(d));
+
+        let total_bytes: usize = found.iter().map(|d| match d {
+            Decoded::Char(_, len) | Decoded::Invalid(len) => len,
+        }).sum();
+        assert_eq!(total_bytes, data.len(), "every byte should be decoded exactly once");
+
+        found
+    }
+
+    /// Helper method to make the expected output, using `len` to get the size of each character.
+    fn chars(s: &str, len: fn(char) -> usize) -> Vec<Decoded> {
+        s.chars().map(|c| Decoded::Char(c, len(c))).collect()
+    }
+
+    fn utf16_len(c: char) -> usize {
+        c.len_utf16() * 2
+    }
+
+    #[test]
+    fn detect_boms() {
+        assert_eq!(detect_bom(&[0xFF, 0xFE, 0x41, 0x00]), Some(Encoding::Utf16le));
+        assert_eq!(detect_bom(&[0xFE, 0xFF, 0x00, 0x41]), Some(Encoding::Utf16be));
+        assert_eq!(detect_bom(&[0xEF, 0xBB, 0xBF, 0x41]), Some(Encoding::Utf8));
+        assert_eq!(detect_bom(b"plain text"), Some(Encoding::Utf8));
+        assert_eq!(detect_bom(&[0xEF, 0xBB]), None);
+    }
+
+    /// A multi-byte character split between chunks should still come out as one character.
+    #[test]
+    fn utf8_split_between_chunks() {
+        let text = "naïve café, déjà vu 😀";
+        for size in 1..5 {
+            assert_eq!(decode_all(text.as_bytes(), Encoding::Utf8, size), chars(text, char::len_utf8), "chunk size {}", size);
+        }
+    }
+
+    #[test]
+    fn utf8_invalid_bytes() {
+        let data = b"ab\xFFc\xE2\x82";
+        let expected = vec![
+            Decoded::Char('a', 1), Decoded::Char('b', 1), Decoded::Invalid(1), Decoded::Char('c', 1), Decoded::Invalid(2)
+        ];
+        assert_eq!(decode_all(data, Encoding::Utf8, 64), expected);
+        assert_eq!(decode_all(data, Encoding::Utf8, 1), expected);
+    }
+
+    #[test]
+    fn utf16_both_endians() {
+        let text = "\u{FEFF}Über 😀\r\n";
+        let le: Vec<u8> = text.encode_utf16().flat_map(|u| u.to_le_bytes()).collect();
+        let be: Vec<u8> = text.encode_utf16().flat_map(|u| u.to_be_bytes()).collect();
+
+        for size in 1..6 {
+            assert_eq!(decode_all(&le, Encoding::Utf16le, size), chars(text, utf16_len));
+            assert_eq!(decode_all(&be, Encoding::Utf16be, size), chars(text, utf16_len));
+            assert_eq!(decode_all(&le, Encoding::Auto, size), chars(text, utf16_len));
+            assert_eq!(decode_all(&be, Encoding::Auto, size), chars(text, utf16_len));
+        }
+    }
+
+    #[test]
+    fn utf16_odd_length_and_lone_surrogate() {
+        // 'A', then a high surrogate with no low surrogate, then half of a unit
+        let data = [0x41, 0x00, 0x3D, 0xD8, 0x42];
+        let expected = vec![Decoded::Char('A', 2), Decoded::Invalid(2), Decoded::Invalid(1)];
+        assert_eq!(decode_all(&data, Encoding::Utf16le, 64), expected);
+        assert_eq!(decode_all(&data, Encoding::Utf16le, 1), expected);
+    }
+
+    #[test]
+    fn latin1_every_byte_is_a_char() {
+        let data = [b'c', b'a', b'f', 0xE9, 0xFF];
+        assert_eq!(decode_all(&data, Encoding::Latin1, 2), chars("café\u{FF}", |_| 1));
+    }
+
+    #[test]
+    fn auto_short_input() {
+        assert_eq!(decode_all(&[0xEF], Encoding::Auto, 1), vec![Decoded::Invalid(1)]);
+        assert_eq!(decode_all(b"a", Encoding::Auto, 1), chars("a", char::len_utf8));
+        assert_eq!(decode_all(b"", Encoding::Auto, 1), vec![]);
+    }
+}
@@ -0,0 +1,242 @@
+//! Line length statistics and histograms, for the `--line-stats` and `--histogram` options.
+//!
+//! Line lengths are collected by `Counter` in the same pass that counts everything
+//! else. Lengths don't include the line ending (`\n` or `\r\n`), the same as `str::lines()`.
+
+use std::collections::BTreeMap;
+
+use clap::ValueEnum;
+
+/// Which line length to show in the histogram. Set with `--histogram=UNIT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum LengthUnit {
+    /// Line length in characters
+    Chars,
+    /// Line length in raw bytes
+    Bytes,
+}
+
+/// How many lines there are of each length, in bytes and in characters.
+///
+/// Keeping a count for each length, instead of a list of every line, keeps this
+/// small for big files and makes it easy to add files together for the total.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct LineStats {
+    bytes: BTreeMap<usize, usize>,
+    chars: BTreeMap<usize, usize>,
+}
+
+/// Summary of one set of line lengths.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Summary {
+    pub(crate) min: usize,
+    pub(crate) mean: f64,
+    pub(crate) median: usize,
+    pub(crate) p95: usize,
+    pub(crate) max: usize,
+}
+
+impl LineStats {
+    /// Add one line with the given length.
+    pub(crate) fn record(&mut self, bytes: usize, chars: usize) {
+        *self.bytes.entry(bytes).or_default() += 1;
+        *self.chars.entry(chars).or_default() += 1;
+    }
+
+    /// Add all the lines from `other`. Used for the total.
+    pub(crate) fn merge(&mut self, other: &LineStats) {
+        for (len, n) in &other.bytes {
+            *self.bytes.entry(*len).or_default() += n;
+        }
+        for (len, n) in &other.chars {
+            *self.chars.entry(*len).or_default() += n;
+        }
+    }
+
+    fn lengths(&self, unit: LengthUnit) -> &BTreeMap<usize, usize> {
+        match unit {
+            LengthUnit::Bytes => &self.bytes,
+            LengthUnit::Chars => &self.chars,
+        }
+    }
+
+    /// Get the min, mean, median, 95th percentile, and max line length.
+    /// Returns `None` if there are no lines.
+    ///
+    /// The median and 95th percentile use the nearest-rank method, so they're
+    /// always the length of a real line.
+    pub(crate) fn summary(&self, unit: LengthUnit) -> Option<Summary> {
+        let lengths = self.lengths(unit);
+        let count: usize = lengths.values().sum();
+        let min = *lengths.keys().next()?;
+        let max = *lengths.keys().next_back()?;
+        let sum: usize = lengths.iter().map(|(len, n)| len * n).sum();
+
+        Some(Summary {
+            min,
+            mean: sum as f64 / count as f64,
+            median: percentile(lengths, count, 50),
+            p95: percentile(lengths, count, 95),
+            max,
+        })
+    }
+
+    /// Count lines in buckets that double in size: 0, 1, 2-3, 4-7, 8-15, and so on.
+    /// Returns the low and high end of each bucket (both inclusive) and the number of lines in it.
+    /// Buckets from the first one up to the longest line are all included, even if they are empty.
+    pub(crate) fn histogram(&self, unit: LengthUnit) -> Vec<(usize, usize, usize)> {
+        let mut buckets: Vec<(usize, usize, usize)> = Vec::new();
+        for (len, n) in self.lengths(unit) {
+            let index = bucket_index(*len);
+            while buckets.len() <= index {
+                let (low, high) = bucket_range(buckets.len());
+                buckets.push((low, high, 0));
+            }
+            buckets[index].2 += n;
+        }
+        buckets
+    }
+}
+
+/// Get the `pct` percentile from lengths and their counts, using the nearest-rank method.
+fn percentile(lengths: &BTreeMap<usize, usize>, count: usize, pct: usize) -> usize {
+    // rank is 1-based: ceil(pct / 100 * count)
+    let rank = (pct * count).div_ceil(100).max(1);
+    let mut seen = 0;
+    for (len, n) in lengths {
+        seen += n;
+        if seen >= rank {
+            return *len;
+        }
+    }
+    0
+}
+
+/// Bucket 0 holds length 0. Bucket `i` holds lengths from 2^(i-1) to 2^i - 1.
+fn bucket_index(len: usize) -> usize {
+    (usize::BITS - len.leading_zeros()) as usize
+}
+
+fn bucket_range(index: usize) -> (usize, usize) {
+    match index {
+        0 => (0, 0),
+        // written as a shift of usize::MAX so the last bucket doesn't overflow
+        i => (1 << (i - 1), usize::MAX >> (usize::BITS as usize - i)),
+    }
+}
+
+/// Format the `--line-stats` lines that go under a row of wc output, like this:
+/// ```text
+///     line bytes: min 0 mean 18.8 median 20 p95 38 max 41
+///     line chars: min 0 mean 18.8 median 20 p95 38 max 41
+/// ```
+pub(crate) fn format_stats(stats: &LineStats) -> String {
+    [LengthUnit::Bytes, LengthUnit::Chars].iter().map(|unit| {
+        let name = match unit {
+            LengthUnit::Bytes => "bytes",
+            LengthUnit::Chars => "chars",
+        };
+        match stats.summary(*unit) {
+            Some(s) => format!("    line {}: min {} mean {:.1} median {} p95 {} max {}",
+                name, s.min, s.mean, s.median, s.p95, s.max),
+            None => format!("    line {}: no lines", name),
+        }
+    }).collect::<Vec<String>>().join("\n")
+}
+
+/// Width of the longest bar in a histogram.
+const BAR_WIDTH: usize = 40;
+
+/// Format the `--histogram` lines that go under a row of wc output, like this:
+/// ```text
+///     line length (chars):
+///     0 - 0 2 ########################################
+///     1 - 1 0
+///     2 - 3 0
+///     4 - 7 1 ####################
+/// ```
+/// The first two columns are the shortest and longest line length in the bucket,
+/// and the third is the number of lines.
+/// Bars are scaled so the biggest bucket is `BAR_WIDTH` characters wide.
+pub(crate) fn format_histogram(stats: &LineStats, unit: LengthUnit) -> String {
+    let name = match unit {
+        LengthUnit::Bytes => "bytes",
+        LengthUnit::Chars => "chars",
+    };
+    let buckets = stats.histogram(unit);
+    if buckets.is_empty() {
+        return format!("    line length ({}): no lines", name);
+    }
+
+    let biggest = buckets.iter().map(|b| b.2).max().unwrap_or(0);
+    let len_width = buckets.last().map_or(1, |b| b.1.to_string().len());
+    let count_width = biggest.to_string().len();
+
+    let mut out = vec![format!("    line length ({}):", name)];
+    for (low, high, n) in buckets {
+        // round up, so a bucket with any lines in it always gets at least one #
+        let bar = (n * BAR_WIDTH).div_ceil(biggest.max(1));
+        out.push(format!("    {:>len_width$} - {:>len_width$} {:>count_width$} {}",
+            low, high, n, "#".repeat(bar)).trim_end().to_owned());
+    }
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats_for(lengths: &[usize]) -> LineStats {
+        let mut stats = LineStats::default();
+        lengths.iter().for_each(|len| stats.record(*len, *len));
+        stats
+    }
+
+    #[test]
+    fn summary_of_lengths() {
+        let stats = stats_for(&[5, 1, 3, 2, 4, 100, 0, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18]);
+        let s = stats.summary(LengthUnit::Chars).unwrap();
+        assert_eq!(s.min, 0);
+        assert_eq!(s.max, 100);
+        // 20 lines: rank 10 for the median, rank 19 for p95
+        assert_eq!(s.median, 9);
+        assert_eq!(s.p95, 18);
+        assert!((s.mean - 271.0 / 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn summary_one_line_and_no_lines() {
+        let s = stats_for(&[42]).summary(LengthUnit::Bytes).unwrap();
+        assert_eq!((s.min, s.median, s.p95, s.max), (42, 42, 42, 42));
+        assert_eq!(LineStats::default().summary(LengthUnit::Bytes), None);
+    }
+
+    #[test]
+    fn merge_adds_counts() {
+        let mut a = stats_for(&[1, 2]);
+        a.merge(&stats_for(&[2, 3]));
+        assert_eq!(a, stats_for(&[1, 2, 2, 3]));
+    }
+
+    #[test]
+    fn histogram_buckets() {
+        let stats = stats_for(&[0, 0, 1, 5, 7, 8]);
+        assert_eq!(stats.histogram(LengthUnit::Chars), vec![
+            (0, 0, 2), (1, 1, 1), (2, 3, 0), (4, 7, 2), (8, 15, 1)
+        ]);
+        assert_eq!(bucket_index(usize::MAX), usize::BITS as usize);
+        assert_eq!(bucket_range(usize::BITS as usize).1, usize::MAX);
+    }
+
+    #[test]
+    fn format_histogram_bars() {
+        let stats = stats_for(&[0, 0, 4]);
+        let expected = concat!(
+            "    line length (chars):\n",
+            "    0 - 0 2 ########################################\n",
+            "    1 - 1 0\n",
+            "    2 - 3 0\n",
+            "    4 - 7 1 ####################");
+        assert_eq!(format_histogram(&stats, LengthUnit::Chars), expected);
+    }
+}
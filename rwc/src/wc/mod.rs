@@ -1,17 +1,40 @@
-use std::io::Error;
-use std::fs;
+use std::borrow::Cow;
+use std::ffi::{OsStr, OsString};
+use std::io::{Error, ErrorKind, Read, Write};
 use std::cmp::max;
+use std::process::ExitCode;
+
+use shared::display::os_bytes;
+use shared::error::{ExitStatus, UError};
+use shared::input::Operands;
+use shared::output::Output;
+use shared::quote::quote_name;
 
 use crate::Cli;
+use counter::Counter;
+use encoding::Decoder;
+use line_stats::LineStats;
+use unique::SeenFiles;
+
+mod counter;
+pub(crate) mod encoding;
+pub(crate) mod line_stats;
+#[cfg(test)]
+mod properties;
+pub(crate) mod table;
+mod unique;
+
+/// Name of the program, shown at the start of error messages.
+const UTIL_NAME: &str = "wc";
 
 /// Enum that handles the two cases that wc can run up against: a file, or an error message.
 enum WCResult {
     FileStats(FileSummary),
-    ErrMsg(String),
+    ErrMsg(UError),
 }
 
 /// Struct that contains info about the files that wc is told to get info about.
-#[derive(Debug)]
+#[derive(Debug, Default, Clone, PartialEq)]
 struct FileSummary {
     /// Number of lines found in the file
     lines: usize,
@@ -21,29 +44,66 @@ struct FileSummary {
     chars: usize,
     /// Number of bytes found in the file.
     bytes: usize, 
+    /// Display width of the longest line in the file.
+    max_line_length: usize,
     /// Label for thing being counted. Is either the file name or `total`.
-    label: String, 
+    label: OsString, 
+    /// With `--unique`, the label of the first operand that was the same file.
+    /// Duplicates are listed, but not added to the total.
+    duplicate_of: Option<OsString>,
+    /// Line lengths, if `--line-stats` or `--histogram` was used.
+    line_stats: Option<LineStats>,
 }
 
 /// Count words, lines, and bytes in the given files.
-pub(crate) fn wc(args: Cli) -> Result<(), Error> {
-
-    if let Some(file_names) = &args.files {
-        let mut summaries = summarize_files(&file_names);
-
-        let max_len = get_totals(&mut summaries);
-
-        summaries.iter().for_each(|file_summary_result| {
-            match file_summary_result {
-                WCResult::FileStats(s) => println!("{}", format_summary(s, max_len, &args)),
-                WCResult::ErrMsg(e) => eprintln!("{}", e),
-            };
-        });
-
-        Ok(())
-    } else {
-        Ok(())
+///
+/// Files that can't be read are reported on standard error, and the rest are still
+/// counted. Returns the exit code: success only if every file was read, and the
+/// output could be written. If standard output is a pipe that gets closed early,
+/// like `wc * | head -1`, wc stops without an error message.
+pub(crate) fn wc(args: Cli) -> ExitCode {
+    let mut status = ExitStatus::new(UTIL_NAME);
+
+    // with no operands, standard input is counted, and its row has no label
+    let file_names = args.files.clone().unwrap_or_default();
+    let mut summaries = summarize_files(&file_names, &args);
+
+    // the total covers every file, even ones hidden by sorting and filtering options
+    let has_total = summaries.len() > 1;
+    let max_len = get_totals(&mut summaries);
+    let total = if has_total { summaries.pop() } else { None };
+    table::arrange(&mut summaries, &args);
+    summaries.extend(total);
+
+    // once writing fails, stop writing rows, but still report the files that failed
+    let mut out = Output::stdout();
+    let mut result = Ok(());
+    for file_summary_result in summaries.iter() {
+        match file_summary_result {
+            WCResult::FileStats(s) => if result.is_ok() {
+                result = out.write_all(&format_summary(s, max_len, &args)).and_then(|_| out.write_all(b"\n"));
+            },
+            WCResult::ErrMsg(e) => {
+                // keep the rows before the error in front of it, if both go to a terminal
+                if result.is_ok() {
+                    result = out.flush();
+                }
+                status.report(e);
+            },
+        }
     }
+    let written = out.finish(result, &mut status);
+
+    if args.unique && written {
+        let duplicates = summaries.iter()
+            .filter(|s| matches!(s, WCResult::FileStats(f) if f.duplicate_of.is_some()))
+            .count();
+        if duplicates > 0 {
+            status.note(format_args!("{} duplicate file(s) left out of the total", duplicates));
+        }
+    }
+
+    status.into()
 }
 
 /// Get totals of all files, if there is more than one.
@@ -57,29 +117,32 @@ pub(crate) fn wc(args: Cli) -> Result<(), Error> {
 /// 
 /// # Arguments
 /// 
-///  * `summaries` - A Vec of `WCResult` enums. If there is more than 
-/// one, add a `FileSummary` struct with the label "total" 
-/// at the end. This will contain totals of all the other structs.
+///  * `summaries` - A Vec of `WCResult` enums. If there is more than
+///    one, add a `FileSummary` struct with the label "total"
+///    at the end. This will contain totals of all the other structs.
 fn get_totals(summaries: &mut Vec<WCResult>) -> usize {
     // get longest number so you can set the amount of padding
     // also get a running total of all lines, words, and chars
     let mut max_len = 0;
     let mut total_summary = FileSummary {
-        lines: 0,
-        words: 0,
-        chars: 0,
-        bytes: 0,
-        label: "total".to_owned(),
+        label: "total".into(),
+        ..Default::default()
     };
 
     for file_summary_result in summaries.iter() {
         if let WCResult::FileStats(filsm) = file_summary_result {
             // calculate totals if there is more than one file
-            if summaries.len() > 1 {
+            // with --unique, files that were already counted are skipped
+            if summaries.len() > 1 && filsm.duplicate_of.is_none() {
                 total_summary.lines += filsm.lines;
                 total_summary.words += filsm.words;
                 total_summary.chars += filsm.chars;
                 total_summary.bytes += filsm.bytes;
+                // the standard wc shows the longest line of any file, not a sum
+                total_summary.max_line_length = max(total_summary.max_line_length, filsm.max_line_length);
+                if let Some(stats) = &filsm.line_stats {
+                    total_summary.line_stats.get_or_insert_with(LineStats::default).merge(stats);
+                }
             }
 
             // get longest number
@@ -87,6 +150,7 @@ fn get_totals(summaries: &mut Vec<WCResult>) -> usize {
             max_len = max(max_len, filsm.words.to_string().len());
             max_len = max(max_len, filsm.chars.to_string().len());
             max_len = max(max_len, filsm.bytes.to_string().len());
+            max_len = max(max_len, filsm.max_line_length.to_string().len());
         }
     }
 
@@ -111,31 +175,53 @@ fn get_totals(summaries: &mut Vec<WCResult>) -> usize {
 /// 
 /// # Arguments
 /// 
-/// * `file_names` - a pointer to an array of Strings that are file names 
-/// recieved from the user at the command line.
-fn summarize_files(file_names: &Vec<String>) -> Vec<WCResult> {
+/// * `file_names` - a pointer to an array of file names recieved from the user
+///   at the command line, which don't have to be UTF-8. `-` means standard input.
+/// * `args` - the command line arguments, as a reference to a `Cli` object
+fn summarize_files(file_names: &[OsString], args: &Cli) -> Vec<WCResult> {
     let mut summaries: Vec<WCResult> = Vec::new();
+    let mut seen = SeenFiles::default();
+
+    for input in Operands::new(file_names.iter().cloned()) {
+        let mut input = match input {
+            Ok(input) => input,
+            Err(e) => {
+                summaries.push(WCResult::ErrMsg(e.into()));
+                continue;
+            },
+        };
 
-    for file_path in file_names.iter() {
-        let contents = fs::read_to_string(file_path);
-        match contents {
-            Ok(c) => {
-                let mut summary = handle_file_contents(c);
-                summary.label = file_path.to_owned();
+        // with --unique, reuse the counts from the first time the file was seen
+        if args.unique {
+            if let Some(first) = input.path().and_then(|path| seen.check(path, summaries.len())) {
+                if let WCResult::FileStats(f) = &summaries[first] {
+                    let mut summary = f.clone();
+                    summary.duplicate_of = Some(f.label.clone());
+                    summary.label = input.label().to_owned();
+                    summaries.push(WCResult::FileStats(summary));
+                    continue;
+                }
+            }
+        }
+
+        match summarize_reader(&mut input, args) {
+            Ok(mut summary) => {
+                summary.label = input.operand().unwrap_or_default().to_owned();
                 summaries.push(WCResult::FileStats(summary));
             },
-            Err(e) => summaries.push(WCResult::ErrMsg(format!("{}: {}", e.to_string(), file_path))),
+            Err(e) => summaries.push(WCResult::ErrMsg(input.error(e).into())),
         };
     }
 
     summaries
 }
 
+
 /// Format a `FileSummary` struct to look like the original wc command's output.
 /// 
 /// This means the following:
 /// * Calculate the value with the longest number of chars, and
-/// pad to that length.
+///   pad to that length.
 /// * Then separate each value by one character.
 /// * Right justify the numbers.
 /// 
@@ -144,7 +230,7 @@ fn summarize_files(file_names: &Vec<String>) -> Vec<WCResult> {
 /// a total line at the end.
 /// 
 /// This makes a nice output like this:
-/// ```
+/// ```text
 /// :~$ wc .xsession-errors .xsession-errors.old .xinputrc
 ///    87   627  7695 .xsession-errors
 ///   118   881 10564 .xsession-errors.old
@@ -152,29 +238,44 @@ fn summarize_files(file_names: &Vec<String>) -> Vec<WCResult> {
 ///   208  1525 18390 total
 /// ```
 /// For missing files, write the output like this:
-/// ```
+/// ```text
 /// :~$ wc .xsession-errors .xsession-errors.old .xinpur
 ///    87   627  7695 .xsession-errors
 ///   118   881 10564 .xsession-errors.old
 /// wc: .xinputr: No such file or directory
 ///   208  1525 18390 total
 /// ```
+/// With `--line-stats` or `--histogram`, more lines go under each row. See the
+/// `line_stats` module for what they look like.
+/// 
 /// List all items in the order they were specified on the command line,
 /// including any errors. This means that the error may be in the middle of the list.
 /// 
+/// With `--unique`, files that were already listed under another name are
+/// marked like this:
+/// ```text
+///   21  83 415 trees.txt
+///   21  83 415 link_to_trees.txt (duplicate of trees.txt)
+///   21  83 415 total
+/// ```
+/// 
 /// # Arguments
 /// 
-/// * `summary` - a `WCResult` enum that can contain a `FileSummary` struct, or an 
-/// error message as a String.
+/// * `summary` - a `WCResult` enum that can contain a `FileSummary` struct, or an
+///   error message as a String.
 /// * `padding` - the number of spaces to pad between values on a line. Get this by
-/// looping through all of the `FileSummary` structs and getting the largest value, 
-/// meaning the longest number when converted to a `String`.
+///   looping through all of the `FileSummary` structs and getting the largest value,
+///   meaning the longest number when converted to a `String`.
 /// * `args` - the command line arguments, as a reference to a `Cli` object
-fn format_summary(f: &FileSummary, padding: usize, args: &Cli) -> String {
+///
+/// Returns bytes, not a `String`, since file names are written out as they are,
+/// even when they aren't UTF-8.
+fn format_summary(f: &FileSummary, padding: usize, args: &Cli) -> Vec<u8> {
     let mut lines_count = "".to_owned();
     let mut words_count = "".to_owned();
     let mut chars_count = "".to_owned();
     let mut bytes_count = "".to_owned();
+    let mut max_line_length = "".to_owned();
 
     if args.lines {
         lines_count = format!("{:>padding$} ", f.lines);
@@ -188,36 +289,109 @@ fn format_summary(f: &FileSummary, padding: usize, args: &Cli) -> String {
     if args.bytes {
         bytes_count = format!("{:>padding$} ", f.bytes);
     }
-    format!("{}{}{}{}{}", lines_count, words_count, chars_count, bytes_count, f.label)
+    if args.max_line_length {
+        max_line_length = format!("{:>padding$} ", f.max_line_length);
+    }
+    let mut row = format!("{}{}{}{}{}", lines_count, words_count, chars_count, bytes_count, max_line_length).into_bytes();
+    if f.label.is_empty() {
+        // standard input with no operands has no label, or space before it
+        row.pop();
+    }
+    row.extend_from_slice(&show_label(&f.label));
+    if let Some(first) = &f.duplicate_of {
+        row.extend_from_slice(b" (duplicate of ");
+        row.extend_from_slice(&show_label(first));
+        row.push(b')');
+    }
+    if let Some(stats) = &f.line_stats {
+        if args.line_stats {
+            row.extend_from_slice(format!("\n{}", line_stats::format_stats(stats)).as_bytes());
+        }
+        if let Some(unit) = args.histogram {
+            row.extend_from_slice(format!("\n{}", line_stats::format_histogram(stats, unit)).as_bytes());
+        }
+    }
+    row
+}
+
+/// Get the label to show at the end of a row. Like the standard wc, a name is only
+/// quoted if it has a newline in it, since that would look like the start of another
+/// row. Other names are shown as they are, byte for byte.
+///
+/// # Arguments
+///
+/// * `label` - the file name, or `total`.
+fn show_label(label: &OsStr) -> Cow<'_, [u8]> {
+    let bytes = os_bytes(label);
+    if bytes.contains(&b'\n') {
+        Cow::Owned(quote_name(label).into_bytes())
+    } else {
+        bytes
+    }
 }
 
 /// Utility function to count lines, words, and bytes in the given file. Return a 
 /// `FileSummary` struct.
+/// 
+/// The byte count is the size of `contents`. Everything else is counted on the
+/// characters after decoding with the encoding from `--encoding`. Line lengths
+/// are kept too, if `--line-stats` or `--histogram` was used.
+/// 
+/// Files are read with `summarize_reader()`. This is for tests that already
+/// have the contents in memory.
 /// # Arguments
-/// * `contents` - the contents of the file in question, as a `String`.
-fn handle_file_contents(contents: String) -> FileSummary {
-    let mut fs = FileSummary { 
-        lines: 0, 
-        words: 0,
-        chars: 0,
-        bytes: 0, 
-        label: "".to_owned()
-    };
+/// * `contents` - the contents of the file in question, as raw bytes.
+/// * `args` - the command line arguments, as a reference to a `Cli` object
+#[cfg(test)]
+fn handle_file_contents(mut contents: &[u8], args: &Cli) -> FileSummary {
+    summarize_reader(&mut contents, args).expect("reading from a slice can't fail")
+}
+
+/// Size of the buffer used to read files. Files are counted one buffer at a time,
+/// so big files don't have to fit in memory.
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Count lines, words, characters, and bytes from `reader`, one buffer at a time.
+/// Return a `FileSummary` struct, or the first read error.
+/// 
+/// Results are the same no matter how the reads are split up. Characters that are
+/// split between two reads are put back together by the `Decoder`.
+/// # Arguments
+/// * `reader` - where to read the file contents from.
+/// * `args` - the command line arguments, as a reference to a `Cli` object
+fn summarize_reader(reader: &mut dyn Read, args: &Cli) -> Result<FileSummary, Error> {
+    let mut counter = Counter::new(args.line_stats || args.histogram.is_some());
+    let mut decoder = Decoder::new(args.encoding);
+    let mut buffer = vec![0; READ_BUFFER_SIZE];
+    let mut bytes = 0;
+
+    loop {
+        let n = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        bytes += n;
+        decoder.feed(&buffer[..n], &mut |d| counter.push(d));
+    }
+    decoder.finish(&mut |d| counter.push(d));
+
+    let mut fs = counter.finish();
+    fs.bytes = bytes;
 
-    // The lines() function checks for either \n or \r\n. Final line ending is optional.
-    // So a file ending in an empty line is the same as one with no final line ending.
-    // See rust docs for core::str::lines().
-    fs.lines = contents.lines().count();
-    fs.words = contents.split_ascii_whitespace().count();
-    fs.chars = contents.chars().count();
-    fs.bytes = contents.len();
-    
-    fs
+    Ok(fs)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use encoding::Encoding;
+
+    /// Helper method to get a row from `format_summary()` as text, to compare it.
+    fn format_text(f: &FileSummary, padding: usize, args: &Cli) -> String {
+        String::from_utf8(format_summary(f, padding, args)).unwrap()
+    }
 
     /// Helper method to make debugging easier.
     /// 
@@ -229,8 +403,9 @@ mod tests {
     /// current directory is `rwc`. Tests like this will be different for other 
     /// programs in this workspace.
     /// 
-    /// There's no need to call this if the test does not read files directly
-    fn debug_set_working_dir() {
+    /// There's no need to call this if the test does not read files directly.
+    /// The other modules in `wc` use it too.
+    pub(super) fn debug_set_working_dir() {
         if !std::env::current_dir().unwrap().ends_with("rwc") {
             std::env::set_current_dir("rwc").unwrap();
         }
@@ -245,7 +420,21 @@ mod tests {
             bytes: true,
             chars: false,
             words: true,
-            max_line_length: None,
+            max_line_length: false,
+            sort: None,
+            top: None,
+            min_lines: None,
+            max_lines: None,
+            min_words: None,
+            max_words: None,
+            min_chars: None,
+            max_chars: None,
+            min_bytes: None,
+            max_bytes: None,
+            encoding: Encoding::Auto,
+            unique: false,
+            line_stats: false,
+            histogram: None,
             files: None
         }
     }
@@ -262,7 +451,7 @@ mod tests {
     /// Simple test to make sure handle_file_contents counts words and stuff.
     fn test_handle_file_contents_1() {
         let simple_str = "this is a short bit of text".to_owned();
-        let fs = handle_file_contents(simple_str);
+        let fs = handle_file_contents(simple_str.as_bytes(), &get_default_args());
         check_file_summary_val(fs.lines, 1, "line".to_owned());
         check_file_summary_val(fs.words, 7, "word".to_owned());
         check_file_summary_val(fs.bytes, 27, "byte".to_owned());
@@ -272,7 +461,7 @@ mod tests {
     /// Read the file trees.txt and get various counts for it.
     fn read_trees() {
         debug_set_working_dir();
-        let file_sum = summarize_files(&vec!["tests/test_files/trees.txt".to_owned()]);
+        let file_sum = summarize_files(&["tests/test_files/trees.txt".into()], &get_default_args());
         assert_eq!(file_sum.len(), 1); // there should be just one item in this vec.
 
         match &file_sum[0] {
@@ -291,7 +480,7 @@ mod tests {
     #[test]
     fn read_fire() {
         debug_set_working_dir();
-        let file_sum = summarize_files(&vec!["tests/test_files/fire_and_ice.txt".to_owned()]);
+        let file_sum = summarize_files(&["tests/test_files/fire_and_ice.txt".into()], &get_default_args());
         assert_eq!(file_sum.len(), 1); // there should be just one item in this vec.
 
         match &file_sum[0] {
@@ -310,7 +499,7 @@ mod tests {
     #[test]
     fn read_so_tired() {
         debug_set_working_dir();
-        let file_sum = summarize_files(&vec!["tests/test_files/so_tired_blues.txt".to_owned()]);
+        let file_sum = summarize_files(&["tests/test_files/so_tired_blues.txt".into()], &get_default_args());
         assert_eq!(file_sum.len(), 1); // there should be just one item in this vec.
 
         match &file_sum[0] {
@@ -331,10 +520,10 @@ mod tests {
         debug_set_working_dir();
         let args = get_default_args();
         let mut file_sum = summarize_files(
-            &vec![
-                "tests/test_files/so_tired_blues.txt".to_owned(),
-                "tests/test_files/fire_and_ice.txt".to_owned()
-            ]);
+            &[
+                "tests/test_files/so_tired_blues.txt".into(),
+                "tests/test_files/fire_and_ice.txt".into()
+            ], &get_default_args());
 
         assert_eq!(file_sum.len(), 2); // there should be two items in this vec.
         // Both entries in vec should be FileStats enums
@@ -370,7 +559,7 @@ mod tests {
         match &file_sum[0] {
             WCResult::FileStats(fs)=> {
                 let expected_so_tired_blues = "  9  26 131 tests/test_files/so_tired_blues.txt";
-                let found_so_tired_blues = format_summary(fs, max_len, &args);
+                let found_so_tired_blues = format_text(fs, max_len, &args);
                 assert_eq!(found_so_tired_blues, expected_so_tired_blues, "Output not correct");
             },
             WCResult::ErrMsg(e) => {
@@ -381,7 +570,7 @@ mod tests {
         match &file_sum[1] {
             WCResult::FileStats(fs)=> {
                 let expected_fire_and_ice = " 13  56 272 tests/test_files/fire_and_ice.txt";
-                let found_fire_and_ice = format_summary(fs, max_len, &args);
+                let found_fire_and_ice = format_text(fs, max_len, &args);
                 assert_eq!(found_fire_and_ice, expected_fire_and_ice, "Output not correct");
             },
             WCResult::ErrMsg(e) => {
@@ -392,7 +581,7 @@ mod tests {
         match &file_sum[2] {
             WCResult::FileStats(fs)=> {
                 let expected_total = " 22  82 403 total";
-                let found_total = format_summary(fs, max_len, &args);
+                let found_total = format_text(fs, max_len, &args);
                 assert_eq!(found_total, expected_total, "Output not correct");
             },
             WCResult::ErrMsg(e) => {
@@ -412,10 +601,10 @@ mod tests {
         args.words = false;
 
         let mut file_sum = summarize_files(
-            &vec![
-            "tests/test_files/so_tired_blues.txt".to_owned(),
-            "tests/test_files/fire_and_ice.txt".to_owned()
-            ]);
+            &[
+            "tests/test_files/so_tired_blues.txt".into(),
+            "tests/test_files/fire_and_ice.txt".into()
+            ], &get_default_args());
 
         assert_eq!(file_sum.len(), 2); // there should be two items in this vec.
         // Both entries in vec should be FileStats enums
@@ -450,7 +639,7 @@ mod tests {
         match &file_sum[0] {
             WCResult::FileStats(fs)=> {
                 let expected_so_tired_blues = "  9 tests/test_files/so_tired_blues.txt";
-                let found_so_tired_blues = format_summary(fs, max_len, &args);
+                let found_so_tired_blues = format_text(fs, max_len, &args);
                 assert_eq!(found_so_tired_blues, expected_so_tired_blues, "Output not correct");
             },
             WCResult::ErrMsg(e) => {
@@ -462,7 +651,7 @@ mod tests {
         match &file_sum[1] {
             WCResult::FileStats(fs)=> {
                 let expected_fire_and_ice = " 13 tests/test_files/fire_and_ice.txt";
-                let found_fire_and_ice = format_summary(fs, max_len, &args);
+                let found_fire_and_ice = format_text(fs, max_len, &args);
                 assert_eq!(found_fire_and_ice, expected_fire_and_ice, "Output not correct");
             },
             WCResult::ErrMsg(e) => {
@@ -473,7 +662,7 @@ mod tests {
         match &file_sum[2] {
             WCResult::FileStats(fs)=> {
                 let expected_total = " 22 total";
-                let found_total = format_summary(fs, max_len, &args);
+                let found_total = format_text(fs, max_len, &args);
                 assert_eq!(found_total, expected_total, "Output not correct");
             },
             WCResult::ErrMsg(e) => {
@@ -487,7 +676,7 @@ mod tests {
     #[test]
     fn read_dracula() {
         debug_set_working_dir();
-        let file_sum = summarize_files(&vec!["tests/test_files/dracula.txt".to_owned()]);
+        let file_sum = summarize_files(&["tests/test_files/dracula.txt".into()], &get_default_args());
         assert_eq!(file_sum.len(), 1); // there should be just one item in this vec.
 
         match &file_sum[0] {
@@ -506,7 +695,7 @@ mod tests {
     #[test]
     fn read_frank() {
         debug_set_working_dir();
-        let file_sum = summarize_files(&vec!["tests/test_files/frankenstein.txt".to_owned()]);
+        let file_sum = summarize_files(&["tests/test_files/frankenstein.txt".into()], &get_default_args());
         assert_eq!(file_sum.len(), 1); // there should be just one item in this vec.
 
         match &file_sum[0] {
@@ -525,7 +714,7 @@ mod tests {
     #[test]
     fn read_moby() {
         debug_set_working_dir();
-        let file_sum = summarize_files(&vec!["tests/test_files/moby_dick.txt".to_owned()]);
+        let file_sum = summarize_files(&["tests/test_files/moby_dick.txt".into()], &get_default_args());
         assert_eq!(file_sum.len(), 1); // there should be just one item in this vec.
 
         match &file_sum[0] {
@@ -545,7 +734,7 @@ mod tests {
     #[test]
     fn read_err() {
         debug_set_working_dir();
-        let file_sum = summarize_files(&vec!["tests/test_files/does_not_exist.txt".to_owned()]);
+        let file_sum = summarize_files(&["tests/test_files/does_not_exist.txt".into()], &get_default_args());
         assert_eq!(file_sum.len(), 1); // there should be just one item in this vec.
 
         match &file_sum[0] {
@@ -554,8 +743,8 @@ mod tests {
             },
             WCResult::ErrMsg(e) => {
                 match std::env::consts::OS {
-                    "linux" => assert_eq!(e, "No such file or directory (os error 2): tests/test_files/does_not_exist.txt"),
-                    "windows" => assert_eq!(e, "The system cannot find the file specified. (os error 2): src/wc/test_files/does_not_exist.txt"),
+                    "linux" => assert_eq!(e.to_string(), "tests/test_files/does_not_exist.txt: No such file or directory"),
+                    "windows" => assert_eq!(e.to_string(), "tests/test_files/does_not_exist.txt: The system cannot find the file specified."),
                     _ => panic!("Not tested on this operating system: {}", std::env::consts::OS),
                 };
             }
@@ -565,8 +754,8 @@ mod tests {
     /// Just test the get_totals() function with mock structs.
     #[test]
     fn test_get_totals() {
-        let f1 = FileSummary {lines: 1, words: 1, chars: 1, bytes: 1, label: "file_1".to_owned()};
-        let f2 = FileSummary {lines: 2, words: 2, chars: 1, bytes: 2, label: "file_2".to_owned()};
+        let f1 = FileSummary {lines: 1, words: 1, chars: 1, bytes: 1, label: "file_1".into(), ..Default::default()};
+        let f2 = FileSummary {lines: 2, words: 2, chars: 1, bytes: 2, label: "file_2".into(), ..Default::default()};
 
         let mut fv = vec!();
         fv.push(WCResult::FileStats(f1));
@@ -582,7 +771,7 @@ mod tests {
                 check_file_summary_val(fs.lines, 3, "line".to_owned());
                 check_file_summary_val(fs.words, 3, "word".to_owned());
                 check_file_summary_val(fs.bytes, 3, "byte".to_owned());
-                assert_eq!(fs.label, "total".to_owned());
+                assert_eq!(fs.label, "total");
             },
             WCResult::ErrMsg(e) => {
                 panic!("Should not have caused this error: {}", e);
@@ -590,11 +779,11 @@ mod tests {
         }
     }
 
-    /// Just test the format_summary() function with mock structs, and command line arguments equal to -l.
+    /// Just test the format_text() function with mock structs, and command line arguments equal to -l.
     #[test]
     fn test_get_format_summary_lines() {
-        let f1 = FileSummary {lines: 1, words: 11, chars: 111, bytes: 11111, label: "file_1".to_owned()};
-        let f2 = FileSummary {lines: 22, words: 2, chars: 1, bytes: 2, label: "file_2".to_owned()};
+        let f1 = FileSummary {lines: 1, words: 11, chars: 111, bytes: 11111, label: "file_1".into(), ..Default::default()};
+        let f2 = FileSummary {lines: 22, words: 2, chars: 1, bytes: 2, label: "file_2".into(), ..Default::default()};
         let mut args = get_default_args();
         args.lines = true;
         args.words = false;
@@ -602,18 +791,46 @@ mod tests {
         args.bytes = false;
 
         let f1_expected = "  1 file_1";
-        let sum1 = format_summary(&f1, 3, &args);
+        let sum1 = format_text(&f1, 3, &args);
         assert_eq!(sum1, f1_expected);
 
         let f2_expected = " 22 file_2";
-        let sum2 = format_summary(&f2, 3, &args);
+        let sum2 = format_text(&f2, 3, &args);
         assert_eq!(sum2, f2_expected);
     }
 
+    /// Duplicates found with --unique are listed, but left out of the totals.
+    #[test]
+    fn test_get_totals_skips_duplicates() {
+        let f1 = FileSummary {lines: 1, words: 1, chars: 1, bytes: 1, label: "file_1".into(), ..Default::default()};
+        let f2 = FileSummary {lines: 1, words: 1, chars: 1, bytes: 1, label: "link_1".into(), duplicate_of: Some("file_1".into()), ..Default::default()};
+        let f3 = FileSummary {lines: 2, words: 2, chars: 2, bytes: 2, label: "file_2".into(), ..Default::default()};
+
+        let mut fv = vec![WCResult::FileStats(f1), WCResult::FileStats(f2), WCResult::FileStats(f3)];
+        get_totals(&mut fv);
+        assert_eq!(fv.len(), 4);
+
+        match &fv[3] {
+            WCResult::FileStats(fs) => {
+                check_file_summary_val(fs.lines, 3, "line".to_owned());
+                check_file_summary_val(fs.bytes, 3, "byte".to_owned());
+            },
+            WCResult::ErrMsg(e) => {
+                panic!("Should not have caused this error: {}", e);
+            }
+        }
+
+        let args = get_default_args();
+        match &fv[1] {
+            WCResult::FileStats(fs) => assert_eq!(format_text(fs, 1, &args), "1 1 1 link_1 (duplicate of file_1)"),
+            WCResult::ErrMsg(e) => panic!("Should not have caused this error: {}", e),
+        }
+    }
+
     /// Test whether there is a totals line if you only read one file.
     #[test]
     fn test_no_totals_with_one_file() {
-        let f1 = FileSummary {lines: 1, words: 1, chars: 1, bytes: 1, label: "file_1".to_owned()};
+        let f1 = FileSummary {lines: 1, words: 1, chars: 1, bytes: 1, label: "file_1".into(), ..Default::default()};
 
         let mut fv = vec!();
         fv.push(WCResult::FileStats(f1));
@@ -627,7 +844,7 @@ mod tests {
                 check_file_summary_val(fs.lines, 1, "line".to_owned());
                 check_file_summary_val(fs.words, 1, "word".to_owned());
                 check_file_summary_val(fs.bytes, 1, "byte".to_owned());
-                assert_eq!(fs.label, "file_1".to_owned());
+                assert_eq!(fs.label, "file_1");
             },
             WCResult::ErrMsg(e) => {
                 panic!("Should not have caused this error: {}", e);
@@ -640,10 +857,10 @@ mod tests {
     fn read_err_2() {
         debug_set_working_dir();
         let file_sum = summarize_files(
-            &vec![
-                "tests/test_files/does_not_exist.txt".to_owned(),
-                "tests/test_files/moby_dick.txt".to_owned()
-                ]);
+            &[
+                "tests/test_files/does_not_exist.txt".into(),
+                "tests/test_files/moby_dick.txt".into()
+                ], &get_default_args());
         assert_eq!(file_sum.len(), 2); // there should be just one item in this vec.
 
         match &file_sum[0] {
@@ -651,15 +868,15 @@ mod tests {
                 panic!("Should not have found the file");
             },
             WCResult::ErrMsg(e) => {
-                let expected_linux = "No such file or directory (os error 2): tests/test_files/does_not_exist.txt";
-                let expected_windows = "The system cannot find the file specified. (os error 2): src/wc/test_files/does_not_exist.txt";
+                let expected_linux = "tests/test_files/does_not_exist.txt: No such file or directory";
+                let expected_windows = "tests/test_files/does_not_exist.txt: The system cannot find the file specified.";
                 let expected = match std::env::consts::OS {
                     "linux" => expected_linux,
                     "windows" => expected_windows,
                     _ => panic!("Not tested on this operating system: {}", std::env::consts::OS),
                 };
 
-                assert_eq!(e, expected);
+                assert_eq!(e.to_string(), expected);
             }
         }
 
@@ -680,11 +897,11 @@ mod tests {
     fn read_err_3() {
         debug_set_working_dir();
         let file_sum = summarize_files(
-            &vec![
-            "tests/test_files/frankenstein.txt".to_owned(),
-            "tests/test_files/does_not_exist.txt".to_owned(),
-            "tests/test_files/moby_dick.txt".to_owned()
-            ]);
+            &[
+            "tests/test_files/frankenstein.txt".into(),
+            "tests/test_files/does_not_exist.txt".into(),
+            "tests/test_files/moby_dick.txt".into()
+            ], &get_default_args());
         assert_eq!(file_sum.len(), 3); // there should be just one item in this vec.
 
         match &file_sum[0] {
@@ -703,14 +920,14 @@ mod tests {
                 panic!("Should not have found the file");
             },
             WCResult::ErrMsg(e) => {
-                let expected_linux = "No such file or directory (os error 2): tests/test_files/does_not_exist.txt";
-                let expected_windows = "The system cannot find the file specified. (os error 2): src/wc/test_files/does_not_exist.txt";
+                let expected_linux = "tests/test_files/does_not_exist.txt: No such file or directory";
+                let expected_windows = "tests/test_files/does_not_exist.txt: The system cannot find the file specified.";
                 let expected = match std::env::consts::OS {
                     "linux" => expected_linux,
                     "windows" => expected_windows,
                     _ => panic!("Not tested on this operating system: {}", std::env::consts::OS),
                 };
-                assert_eq!(e, expected);
+                assert_eq!(e.to_string(), expected);
             }
         }
 
@@ -729,29 +946,52 @@ mod tests {
     /// Test setting the padding parameter to 5 using a mock struct.
     #[test]
     fn test_format_summary_padding_5() {
-        let ws = FileSummary{lines: 1, words: 1, chars: 1, bytes: 1, label: "thing".to_owned()};
+        let ws = FileSummary{lines: 1, words: 1, chars: 1, bytes: 1, label: "thing".into(), ..Default::default()};
         let args = get_default_args();
-        let s = format_summary(&ws, 5, &args);
+        let s = format_text(&ws, 5, &args);
         assert_eq!(s, "    1     1     1 thing");
     }
 
     /// Test setting the padding parameter to 2 using a mock struct.
     #[test]
     fn test_format_summary_padding_2() {
-        let ws = FileSummary{lines: 1, words: 1, chars: 1, bytes: 1, label: "thing".to_owned()};
+        let ws = FileSummary{lines: 1, words: 1, chars: 1, bytes: 1, label: "thing".into(), ..Default::default()};
         let args = get_default_args();
-        let s = format_summary(&ws, 2, &args);
+        let s = format_text(&ws, 2, &args);
         assert_eq!(s, " 1  1  1 thing");
     }
 
+    /// Test that only names with a newline in them are quoted.
+    #[test]
+    fn test_format_summary_quoted_label() {
+        let args = get_default_args();
+        let ws = FileSummary{lines: 1, words: 1, chars: 1, bytes: 1, label: "a\nb".into(), ..Default::default()};
+        assert_eq!(format_text(&ws, 1, &args), "1 1 1 'a'$'\\n''b'");
+        let ws = FileSummary{label: "a b\t".into(), ..ws};
+        assert_eq!(format_text(&ws, 1, &args), "1 1 1 a b\t");
+    }
+
+    /// Test that names that aren't UTF-8 are shown byte for byte, unless they're quoted.
+    #[cfg(unix)]
+    #[test]
+    fn test_format_summary_label_not_utf8() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let args = get_default_args();
+        let ws = FileSummary{lines: 1, words: 1, chars: 1, bytes: 1, label: OsStr::from_bytes(b"caf\xe9").into(), ..Default::default()};
+        assert_eq!(format_summary(&ws, 1, &args), b"1 1 1 caf\xe9");
+        let ws = FileSummary{label: OsStr::from_bytes(b"a\n\xe9").into(), ..ws};
+        assert_eq!(format_summary(&ws, 1, &args), b"1 1 1 'a'$'\\n\\351'");
+    }
+
     /// Test the padding size when reading several large files.
     #[test]
     fn test_format_summary_padding_3() {
         debug_set_working_dir();
         let mut file_sum = summarize_files(
-            &vec![
-            "tests/test_files/dracula.txt".to_owned(),
-            "tests/test_files/frankenstein.txt".to_owned()]);
+            &[
+            "tests/test_files/dracula.txt".into(),
+            "tests/test_files/frankenstein.txt".into()], &get_default_args());
         let max_len = get_totals(&mut file_sum);
         assert_eq!(max_len, 7, "Max length should have been 7, but was {}", max_len);
     }
@@ -0,0 +1,249 @@
+//! Property tests for counting.
+//!
+//! The tests in `wc::tests` check a few hand-recorded numbers for the files in
+//! `tests/test_files`. These generate lots of random input instead (invalid UTF-8,
+//! `\r\n` line endings, Unicode whitespace, very long lines) and check things that
+//! should always be true, no matter what the input is.
+
+use std::io::Read;
+
+use clap::Parser;
+use proptest::prelude::*;
+
+use super::*;
+use encoding::Encoding;
+
+/// Helper method to make command line arguments. Everything is counted, and line
+/// stats are kept so they get checked too.
+fn args(encoding: Encoding) -> Cli {
+    let mut args = Cli::parse_from(["wc", "-lwmcL", "--line-stats"]);
+    args.encoding = encoding;
+    args
+}
+
+/// A reader that hands out its data in pieces of the given sizes, to check that the
+/// way reads are split up doesn't change anything.
+struct ChunkedReader<'a> {
+    data: &'a [u8],
+    sizes: Vec<usize>,
+    next: usize,
+}
+
+impl Read for ChunkedReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let size = self.sizes[self.next % self.sizes.len()];
+        self.next += 1;
+
+        let n = size.min(self.data.len()).min(buf.len());
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        Ok(n)
+    }
+}
+
+fn any_encoding() -> impl Strategy<Value = Encoding> {
+    prop_oneof![
+        Just(Encoding::Auto),
+        Just(Encoding::Utf8),
+        Just(Encoding::Utf16le),
+        Just(Encoding::Utf16be),
+        Just(Encoding::Latin1),
+    ]
+}
+
+/// Bytes that look a bit like text, so line endings, whitespace, multi-byte
+/// characters, and invalid UTF-8 all show up more often than in fully random bytes.
+fn text_like_bytes() -> impl Strategy<Value = Vec<u8>> {
+    let piece = prop_oneof![
+        4 => prop::sample::select(b"abc XYZ.,".to_vec()).prop_map(|b| vec![b]),
+        2 => Just(b"\n".to_vec()),
+        1 => Just(b"\r\n".to_vec()),
+        1 => Just(b"\t".to_vec()),
+        1 => Just("é".as_bytes().to_vec()),
+        1 => Just("漢".as_bytes().to_vec()),
+        1 => Just("\u{2003}".as_bytes().to_vec()),
+        1 => Just(vec![0xFF]),
+        1 => Just(vec![0xE2, 0x82]),
+        1 => any::<u8>().prop_map(|b| vec![b]),
+    ];
+    prop::collection::vec(piece, 0..300).prop_map(|pieces| pieces.concat())
+}
+
+fn any_bytes() -> impl Strategy<Value = Vec<u8>> {
+    prop_oneof![
+        prop::collection::vec(any::<u8>(), 0..2048),
+        text_like_bytes(),
+    ]
+}
+
+/// Characters that the standard wc treats as word separators.
+fn word_separator() -> impl Strategy<Value = char> {
+    prop::sample::select(vec![
+        ' ', '\t', '\n', '\x0b', '\x0c', '\r', '\u{A0}', '\u{1680}', '\u{2000}', '\u{2003}',
+        '\u{200A}', '\u{202F}', '\u{205F}', '\u{2060}', '\u{3000}',
+    ])
+}
+
+proptest! {
+    /// Splitting the input into reads of any size gives the same result as reading it all at once.
+    #[test]
+    fn chunking_never_changes_results(
+        data in any_bytes(),
+        sizes in prop::collection::vec(1usize..70, 1..20),
+        encoding in any_encoding(),
+    ) {
+        let args = args(encoding);
+        let whole = handle_file_contents(&data, &args);
+        let mut reader = ChunkedReader { data: &data, sizes, next: 0 };
+        let chunked = summarize_reader(&mut reader, &args).unwrap();
+        prop_assert_eq!(chunked, whole);
+    }
+
+    /// The byte count is always the raw size, and there are never more characters than bytes.
+    #[test]
+    fn bytes_are_raw_size(data in any_bytes(), encoding in any_encoding()) {
+        let fs = handle_file_contents(&data, &args(encoding));
+        prop_assert_eq!(fs.bytes, data.len());
+        prop_assert!(fs.chars <= fs.bytes);
+        prop_assert!(fs.words <= fs.chars);
+    }
+
+    /// Lines are newlines, plus one for a last line with no newline at the end.
+    ///
+    /// This is a known difference from GNU wc, which only counts newlines (see
+    /// `Counter::finish()`). It's checked here so it doesn't change by accident, not
+    /// because it's what GNU does. A change to match GNU should change this test too.
+    #[test]
+    fn lines_are_newlines_plus_unfinished_line_not_gnu(data in any_bytes()) {
+        let newlines = data.iter().filter(|b| **b == b'\n').count();
+        let unfinished = usize::from(data.last().is_some_and(|b| *b != b'\n'));
+        for encoding in [Encoding::Utf8, Encoding::Latin1] {
+            let fs = handle_file_contents(&data, &args(encoding));
+            prop_assert_eq!(fs.lines, newlines + unfinished);
+        }
+    }
+
+    /// Invalid UTF-8 bytes are not counted as characters.
+    #[test]
+    fn invalid_utf8_not_counted_as_chars(data in any_bytes()) {
+        let valid_chars: usize = data.utf8_chunks().map(|c| c.valid().chars().count()).sum();
+        let fs = handle_file_contents(&data, &args(Encoding::Utf8));
+        prop_assert_eq!(fs.chars, valid_chars);
+    }
+
+    /// In Latin-1, every byte is a character.
+    #[test]
+    fn latin1_chars_are_bytes(data in any_bytes()) {
+        let fs = handle_file_contents(&data, &args(Encoding::Latin1));
+        prop_assert_eq!(fs.chars, data.len());
+    }
+
+    /// For valid UTF-8, counts match the `str` methods the original wc code used.
+    #[test]
+    fn utf8_matches_str_methods(s in any::<String>()) {
+        let fs = handle_file_contents(s.as_bytes(), &args(Encoding::Utf8));
+        prop_assert_eq!(fs.chars, s.chars().count());
+        prop_assert_eq!(fs.lines, s.lines().count());
+    }
+
+    /// For printable ASCII and ASCII whitespace, words match `str::split_ascii_whitespace()`.
+    #[test]
+    fn ascii_words_match_split(s in "[ -~\t\n\r\x0c]*") {
+        let fs = handle_file_contents(s.as_bytes(), &args(Encoding::Auto));
+        prop_assert_eq!(fs.words, s.split_ascii_whitespace().count());
+    }
+
+    /// Words joined by any mix of ASCII and Unicode whitespace are all counted.
+    #[test]
+    fn unicode_whitespace_separates_words(
+        words in prop::collection::vec("[a-zé漢]{1,6}", 0..30),
+        seps in prop::collection::vec(prop::collection::vec(word_separator(), 1..4), 30),
+    ) {
+        let mut text = String::new();
+        for (word, sep) in words.iter().zip(&seps) {
+            text.push_str(word);
+            text.extend(sep);
+        }
+        let fs = handle_file_contents(text.as_bytes(), &args(Encoding::Utf8));
+        prop_assert_eq!(fs.words, words.len());
+    }
+
+    /// Changing `\n` to `\r\n` only adds characters and bytes. Line lengths don't
+    /// include the line ending, so line stats don't change either.
+    #[test]
+    fn crlf_same_as_lf(s in "[^\r]*") {
+        let crlf = s.replace('\n', "\r\n");
+        let newlines = s.matches('\n').count();
+        let lf = handle_file_contents(s.as_bytes(), &args(Encoding::Utf8));
+        let fs = handle_file_contents(crlf.as_bytes(), &args(Encoding::Utf8));
+        prop_assert_eq!(fs.lines, lf.lines);
+        prop_assert_eq!(fs.words, lf.words);
+        prop_assert_eq!(fs.chars, lf.chars + newlines);
+        prop_assert_eq!(fs.bytes, lf.bytes + newlines);
+        prop_assert_eq!(fs.line_stats, lf.line_stats);
+    }
+
+    /// UTF-16 text with a byte order mark counts the same as UTF-8 text with one,
+    /// except for bytes.
+    #[test]
+    fn utf16_matches_utf8(s in any::<String>()) {
+        let text = format!("\u{FEFF}{}", s);
+        let le: Vec<u8> = text.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let be: Vec<u8> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+
+        let utf8 = handle_file_contents(text.as_bytes(), &args(Encoding::Auto));
+        for data in [le, be] {
+            let fs = handle_file_contents(&data, &args(Encoding::Auto));
+            prop_assert_eq!(fs.bytes, data.len());
+            prop_assert_eq!(
+                (fs.lines, fs.words, fs.chars, fs.max_line_length),
+                (utf8.lines, utf8.words, utf8.chars, utf8.max_line_length));
+        }
+    }
+
+    /// Adding up the rows gives the total. The max line length of the total is the
+    /// longest of any row, not a sum.
+    #[test]
+    fn totals_equal_sums(rows in prop::collection::vec((0usize..1_000_000, 0usize..1_000_000, 0usize..1_000), 2..20)) {
+        let mut summaries: Vec<WCResult> = rows.iter().enumerate().map(|(i, (lines, bytes, width))| {
+            WCResult::FileStats(FileSummary {
+                lines: *lines, words: lines / 2, chars: *bytes, bytes: *bytes, max_line_length: *width,
+                label: format!("file_{}", i).into(), ..Default::default()
+            })
+        }).collect();
+
+        let max_len = get_totals(&mut summaries);
+        prop_assert_eq!(summaries.len(), rows.len() + 1);
+        let total = match summaries.last() {
+            Some(WCResult::FileStats(f)) => f.clone(),
+            _ => panic!("last row should be the total"),
+        };
+        prop_assert_eq!(total.lines, rows.iter().map(|r| r.0).sum::<usize>());
+        prop_assert_eq!(total.words, rows.iter().map(|r| r.0 / 2).sum::<usize>());
+        prop_assert_eq!(total.bytes, rows.iter().map(|r| r.1).sum::<usize>());
+        prop_assert_eq!(total.max_line_length, rows.iter().map(|r| r.2).max().unwrap());
+        prop_assert_eq!(max_len, total.bytes.max(total.lines).to_string().len());
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    /// Very long lines are measured correctly, in display width, bytes, and characters.
+    #[test]
+    fn huge_lines(n in 0usize..300_000, c in prop::sample::select(vec!['x', 'é', '漢'])) {
+        let mut line: String = std::iter::repeat_n(c, n).collect();
+        line.push('\n');
+        let fs = handle_file_contents(line.as_bytes(), &args(Encoding::Utf8));
+        let width = if c == '漢' { 2 } else { 1 };
+
+        prop_assert_eq!(fs.lines, 1);
+        prop_assert_eq!(fs.words, usize::from(n > 0));
+        prop_assert_eq!(fs.chars, n + 1);
+        prop_assert_eq!(fs.max_line_length, n * width);
+
+        let stats = fs.line_stats.unwrap();
+        prop_assert_eq!(stats.summary(line_stats::LengthUnit::Bytes).unwrap().max, n * c.len_utf8());
+        prop_assert_eq!(stats.summary(line_stats::LengthUnit::Chars).unwrap().max, n);
+    }
+}
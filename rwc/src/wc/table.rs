@@ -0,0 +1,230 @@
+//! Ordering and filtering of the rows that wc prints.
+//!
+//! The standard wc prints one row per file, in the order the files were given
+//! on the command line. When there are thousands of files, it's handy to sort
+//! them or only show some of them without piping into `sort -n` and `head`.
+
+use std::cmp::Ordering;
+
+use crate::Cli;
+use super::{FileSummary, WCResult};
+
+/// The value to sort rows by. Set with `--sort`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SortKey {
+    Lines,
+    Words,
+    Chars,
+    Bytes,
+    Name,
+}
+
+/// Parsed value of the `--sort` command line option, like `lines` or `bytes,reverse`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SortSpec {
+    /// Value to sort by.
+    pub(crate) key: SortKey,
+    /// Sort largest first (or Z to A for names) instead of smallest first.
+    pub(crate) reverse: bool,
+}
+
+/// Parse the value of the `--sort` option. Used by `clap` as a value parser.
+///
+/// Accepts one of `lines`, `words`, `chars`, `bytes`, or `name`, optionally
+/// followed by `,reverse`.
+///
+/// # Arguments
+///
+///  * `s` - the text entered after `--sort=`.
+pub(crate) fn parse_sort_spec(s: &str) -> Result<SortSpec, String> {
+    let mut parts = s.split(',');
+    let key = match parts.next().unwrap_or("") {
+        "lines" => SortKey::Lines,
+        "words" => SortKey::Words,
+        "chars" => SortKey::Chars,
+        "bytes" => SortKey::Bytes,
+        "name" => SortKey::Name,
+        other => return Err(format!(
+            "invalid sort key '{}' (expected lines, words, chars, bytes, or name)", other)),
+    };
+
+    let reverse = match parts.next() {
+        None => false,
+        Some("reverse") => true,
+        Some(other) => return Err(format!("invalid sort modifier '{}' (expected reverse)", other)),
+    };
+
+    if parts.next().is_some() {
+        return Err(format!("too many sort modifiers in '{}'", s));
+    }
+
+    Ok(SortSpec { key, reverse })
+}
+
+/// Sort, filter, and truncate the rows in `summaries`, according to the
+/// `--sort`, `--top`, and `--min-*`/`--max-*` command line options.
+///
+/// The totals row should not be in `summaries`. It's counted from every file,
+/// whether or not the file is shown, and is always printed last.
+///
+/// If any of the options were used, error messages are moved to the front, in
+/// the order they were found. They go to stderr anyway, so this just gets them
+/// out of the way of the sorted rows. If none of the options were used, nothing
+/// changes.
+///
+/// # Arguments
+///
+///  * `summaries` - a Vec of `WCResult` enums, not including the totals row.
+///  * `args` - the command line arguments, as a reference to a `Cli` object
+pub(super) fn arrange(summaries: &mut Vec<WCResult>, args: &Cli) {
+    if args.sort.is_none() && args.top.is_none() && !has_filters(args) {
+        return;
+    }
+
+    let mut errors = Vec::new();
+    let mut rows = Vec::new();
+    for summary in summaries.drain(..) {
+        match summary {
+            WCResult::FileStats(s) => if in_range(&s, args) { rows.push(s) },
+            WCResult::ErrMsg(_) => errors.push(summary),
+        }
+    }
+
+    if let Some(spec) = args.sort {
+        // sort_by is stable, so ties stay in command line order
+        rows.sort_by(|a, b| {
+            let ord = compare(a, b, spec.key);
+            if spec.reverse { ord.reverse() } else { ord }
+        });
+    }
+
+    if let Some(top) = args.top {
+        rows.truncate(top);
+    }
+
+    summaries.append(&mut errors);
+    summaries.extend(rows.into_iter().map(WCResult::FileStats));
+}
+
+/// Returns `true` if any of the `--min-*` or `--max-*` options were used.
+fn has_filters(args: &Cli) -> bool {
+    [
+        args.min_lines, args.max_lines,
+        args.min_words, args.max_words,
+        args.min_chars, args.max_chars,
+        args.min_bytes, args.max_bytes,
+    ].iter().any(Option::is_some)
+}
+
+/// Check whether a row should be shown, according to the `--min-*` and
+/// `--max-*` options. Both ends of each range are inclusive.
+fn in_range(f: &FileSummary, args: &Cli) -> bool {
+    let within = |val: usize, min: Option<usize>, max: Option<usize>| {
+        min.is_none_or(|m| val >= m) && max.is_none_or(|m| val <= m)
+    };
+
+    within(f.lines, args.min_lines, args.max_lines)
+        && within(f.words, args.min_words, args.max_words)
+        && within(f.chars, args.min_chars, args.max_chars)
+        && within(f.bytes, args.min_bytes, args.max_bytes)
+}
+
+/// Compare two rows by the given key, smallest first.
+fn compare(a: &FileSummary, b: &FileSummary, key: SortKey) -> Ordering {
+    match key {
+        SortKey::Lines => a.lines.cmp(&b.lines),
+        SortKey::Words => a.words.cmp(&b.words),
+        SortKey::Chars => a.chars.cmp(&b.chars),
+        SortKey::Bytes => a.bytes.cmp(&b.bytes),
+        SortKey::Name => a.label.cmp(&b.label),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+    use shared::error::UError;
+
+    /// Helper method to make a mock `FileSummary` with the same value for every count.
+    fn row(label: &str, n: usize) -> WCResult {
+        WCResult::FileStats(FileSummary {lines: n, words: n, chars: n, bytes: n, max_line_length: n, label: label.into(), ..Default::default()})
+    }
+
+    /// Helper method to get the labels of the rows, in order. Errors show up as `err`.
+    fn labels(summaries: &[WCResult]) -> Vec<&str> {
+        summaries.iter().map(|s| match s {
+            WCResult::FileStats(f) => f.label.to_str().unwrap(),
+            WCResult::ErrMsg(_) => "err",
+        }).collect()
+    }
+
+    /// Helper method to create command line arguments with no sorting or filtering.
+    fn get_args() -> Cli {
+        Cli::parse_from(["wc", "-lwc"])
+    }
+
+    #[test]
+    fn parse_sort_spec_valid() {
+        assert_eq!(parse_sort_spec("lines"), Ok(SortSpec {key: SortKey::Lines, reverse: false}));
+        assert_eq!(parse_sort_spec("name,reverse"), Ok(SortSpec {key: SortKey::Name, reverse: true}));
+        assert_eq!(parse_sort_spec("bytes,reverse"), Ok(SortSpec {key: SortKey::Bytes, reverse: true}));
+    }
+
+    #[test]
+    fn parse_sort_spec_invalid() {
+        assert!(parse_sort_spec("").is_err());
+        assert!(parse_sort_spec("size").is_err());
+        assert!(parse_sort_spec("lines,backwards").is_err());
+        assert!(parse_sort_spec("lines,reverse,reverse").is_err());
+    }
+
+    /// With no options, the rows should come back exactly as they went in.
+    #[test]
+    fn arrange_no_options() {
+        let mut fv = vec![row("b", 2), WCResult::ErrMsg(UError::new("oops")), row("a", 1)];
+        arrange(&mut fv, &get_args());
+        assert_eq!(labels(&fv), vec!["b", "err", "a"]);
+    }
+
+    /// Sort by lines, both ways. Errors should be moved to the front.
+    #[test]
+    fn arrange_sort_lines() {
+        let mut args = get_args();
+        args.sort = Some(SortSpec {key: SortKey::Lines, reverse: false});
+
+        let mut fv = vec![row("b", 2), WCResult::ErrMsg(UError::new("oops")), row("c", 3), row("a", 1)];
+        arrange(&mut fv, &args);
+        assert_eq!(labels(&fv), vec!["err", "a", "b", "c"]);
+
+        args.sort = Some(SortSpec {key: SortKey::Lines, reverse: true});
+        arrange(&mut fv, &args);
+        assert_eq!(labels(&fv), vec!["err", "c", "b", "a"]);
+    }
+
+    /// Rows with the same value should stay in command line order.
+    #[test]
+    fn arrange_sort_stable() {
+        let mut args = get_args();
+        args.sort = Some(SortSpec {key: SortKey::Bytes, reverse: false});
+
+        let mut fv = vec![row("z", 5), row("y", 1), row("x", 5)];
+        arrange(&mut fv, &args);
+        assert_eq!(labels(&fv), vec!["y", "z", "x"]);
+    }
+
+    /// Filter, then only keep the first two of what's left.
+    #[test]
+    fn arrange_filter_and_top() {
+        let mut args = get_args();
+        args.sort = Some(SortSpec {key: SortKey::Name, reverse: false});
+        args.min_lines = Some(2);
+        args.max_bytes = Some(9);
+        args.top = Some(2);
+
+        let mut fv = vec![row("e", 10), row("d", 4), row("c", 1), row("b", 9), row("a", 2)];
+        arrange(&mut fv, &args);
+        assert_eq!(labels(&fv), vec!["a", "b"]);
+    }
+}
@@ -0,0 +1,86 @@
+//! Finding command line operands that are really the same file, for the `--unique` option.
+//!
+//! The same file can show up more than once through symlinks, hard links, or
+//! different relative paths. With `--unique`, every operand is still listed, but
+//! each physical file is only counted once in the total.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Something that identifies a physical file, no matter what path was used to get to it.
+///
+/// On Unix this is the device and inode number. Elsewhere it's the canonical path,
+/// which catches symlinks and relative paths, but not hard links.
+#[cfg(unix)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct FileId(u64, u64);
+
+#[cfg(not(unix))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct FileId(std::path::PathBuf);
+
+impl FileId {
+    /// Get the `FileId` of the file at `path`, following symlinks.
+    /// Returns `None` if the file can't be looked at.
+    #[cfg(unix)]
+    pub(crate) fn of(path: &Path) -> Option<FileId> {
+        use std::os::unix::fs::MetadataExt;
+
+        let meta = fs::metadata(path).ok()?;
+        Some(FileId(meta.dev(), meta.ino()))
+    }
+
+    #[cfg(not(unix))]
+    pub(crate) fn of(path: &Path) -> Option<FileId> {
+        fs::canonicalize(path).ok().map(FileId)
+    }
+}
+
+/// Remembers which files have been seen, and where.
+#[derive(Debug, Default)]
+pub(crate) struct SeenFiles {
+    seen: HashMap<FileId, usize>,
+}
+
+impl SeenFiles {
+    /// Check whether `path` is a file that was already seen. If it was, return the
+    /// value that was given with it the first time. If not, remember it with `value`.
+    ///
+    /// Files that can't be looked at are never treated as duplicates, so the real
+    /// error shows up when the file is read.
+    pub(crate) fn check(&mut self, path: &Path, value: usize) -> Option<usize> {
+        let id = FileId::of(path)?;
+        match self.seen.get(&id) {
+            Some(first) => Some(*first),
+            None => {
+                self.seen.insert(id, value);
+                None
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wc::tests::debug_set_working_dir;
+
+    #[test]
+    fn same_file_different_paths() {
+        debug_set_working_dir();
+        let mut seen = SeenFiles::default();
+        assert_eq!(seen.check(Path::new("tests/test_files/trees.txt"), 0), None);
+        assert_eq!(seen.check(Path::new("tests/test_files/jack.txt"), 1), None);
+        assert_eq!(seen.check(Path::new("tests/../tests/test_files/trees.txt"), 2), Some(0));
+        assert_eq!(seen.check(Path::new("./tests/test_files/jack.txt"), 3), Some(1));
+    }
+
+    #[test]
+    fn missing_file_is_never_a_duplicate() {
+        debug_set_working_dir();
+        let mut seen = SeenFiles::default();
+        assert_eq!(seen.check(Path::new("tests/test_files/does_not_exist.txt"), 0), None);
+        assert_eq!(seen.check(Path::new("tests/test_files/does_not_exist.txt"), 1), None);
+    }
+}
@@ -0,0 +1,145 @@
+/// Compare output with the system's GNU wc, if there is one.
+///
+/// The column padding is different on purpose (see `format_summary()`), so only the
+/// numbers and labels on each line are compared, not the spaces between them.
+///
+/// Inputs with a known difference are skipped, see `known_difference()`.
+mod test_utils;
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+
+    use harness::Scene;
+    use proptest::prelude::*;
+
+    use crate::test_utils as tu;
+
+    /// Helper method to get a `Command` for the system wc, set up to use a UTF-8 locale.
+    /// Returns `None` if there is no GNU wc, or it can't count UTF-8 characters.
+    fn system_wc() -> Option<Command> {
+        let path = ["/usr/bin/wc", "/bin/wc"].into_iter().find(|p| Path::new(p).exists())?;
+        let make = || {
+            let mut cmd = Command::new(path);
+            cmd.env("LC_ALL", "C.UTF-8");
+            cmd
+        };
+
+        let version = make().arg("--version").output().ok()?;
+        if !String::from_utf8_lossy(&version.stdout).contains("GNU coreutils") {
+            return None;
+        }
+
+        // without a UTF-8 locale, -m counts bytes
+        let mut check = make();
+        check.arg("-m").stdin(std::process::Stdio::piped()).stdout(std::process::Stdio::piped());
+        let mut child = check.spawn().ok()?;
+        child.stdin.take()?.write_all("é\n".as_bytes()).ok()?;
+        let chars = child.wait_with_output().ok()?;
+        if String::from_utf8_lossy(&chars.stdout).trim() != "2" {
+            return None;
+        }
+
+        Some(make())
+    }
+
+    /// A last line with no newline at the end is counted as a line here, but not by
+    /// GNU wc (see `lines_are_newlines_plus_unfinished_line_not_gnu` in `properties.rs`).
+    const UNFINISHED_LAST_LINE: &str = "last line has no newline, which GNU wc doesn't count as a line";
+
+    /// Helper method to say why the two programs are known to count `data` differently,
+    /// or `None` if they should count it the same.
+    fn known_difference(data: &[u8]) -> Option<&'static str> {
+        (!data.is_empty() && data.last() != Some(&b'\n')).then_some(UNFINISHED_LAST_LINE)
+    }
+
+    /// Helper method to split output into lines of whitespace separated fields.
+    fn fields(output: &[u8]) -> Vec<Vec<String>> {
+        String::from_utf8_lossy(output)
+            .lines()
+            .map(|l| l.split_whitespace().map(str::to_owned).collect())
+            .collect()
+    }
+
+    /// Run both programs with the same arguments and return their output, split into fields.
+    fn run_both(system: &mut Command, args: &[&str], files: &[&Path]) -> (Vec<Vec<String>>, Vec<Vec<String>>) {
+        // GNU wc doesn't look for UTF-16 byte order marks
+        let ours = tu::get_cmd().arg("--encoding=utf8").args(args).args(files).output().unwrap();
+        let theirs = system.args(args).args(files).output().unwrap();
+        (fields(&ours.stdout), fields(&theirs.stdout))
+    }
+
+    /// Count every test file without a known difference, with each of the counting switches.
+    #[test]
+    fn same_as_system_wc_test_files() {
+        let Some(_) = system_wc() else {
+            eprintln!("GNU wc not found, skipping differential test");
+            return;
+        };
+
+        let mut files: Vec<PathBuf> = std::fs::read_dir("tests/test_files").unwrap()
+            .map(|e| e.unwrap().path())
+            .filter(|p| match known_difference(&std::fs::read(p).unwrap()) {
+                Some(reason) => {
+                    eprintln!("skipping {}: {}", p.display(), reason);
+                    false
+                },
+                None => true,
+            })
+            .collect();
+        files.sort();
+        let files: Vec<&Path> = files.iter().map(PathBuf::as_path).collect();
+        assert!(files.len() > 5, "should have found the test files");
+
+        for args in [vec![], vec!["-l"], vec!["-w"], vec!["-m"], vec!["-c"], vec!["-L"], vec!["-lwmcL"]] {
+            let (ours, theirs) = run_both(&mut system_wc().unwrap(), &args, &files);
+            assert_eq!(ours, theirs, "output is different with arguments {:?}", args);
+        }
+    }
+
+    /// Bytes that look a bit like text: ASCII, whitespace (ASCII and Unicode), control
+    /// characters, multi-byte and wide characters, and invalid UTF-8. Most of them end
+    /// with a newline.
+    fn text_like_bytes() -> impl Strategy<Value = Vec<u8>> {
+        let pieces: Vec<&[u8]> = vec![
+            b"a", b"b", b"Z", b"7", b".", b" ", b"  ", b"\t", b"\n", b"\r\n", b"\x0b", b"\x0c",
+            b"\x01", b"\x7f", b"\xFF", b"\xE2\x82",
+            "é".as_bytes(), "漢".as_bytes(), "😀".as_bytes(), "\u{301}".as_bytes(),
+            "\u{A0}".as_bytes(), "\u{2003}".as_bytes(), "\u{3000}".as_bytes(),
+            "\u{200B}".as_bytes(), "\u{2028}".as_bytes(), "\u{FEFF}".as_bytes(),
+        ];
+        (prop::collection::vec(prop::sample::select(pieces), 0..400), prop::bool::weighted(0.9))
+            .prop_map(|(p, newline)| {
+                let mut data = p.concat();
+                if newline {
+                    data.push(b'\n');
+                }
+                data
+            })
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        /// Count random files with both programs.
+        #[test]
+        fn same_as_system_wc_random(files in prop::collection::vec(text_like_bytes(), 1..4)) {
+            let Some(mut system) = system_wc() else {
+                return Ok(());
+            };
+            let skip = files.iter().find_map(|data| known_difference(data));
+            prop_assume!(skip.is_none(), "{}", skip.unwrap_or_default());
+
+            // the scene's directory is removed when it's dropped, even if the case fails
+            let names: Vec<String> = (0..files.len()).map(|i| format!("random_{}.txt", i)).collect();
+            let scene = names.iter().zip(&files).fold(Scene::new(), |scene, (name, data)| scene.file(name, data));
+            let paths: Vec<PathBuf> = names.iter().map(|name| scene.join(name)).collect();
+            let paths: Vec<&Path> = paths.iter().map(PathBuf::as_path).collect();
+
+            let (ours, theirs) = run_both(&mut system, &["-lwmcL"], &paths);
+            prop_assert_eq!(ours, theirs, "files: {:?}", files);
+        }
+    }
+}
@@ -0,0 +1,128 @@
+/// Test counting files in other encodings using the --encoding switch.
+mod test_utils;
+
+#[cfg(test)]
+mod test {
+    use assert_cmd::prelude::*;
+    use predicates::prelude::*;
+
+    use crate::test_utils as tu;
+
+    /// Count a UTF-16LE file with a byte order mark. The encoding is found automatically:
+    /// ```
+    /// :~$ wc -lwmcL tests/test_files/encoding_utf16le.txt
+    /// ```
+    /// Characters, words, lines, and the max line width should match the UTF-8 version
+    /// of the file, plus one character for the byte order mark. Bytes are the raw file size.
+    /// ```
+    ///   3   9  58 116  21 tests/test_files/encoding_utf16le.txt
+    /// ```
+    #[test]
+    fn auto_utf16le_bom() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = tu::get_cmd();
+        cmd.arg("-lwmcL")
+            .arg("tests/test_files/encoding_utf16le.txt")
+            .assert()
+            .success()
+            .stdout(predicate::eq("  3   9  58 116  21 tests/test_files/encoding_utf16le.txt\n"))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Same as above, but big endian.
+    /// ```
+    /// :~$ wc -lwmcL tests/test_files/encoding_utf16be.txt
+    /// ```
+    #[test]
+    fn auto_utf16be_bom() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = tu::get_cmd();
+        cmd.arg("-lwmcL")
+            .arg("tests/test_files/encoding_utf16be.txt")
+            .assert()
+            .success()
+            .stdout(predicate::eq("  3   9  58 116  21 tests/test_files/encoding_utf16be.txt\n"))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Without a byte order mark, UTF-16 has to be asked for:
+    /// ```
+    /// :~$ wc -lwmcL --encoding=utf16le tests/test_files/encoding_utf16le_no_bom.txt
+    /// ```
+    /// Output should match the UTF-8 version of the file, except for bytes:
+    /// ```
+    ///   3   9  57 114  21 tests/test_files/encoding_utf16le_no_bom.txt
+    /// ```
+    #[test]
+    fn utf16le_no_bom() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = tu::get_cmd();
+        cmd.arg("-lwmcL")
+            .arg("--encoding=utf16le")
+            .arg("tests/test_files/encoding_utf16le_no_bom.txt")
+            .assert()
+            .success()
+            .stdout(predicate::eq("  3   9  57 114  21 tests/test_files/encoding_utf16le_no_bom.txt\n"))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Latin-1 and UTF-8 versions of the same text should have the same character counts:
+    /// ```
+    /// :~$ wc -m --encoding=latin1 tests/test_files/encoding_latin1.txt
+    /// :~$ wc -m --encoding=utf8 tests/test_files/encoding_utf8.txt
+    /// ```
+    #[test]
+    fn latin1_and_utf8_chars() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = tu::get_cmd();
+        cmd.arg("-mc")
+            .arg("--encoding=latin1")
+            .arg("tests/test_files/encoding_latin1.txt")
+            .assert()
+            .success()
+            .stdout(predicate::eq("57 57 tests/test_files/encoding_latin1.txt\n"))
+            .code(predicate::eq(0));
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("-mc")
+            .arg("--encoding=utf8")
+            .arg("tests/test_files/encoding_utf8.txt")
+            .assert()
+            .success()
+            .stdout(predicate::eq("57 63 tests/test_files/encoding_utf8.txt\n"))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// The max line length of the big files should match the standard wc:
+    /// ```
+    /// :~$ wc -L tests/test_files/frankenstein.txt tests/test_files/jack.txt
+    /// ```
+    /// Output from wc looks like this:
+    /// ```
+    ///    104 tests/test_files/frankenstein.txt
+    ///   1055 tests/test_files/jack.txt
+    ///   1055 total
+    /// ```
+    #[test]
+    fn max_line_length() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = concat!(
+            "   104 tests/test_files/frankenstein.txt\n",
+            "  1055 tests/test_files/jack.txt\n",
+            "  1055 total\n");
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("-L")
+            .arg("tests/test_files/frankenstein.txt")
+            .arg("tests/test_files/jack.txt")
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+}
@@ -17,11 +17,11 @@ mod test {
     /// ```
     /// wc: tests/test_files/does_not_exist.txt: No such file or directory
     /// ```
-    /// Make the output look like that, depending on the OS.
+    /// Make the output look like that, depending on the OS. The exit code should be 1.
     #[test]
     fn read_err() -> Result<(), Box<dyn std::error::Error>> {
-        let expected_linux = "No such file or directory (os error 2): tests/test_files/does_not_exist.txt\n";
-        let expected_windows = "The system cannot find the file specified. (os error 2): tests/test_files/does_not_exist.txt\n";
+        let expected_linux = "wc: tests/test_files/does_not_exist.txt: No such file or directory\n";
+        let expected_windows = "wc: tests/test_files/does_not_exist.txt: The system cannot find the file specified.\n";
         let expected = match std::env::consts::OS {
             "linux" => expected_linux,
             "windows" => expected_windows,
@@ -31,10 +31,96 @@ mod test {
         let mut cmd = tu::get_cmd();
         cmd.arg("tests/test_files/does_not_exist.txt")
             .assert()
-            .success()
+            .failure()
             .stderr(predicate::eq(expected))
-            .code(predicate::eq(0));
+            .code(predicate::eq(1));
         
         Ok(())
     }
+
+    /// Run wc with a directory between two files, like this:
+    /// ```
+    /// :~$ wc tests/test_files/trees.txt tests/test_files tests/test_files/trees.txt
+    /// ```
+    /// Output from wc looks like this:
+    /// ```
+    ///  21  83 415 tests/test_files/trees.txt
+    /// wc: tests/test_files: Is a directory
+    ///  21  83 415 tests/test_files/trees.txt
+    ///  42 166 830 total
+    /// ```
+    /// The other files are still counted, but the exit code should be 1.
+    #[cfg(unix)]
+    #[test]
+    fn read_dir_between_files() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = concat!(
+            " 21  83 415 tests/test_files/trees.txt\n",
+            " 21  83 415 tests/test_files/trees.txt\n",
+            " 42 166 830 total\n");
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("tests/test_files/trees.txt")
+            .arg("tests/test_files")
+            .arg("tests/test_files/trees.txt")
+            .assert()
+            .failure()
+            .stdout(predicate::eq(expected))
+            .stderr(predicate::eq("wc: tests/test_files: Is a directory\n"))
+            .code(predicate::eq(1));
+
+        Ok(())
+    }
+
+    /// Run wc with an option it doesn't have:
+    /// ```
+    /// :~$ wc --no-such-option tests/test_files/trees.txt
+    /// ```
+    /// The standard wc exits with 1 for a bad command line, not the 2 that clap uses.
+    #[test]
+    fn bad_option() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = tu::get_cmd();
+        cmd.arg("--no-such-option")
+            .arg("tests/test_files/trees.txt")
+            .assert()
+            .failure()
+            .stdout(predicate::str::is_empty())
+            .stderr(predicate::str::contains("--no-such-option"))
+            .code(predicate::eq(1));
+
+        Ok(())
+    }
+
+    /// `--help` isn't an error, so the exit code should be 0:
+    /// ```
+    /// :~$ wc --help
+    /// ```
+    #[test]
+    fn help() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = tu::get_cmd();
+        cmd.arg("--help")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Usage:"))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// `--version` isn't GNU's, so tests can tell them apart:
+    /// ```
+    /// :~$ wc --version
+    /// ```
+    #[test]
+    fn version() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = tu::get_cmd();
+        cmd.arg("--version")
+            .assert()
+            .success()
+            .stdout(predicate::str::starts_with(format!("wc {}\n", env!("CARGO_PKG_VERSION"))))
+            .stdout(predicate::str::contains("GNU coreutils").not())
+            .stdout(predicate::str::contains("@").not())
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
 }
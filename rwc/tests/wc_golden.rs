@@ -0,0 +1,95 @@
+/// Test against golden files in `tests/golden`, made by running GNU wc.
+///
+/// To make them again, and see what changed:
+/// ```text
+/// :~$ REGENERATE_GOLDEN=1 cargo test -p wc --test wc_golden
+/// :~$ git diff tests/golden
+/// ```
+/// Only cases where this wc is meant to match GNU wc go here. The column padding
+/// is different on purpose when a single count is shown (see `format_summary()`).
+#[cfg(unix)]
+mod test {
+    use harness::{Golden, Scene, UCmd};
+
+    /// Helper method to get the golden files for wc.
+    fn golden() -> Golden {
+        Golden::new("tests/golden")
+    }
+
+    /// Helper method to make a scene with two of the test files in it.
+    fn poems() -> Scene {
+        Scene::new()
+            .file_from("trees.txt", "tests/test_files/trees.txt")
+            .file_from("so_tired_blues.txt", "tests/test_files/so_tired_blues.txt")
+    }
+
+    /// ```
+    /// :~$ wc trees.txt
+    /// ```
+    #[test]
+    fn one_file() {
+        let scene = poems();
+        UCmd::new("wc").arg("trees.txt").in_scene(&scene).check_golden(&golden(), "one_file");
+    }
+
+    /// ```
+    /// :~$ wc -lw trees.txt so_tired_blues.txt
+    /// ```
+    #[test]
+    fn lines_and_words() {
+        let scene = poems();
+        UCmd::new("wc").args(["-lw", "trees.txt", "so_tired_blues.txt"]).in_scene(&scene)
+            .check_golden(&golden(), "lines_and_words");
+    }
+
+    /// ```
+    /// :~$ wc -m -L multi.txt
+    /// ```
+    #[test]
+    fn chars_and_longest_line() {
+        let scene = Scene::new().file("multi.txt", "día\n日本語のテキスト\ne\u{301}\n");
+        UCmd::new("wc").args(["-m", "-L", "multi.txt"]).in_scene(&scene)
+            .check_golden(&golden(), "chars_and_longest_line");
+    }
+
+    /// ```
+    /// :~$ wc nope trees.txt
+    /// ```
+    #[test]
+    fn missing_file() {
+        let scene = poems();
+        UCmd::new("wc").args(["nope", "trees.txt"]).in_scene(&scene)
+            .check_golden(&golden(), "missing_file");
+    }
+
+    /// ```
+    /// :~$ printf 'a b c\n' | wc -w -
+    /// ```
+    #[test]
+    fn stdin() {
+        UCmd::new("wc").args(["-w", "-"]).stdin("a b c\n").check_golden(&golden(), "stdin");
+    }
+
+    /// ```
+    /// :~$ wc 'no such' $'new\nline' "it's" a:b
+    /// ```
+    #[test]
+    fn quoted_names() {
+        let scene = Scene::new().file("new\nline", "x\n").file("it's", "x y\n");
+        UCmd::new("wc").args(["no such", "new\nline", "it's", "a:b"]).in_scene(&scene)
+            .check_golden(&golden(), "quoted_names");
+    }
+
+    /// ```
+    /// :~$ wc $'caf\xe9.txt' $'nope\xff' $'new\n\xe9'
+    /// ```
+    #[test]
+    fn names_not_utf8() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let names = [&b"caf\xe9.txt"[..], b"nope\xff", b"new\n\xe9"].map(OsStr::from_bytes);
+        let scene = Scene::new().file(names[0], "x y\n").file(names[2], "z\n");
+        UCmd::new("wc").args(names).in_scene(&scene).check_golden(&golden(), "names_not_utf8");
+    }
+}
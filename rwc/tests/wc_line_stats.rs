@@ -0,0 +1,133 @@
+/// Test line length statistics using the --line-stats and --histogram switches.
+mod test_utils;
+
+#[cfg(test)]
+mod test {
+    use assert_cmd::prelude::*;
+    use predicates::prelude::*;
+
+    use crate::test_utils as tu;
+
+    /// Print line stats for one small file:
+    /// ```
+    /// :~$ wc -l --line-stats tests/test_files/fire_and_ice.txt
+    /// ```
+    /// Output should look like this:
+    /// ```
+    ///  13 tests/test_files/fire_and_ice.txt
+    ///     line bytes: min 0 mean 19.9 median 18 p95 36 max 36
+    ///     line chars: min 0 mean 19.9 median 18 p95 36 max 36
+    /// ```
+    #[test]
+    fn line_stats_fire() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = concat!(
+            " 13 tests/test_files/fire_and_ice.txt\n",
+            "    line bytes: min 0 mean 19.9 median 18 p95 36 max 36\n",
+            "    line chars: min 0 mean 19.9 median 18 p95 36 max 36\n");
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("-l")
+            .arg("--line-stats")
+            .arg("tests/test_files/fire_and_ice.txt")
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Line stats for the total should cover the lines of every file:
+    /// ```
+    /// :~$ wc -l --line-stats tests/test_files/fire_and_ice.txt tests/test_files/so_tired_blues.txt
+    /// ```
+    /// The last three lines of output should look like this:
+    /// ```
+    ///  22 total
+    ///     line bytes: min 0 mean 17.3 median 16 p95 34 max 36
+    ///     line chars: min 0 mean 17.3 median 16 p95 34 max 36
+    /// ```
+    #[test]
+    fn line_stats_total() -> Result<(), Box<dyn std::error::Error>> {
+        let expected_total = concat!(
+            " 22 total\n",
+            "    line bytes: min 0 mean 17.3 median 16 p95 34 max 36\n",
+            "    line chars: min 0 mean 17.3 median 16 p95 34 max 36\n");
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("-l")
+            .arg("--line-stats")
+            .arg("tests/test_files/fire_and_ice.txt")
+            .arg("tests/test_files/so_tired_blues.txt")
+            .assert()
+            .success()
+            .stdout(predicate::str::ends_with(expected_total))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Print a histogram of line lengths. With no value, --histogram uses characters, and
+    /// it should not take the file name as its value:
+    /// ```
+    /// :~$ wc -l --histogram tests/test_files/so_tired_blues.txt
+    /// ```
+    /// Output should look like this:
+    /// ```
+    ///   9 tests/test_files/so_tired_blues.txt
+    ///     line length (chars):
+    ///      0 -  0 2 ####################
+    ///      1 -  1 0
+    ///      2 -  3 0
+    ///      4 -  7 0
+    ///      8 - 15 3 ##############################
+    ///     16 - 31 4 ########################################
+    /// ```
+    #[test]
+    fn histogram_so_tired() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = concat!(
+            "  9 tests/test_files/so_tired_blues.txt\n",
+            "    line length (chars):\n",
+            "     0 -  0 2 ####################\n",
+            "     1 -  1 0\n",
+            "     2 -  3 0\n",
+            "     4 -  7 0\n",
+            "     8 - 15 3 ##############################\n",
+            "    16 - 31 4 ########################################\n");
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("-l")
+            .arg("--histogram")
+            .arg("tests/test_files/so_tired_blues.txt")
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Line lengths in bytes and chars are different for UTF-16, and the \r\n line
+    /// endings are not part of the length:
+    /// ```
+    /// :~$ wc -l --line-stats tests/test_files/encoding_utf16be.txt
+    /// ```
+    #[test]
+    fn line_stats_utf16() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = concat!(
+            "  3 tests/test_files/encoding_utf16be.txt\n",
+            "    line bytes: min 24 mean 34.7 median 38 p95 42 max 42\n",
+            "    line chars: min 12 mean 17.3 median 19 p95 21 max 21\n");
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("-l")
+            .arg("--line-stats")
+            .arg("tests/test_files/encoding_utf16be.txt")
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+}
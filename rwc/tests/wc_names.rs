@@ -0,0 +1,54 @@
+/// Test file names that aren't UTF-8. They're shown byte for byte in rows, and
+/// quoted in error messages.
+#[cfg(test)]
+#[cfg(unix)]
+mod test {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    use harness::{Scene, UCmd};
+
+    /// Count a file with a Latin-1 name, and one that isn't there:
+    /// ```
+    /// :~$ wc $'caf\xe9.txt' $'nope\xff'
+    /// ```
+    /// Output from wc looks like this, with the byte `\xe9` in the row as it is:
+    /// ```
+    /// 1 2 4 caf?.txt
+    /// wc: 'nope'$'\377': No such file or directory
+    /// 1 2 4 total
+    /// ```
+    /// The exit code should be 1.
+    #[test]
+    fn latin1_names() {
+        let name = OsStr::from_bytes(b"caf\xe9.txt");
+        let scene = Scene::new().file(name, "x y\n");
+
+        let capture = UCmd::new("wc").arg(name).arg(OsStr::from_bytes(b"nope\xff")).in_scene(&scene).run();
+        assert_eq!(capture.stdout, b"1 2 4 caf\xe9.txt\n1 2 4 total\n");
+        assert_eq!(capture.stderr, b"wc: 'nope'$'\\377': No such file or directory\n");
+        assert_eq!(capture.code, Some(1));
+    }
+
+    /// Sort by name, which compares the raw bytes of the names:
+    /// ```
+    /// :~$ wc -l --sort=name $'\xe9' b a
+    /// ```
+    /// Output from wc looks like this, with `\xe9` last, since it's the biggest byte:
+    /// ```
+    /// 1 a
+    /// 1 b
+    /// 1 ?
+    /// 3 total
+    /// ```
+    #[test]
+    fn sort_by_raw_name() {
+        let names = [OsStr::from_bytes(b"\xe9"), OsStr::new("b"), OsStr::new("a")];
+        let scene = names.iter().fold(Scene::new(), |scene, name| scene.file(name, "x\n"));
+
+        let capture = UCmd::new("wc").args(names).args(["-l", "--sort=name"]).in_scene(&scene).run();
+        assert_eq!(capture.stdout, b"1 a\n1 b\n1 \xe9\n3 total\n");
+        assert_eq!(capture.stderr, b"");
+        assert_eq!(capture.code, Some(0));
+    }
+}
@@ -0,0 +1,67 @@
+/// Test writing to pipes that are already closed, and to full devices.
+mod test_utils;
+
+#[cfg(all(test, unix))]
+mod test {
+    use std::fs::OpenOptions;
+    use std::process::Stdio;
+
+    use crate::test_utils as tu;
+
+    /// Helper method to run wc with `args` and `stdout`, and get its exit code and
+    /// standard error.
+    fn run(args: &[&str], stdout: Stdio) -> (Option<i32>, String) {
+        let output = tu::get_cmd()
+            .args(args)
+            .stdout(stdout)
+            .stderr(Stdio::piped())
+            .output()
+            .unwrap();
+        (output.status.code(), String::from_utf8(output.stderr).unwrap())
+    }
+
+    /// Writing to a pipe that nothing reads from anymore should stop quietly:
+    /// ```
+    /// :~$ wc tests/test_files/trees.txt tests/test_files/jack.txt | true
+    /// ```
+    #[test]
+    fn closed_pipe() {
+        let (reader, writer) = std::io::pipe().unwrap();
+        drop(reader);
+
+        let args = ["tests/test_files/trees.txt", "tests/test_files/jack.txt"];
+        assert_eq!(run(&args, Stdio::from(writer)), (Some(0), String::new()));
+    }
+
+    /// An error for a missing file still fails, even if the pipe is closed:
+    /// ```
+    /// :~$ wc tests/test_files/trees.txt tests/test_files/does_not_exist.txt | true
+    /// ```
+    #[test]
+    fn closed_pipe_after_error() {
+        let (reader, writer) = std::io::pipe().unwrap();
+        drop(reader);
+
+        let args = ["tests/test_files/trees.txt", "tests/test_files/does_not_exist.txt"];
+        let (code, stderr) = run(&args, Stdio::from(writer));
+        assert_eq!(code, Some(1));
+        assert_eq!(stderr, "wc: tests/test_files/does_not_exist.txt: No such file or directory\n");
+    }
+
+    /// Writing to a device with no space left is an error:
+    /// ```
+    /// :~$ wc tests/test_files/trees.txt > /dev/full
+    /// ```
+    /// Output from wc looks like this:
+    /// ```
+    /// wc: write error: No space left on device
+    /// ```
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn full_device() {
+        let full = OpenOptions::new().write(true).open("/dev/full").unwrap();
+        let (code, stderr) = run(&["tests/test_files/trees.txt"], Stdio::from(full));
+        assert_eq!(code, Some(1));
+        assert_eq!(stderr, "wc: write error: No space left on device\n");
+    }
+}
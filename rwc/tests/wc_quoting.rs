@@ -0,0 +1,76 @@
+/// Test quoting file names in rows and error messages.
+mod test_utils;
+
+#[cfg(test)]
+mod test {
+    use assert_cmd::prelude::*;
+    use predicates::prelude::*;
+
+    use crate::test_utils as tu;
+
+    /// Run wc with file names that need quotes, and don't exist, like this:
+    /// ```
+    /// :~$ wc 'no such' $'no\nsuch' "isn't" a:b plain
+    /// ```
+    /// Output from wc looks like this:
+    /// ```
+    /// wc: 'no such': No such file or directory
+    /// wc: 'no'$'\n''such': No such file or directory
+    /// wc: "isn't": No such file or directory
+    /// wc: 'a:b': No such file or directory
+    /// wc: plain: No such file or directory
+    /// 0 0 0 total
+    /// ```
+    /// The exit code should be 1.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn quoted_errors() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = concat!(
+            "wc: 'no such': No such file or directory\n",
+            "wc: 'no'$'\\n''such': No such file or directory\n",
+            "wc: \"isn't\": No such file or directory\n",
+            "wc: 'a:b': No such file or directory\n",
+            "wc: plain: No such file or directory\n");
+
+        let mut cmd = tu::get_cmd();
+        cmd.current_dir("tests/test_files")
+            .args(["no such", "no\nsuch", "isn't", "a:b", "plain"])
+            .assert()
+            .failure()
+            .stderr(predicate::eq(expected))
+            .code(predicate::eq(1));
+
+        Ok(())
+    }
+
+    /// Count a file with a newline in its name, and one with a tab:
+    /// ```
+    /// :~$ wc $'new\nline' $'t\tab'
+    /// ```
+    /// Output from wc looks like this, with a real tab. Only the name with a newline
+    /// is quoted, since it would look like two rows otherwise:
+    /// ```
+    /// 1 1 2 'new'$'\n''line'
+    /// 1 1 2 t<tab>ab
+    /// 2 2 4 total
+    /// ```
+    #[cfg(unix)]
+    #[test]
+    fn quoted_labels() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = std::path::PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("wc_quoted_labels");
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(dir.join("new\nline"), "x\n")?;
+        std::fs::write(dir.join("t\tab"), "x\n")?;
+
+        let mut cmd = tu::get_cmd();
+        cmd.current_dir(&dir)
+            .args(["new\nline", "t\tab"])
+            .assert()
+            .success()
+            .stdout(predicate::eq("1 1 2 'new'$'\\n''line'\n1 1 2 t\tab\n2 2 4 total\n"))
+            .code(predicate::eq(0));
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,111 @@
+/// Test sorting and filtering rows with --sort, --top, and the --min-*/--max-* switches.
+mod test_utils;
+
+#[cfg(test)]
+mod test {
+    use assert_cmd::prelude::*;
+    use predicates::prelude::*;
+
+    use crate::test_utils as tu;
+
+    /// Sort three small files by line count, largest first:
+    /// ```
+    /// :~$ wc -l --sort=lines,reverse tests/test_files/so_tired_blues.txt tests/test_files/trees.txt tests/test_files/fire_and_ice.txt
+    /// ```
+    /// Output should look like this, with the total still at the end:
+    /// ```
+    ///  21 tests/test_files/trees.txt
+    ///  13 tests/test_files/fire_and_ice.txt
+    ///   9 tests/test_files/so_tired_blues.txt
+    ///  43 total
+    /// ```
+    #[test]
+    fn sort_lines_reverse() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = concat!(
+            " 21 tests/test_files/trees.txt\n",
+            " 13 tests/test_files/fire_and_ice.txt\n",
+            "  9 tests/test_files/so_tired_blues.txt\n",
+            " 43 total\n");
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("-l")
+            .arg("--sort=lines,reverse")
+            .arg("tests/test_files/so_tired_blues.txt")
+            .arg("tests/test_files/trees.txt")
+            .arg("tests/test_files/fire_and_ice.txt")
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Hide files with fewer than 10 lines, sort by bytes, and only show the first row:
+    /// ```
+    /// :~$ wc --min-lines 10 --sort=bytes --top 1 tests/test_files/trees.txt tests/test_files/so_tired_blues.txt tests/test_files/fire_and_ice.txt
+    /// ```
+    /// Output should look like this. The total still counts all three files:
+    /// ```
+    ///  13  56 272 tests/test_files/fire_and_ice.txt
+    ///  43 165 818 total
+    /// ```
+    #[test]
+    fn filter_sort_and_top() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = concat!(
+            " 13  56 272 tests/test_files/fire_and_ice.txt\n",
+            " 43 165 818 total\n");
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("--min-lines").arg("10")
+            .arg("--sort=bytes")
+            .arg("--top").arg("1")
+            .arg("tests/test_files/trees.txt")
+            .arg("tests/test_files/so_tired_blues.txt")
+            .arg("tests/test_files/fire_and_ice.txt")
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Sort by name with a missing file in the middle. The error should still go to
+    /// stderr, and stdout should only have the sorted rows and the total:
+    /// ```
+    /// :~$ wc -c --sort=name tests/test_files/trees.txt tests/test_files/does_not_exist.txt tests/test_files/jack.txt
+    /// ```
+    #[test]
+    fn sort_name_with_error() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = concat!(
+            "1056 tests/test_files/jack.txt\n",
+            " 415 tests/test_files/trees.txt\n",
+            "1471 total\n");
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("-c")
+            .arg("--sort=name")
+            .arg("tests/test_files/trees.txt")
+            .arg("tests/test_files/does_not_exist.txt")
+            .arg("tests/test_files/jack.txt")
+            .assert()
+            .stdout(predicate::eq(expected))
+            .stderr(predicate::str::contains("tests/test_files/does_not_exist.txt"));
+
+        Ok(())
+    }
+
+    /// An unknown sort key should be rejected by the argument parser.
+    #[test]
+    fn sort_bad_key() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = tu::get_cmd();
+        cmd.arg("--sort=size")
+            .arg("tests/test_files/trees.txt")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("invalid sort key 'size'"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+/// Test reading standard input, with no operands or through the `-` operand.
+mod test_utils;
+
+#[cfg(test)]
+mod test {
+    use predicates::prelude::*;
+
+    use crate::test_utils as tu;
+
+    /// Count standard input when there are no operands. The row has no label:
+    /// ```
+    /// :~$ cat tests/test_files/trees.txt | wc
+    /// ```
+    /// Output from wc looks like this:
+    /// ```
+    ///  21  83 415
+    /// ```
+    #[test]
+    fn read_stdin_no_operands() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = assert_cmd::Command::from_std(tu::get_cmd());
+        cmd.write_stdin(std::fs::read("tests/test_files/trees.txt")?)
+            .assert()
+            .success()
+            .stdout(predicate::eq(" 21  83 415\n"))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Count standard input:
+    /// ```
+    /// :~$ cat tests/test_files/trees.txt | wc -
+    /// ```
+    /// Output from wc looks like this:
+    /// ```
+    ///  21  83 415 -
+    /// ```
+    #[test]
+    fn read_stdin() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = assert_cmd::Command::from_std(tu::get_cmd());
+        cmd.arg("-")
+            .write_stdin(std::fs::read("tests/test_files/trees.txt")?)
+            .assert()
+            .success()
+            .stdout(predicate::eq(" 21  83 415 -\n"))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Count standard input between two files. The second `-` gets nothing,
+    /// because standard input was already read to the end:
+    /// ```
+    /// :~$ cat tests/test_files/trees.txt | wc tests/test_files/so_tired_blues.txt - -
+    /// ```
+    /// Output from wc looks like this:
+    /// ```
+    ///   9  26 131 tests/test_files/so_tired_blues.txt
+    ///  21  83 415 -
+    ///   0   0   0 -
+    ///  30 109 546 total
+    /// ```
+    #[test]
+    fn read_files_and_stdin() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = concat!(
+            "  9  26 131 tests/test_files/so_tired_blues.txt\n",
+            " 21  83 415 -\n",
+            "  0   0   0 -\n",
+            " 30 109 546 total\n");
+
+        let mut cmd = assert_cmd::Command::from_std(tu::get_cmd());
+        cmd.arg("tests/test_files/so_tired_blues.txt")
+            .arg("-")
+            .arg("-")
+            .write_stdin(std::fs::read("tests/test_files/trees.txt")?)
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+}
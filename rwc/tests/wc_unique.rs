@@ -0,0 +1,90 @@
+/// Test counting files that are given more than once using the --unique switch.
+mod test_utils;
+
+#[cfg(test)]
+mod test {
+    use assert_cmd::prelude::*;
+    use harness::{Scene, UCmd};
+    use predicates::prelude::*;
+
+    use crate::test_utils as tu;
+
+    /// Give the same file twice, through two different relative paths:
+    /// ```
+    /// :~$ wc --unique tests/test_files/trees.txt tests/../tests/test_files/trees.txt tests/test_files/fire_and_ice.txt
+    /// ```
+    /// Every operand is listed, but the second trees.txt is left out of the total:
+    /// ```
+    ///  21  83 415 tests/test_files/trees.txt
+    ///  21  83 415 tests/../tests/test_files/trees.txt (duplicate of tests/test_files/trees.txt)
+    ///  13  56 272 tests/test_files/fire_and_ice.txt
+    ///  34 139 687 total
+    /// ```
+    #[test]
+    fn unique_relative_paths() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = concat!(
+            " 21  83 415 tests/test_files/trees.txt\n",
+            " 21  83 415 tests/../tests/test_files/trees.txt (duplicate of tests/test_files/trees.txt)\n",
+            " 13  56 272 tests/test_files/fire_and_ice.txt\n",
+            " 34 139 687 total\n");
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("--unique")
+            .arg("tests/test_files/trees.txt")
+            .arg("tests/../tests/test_files/trees.txt")
+            .arg("tests/test_files/fire_and_ice.txt")
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected))
+            .stderr(predicate::eq("wc: 1 duplicate file(s) left out of the total\n"))
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Without --unique, the same file is counted twice, like the standard wc does.
+    #[test]
+    fn no_unique_counts_twice() -> Result<(), Box<dyn std::error::Error>> {
+        let expected = concat!(
+            " 21  83 415 tests/test_files/trees.txt\n",
+            " 21  83 415 tests/../tests/test_files/trees.txt\n",
+            " 42 166 830 total\n");
+
+        let mut cmd = tu::get_cmd();
+        cmd.arg("tests/test_files/trees.txt")
+            .arg("tests/../tests/test_files/trees.txt")
+            .assert()
+            .success()
+            .stdout(predicate::eq(expected))
+            .stderr(predicate::str::is_empty())
+            .code(predicate::eq(0));
+
+        Ok(())
+    }
+
+    /// Give the same file through a symlink to it:
+    /// ```
+    /// :~$ ln -s trees.txt link_to_trees.txt
+    /// :~$ wc -l --unique trees.txt link_to_trees.txt
+    /// ```
+    /// Output from wc looks like this:
+    /// ```
+    ///  21 trees.txt
+    ///  21 link_to_trees.txt (duplicate of trees.txt)
+    ///  21 total
+    /// wc: 1 duplicate file(s) left out of the total
+    /// ```
+    #[cfg(unix)]
+    #[test]
+    fn unique_symlink() {
+        let scene = Scene::new()
+            .file_from("trees.txt", "tests/test_files/trees.txt")
+            .symlink("link_to_trees.txt", "trees.txt");
+
+        let capture = UCmd::new("wc").args(["-l", "--unique", "trees.txt", "link_to_trees.txt"]).in_scene(&scene).run();
+        assert_eq!(String::from_utf8_lossy(&capture.stdout),
+            " 21 trees.txt\n 21 link_to_trees.txt (duplicate of trees.txt)\n 21 total\n");
+        assert_eq!(String::from_utf8_lossy(&capture.stderr), "wc: 1 duplicate file(s) left out of the total\n");
+        assert_eq!(capture.code, Some(0));
+    }
+}
@@ -0,0 +1,72 @@
+//! Showing operands and file names, which don't have to be UTF-8.
+//!
+//! On Unix a file name is any string of bytes, and the standard utilities write it
+//! back out exactly as it was given, so `wc` on a Latin-1 name shows the same
+//! Latin-1 bytes. These helpers do that. Elsewhere, names are always Unicode, and
+//! are written as UTF-8.
+//!
+//! For names in diagnostics, use `quote::quote_name()` instead, which also makes
+//! bytes that aren't UTF-8 visible.
+
+use std::borrow::Cow;
+use std::ffi::OsStr;
+use std::io::{self, Write};
+
+/// The bytes to write out for `name`.
+///
+/// # Arguments
+///
+/// * `name` - An operand or file name.
+#[cfg(unix)]
+pub fn os_bytes(name: &OsStr) -> Cow<'_, [u8]> {
+    use std::os::unix::ffi::OsStrExt;
+    Cow::Borrowed(name.as_bytes())
+}
+
+/// The bytes to write out for `name`.
+///
+/// # Arguments
+///
+/// * `name` - An operand or file name.
+#[cfg(not(unix))]
+pub fn os_bytes(name: &OsStr) -> Cow<'_, [u8]> {
+    match name.to_string_lossy() {
+        Cow::Borrowed(text) => Cow::Borrowed(text.as_bytes()),
+        Cow::Owned(text) => Cow::Owned(text.into_bytes()),
+    }
+}
+
+/// Write `name` to `out`, as it is.
+///
+/// # Arguments
+///
+/// * `out` - Where to write it.
+/// * `name` - An operand or file name.
+pub fn write_os(out: &mut impl Write, name: &OsStr) -> io::Result<()> {
+    out.write_all(&os_bytes(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utf8_name() {
+        assert_eq!(os_bytes(OsStr::new("día.txt")), "día.txt".as_bytes());
+        let mut out = Vec::new();
+        write_os(&mut out, OsStr::new("a b")).unwrap();
+        assert_eq!(out, b"a b");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn name_not_utf8() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let name = OsStr::from_bytes(b"caf\xe9.txt");
+        assert_eq!(os_bytes(name), &b"caf\xe9.txt"[..]);
+        let mut out = b"1 ".to_vec();
+        write_os(&mut out, name).unwrap();
+        assert_eq!(out, b"1 caf\xe9.txt");
+    }
+}
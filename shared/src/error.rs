@@ -0,0 +1,252 @@
+//! Reporting errors the way the standard utilities do.
+//!
+//! Diagnostics look like `util: context: message`, for example
+//! `wc: notes.txt: No such file or directory`. The message for a system error is
+//! the text `strerror` gives, without the ` (os error 2)` that Rust adds. File names
+//! are quoted if they need it, like `wc: 'a b': No such file or directory`.
+//!
+//! An error doesn't have to stop a utility. Most of them report it, go on to the
+//! next operand, and exit with a failure status at the end. `ExitStatus` keeps
+//! track of that.
+//!
+//! Exit codes are 0 for success and 1 for failure, including a bad command line.
+//! Tools that run another program (like `env`, `nice`, and `timeout`) use 125 for
+//! their own errors, 126 when the program can't be run, and 127 when it can't be found.
+
+use std::ffi::OsString;
+use std::fmt;
+use std::io::{self, ErrorKind, Write};
+use std::process::ExitCode;
+
+use crate::help;
+use crate::input::InputError;
+use crate::quote::quote_name;
+
+/// Everything worked.
+pub const EXIT_SUCCESS: i32 = 0;
+/// Something went wrong.
+pub const EXIT_FAILURE: i32 = 1;
+/// A tool that runs another program failed itself, before running it.
+pub const EXIT_CANCELED: i32 = 125;
+/// The program was found, but couldn't be run.
+pub const EXIT_CANNOT_INVOKE: i32 = 126;
+/// The program couldn't be found.
+pub const EXIT_ENOENT: i32 = 127;
+
+/// An error to show to the user, with the exit code it should cause.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UError {
+    /// What the error is about, usually a file name.
+    context: Option<String>,
+    message: String,
+    code: i32,
+}
+
+impl UError {
+    /// Make an error with just a message, and the exit code `EXIT_FAILURE`.
+    pub fn new(message: impl Into<String>) -> UError {
+        UError { context: None, message: message.into(), code: EXIT_FAILURE }
+    }
+
+    /// Make an error from a system error, about `context`.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - What the error is about, usually a file name.
+    /// * `e` - The system error.
+    pub fn io(context: &str, e: &io::Error) -> UError {
+        UError::new(strerror(e)).with_context(context)
+    }
+
+    /// Say what the error is about. It's shown in front of the message.
+    pub fn with_context(mut self, context: impl Into<String>) -> UError {
+        self.context = Some(context.into());
+        self
+    }
+
+    /// Change the exit code the error causes.
+    pub fn with_code(mut self, code: i32) -> UError {
+        self.code = code;
+        self
+    }
+
+    /// Exit code the error causes.
+    pub fn code(&self) -> i32 {
+        self.code
+    }
+}
+
+impl fmt::Display for UError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.context {
+            Some(context) => write!(f, "{}: {}", context, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for UError {}
+
+impl From<io::Error> for UError {
+    fn from(e: io::Error) -> UError {
+        UError::new(strerror(&e))
+    }
+}
+
+impl From<InputError> for UError {
+    fn from(e: InputError) -> UError {
+        UError::io(&quote_name(&e.operand), &e.error)
+    }
+}
+
+/// Text for a system error, like `strerror` gives: `No such file or directory`,
+/// without the ` (os error 2)` that Rust puts at the end.
+pub fn strerror(e: &io::Error) -> String {
+    let text = e.to_string();
+    match text.rfind(" (os error ") {
+        Some(i) if e.raw_os_error().is_some() && text.ends_with(')') => text[..i].to_owned(),
+        _ => text,
+    }
+}
+
+/// Exit code for a tool whose program couldn't be started: `EXIT_ENOENT` if it
+/// wasn't found, or `EXIT_CANNOT_INVOKE` for anything else.
+pub fn exec_error_code(e: &io::Error) -> i32 {
+    match e.kind() {
+        ErrorKind::NotFound => EXIT_ENOENT,
+        _ => EXIT_CANNOT_INVOKE,
+    }
+}
+
+/// Exit status of a utility, kept from one operand to the next. Once something
+/// fails, the status stays failed.
+#[derive(Debug)]
+pub struct ExitStatus {
+    /// Name to show at the start of each diagnostic.
+    util_name: String,
+    code: i32,
+}
+
+impl ExitStatus {
+    /// Start with success.
+    ///
+    /// # Arguments
+    ///
+    /// * `util_name` - Name of the utility, like `wc`.
+    pub fn new(util_name: &str) -> ExitStatus {
+        ExitStatus { util_name: util_name.to_owned(), code: EXIT_SUCCESS }
+    }
+
+    /// Show `e` on standard error as `util: context: message`, and fail with its code.
+    pub fn report(&mut self, e: &UError) {
+        self.show(e);
+        self.fail(e.code());
+    }
+
+    /// Show `message` on standard error as `util: message`, without failing. It's for
+    /// things the user should know about that aren't errors.
+    pub fn note(&self, message: impl fmt::Display) {
+        self.show(message);
+    }
+
+    /// Write a diagnostic to standard error. If that fails, like when it's closed,
+    /// there's nowhere left to say so.
+    fn show(&self, message: impl fmt::Display) {
+        let _ = writeln!(io::stderr().lock(), "{}: {}", self.util_name, message);
+    }
+
+    /// Fail with `code`, without showing anything. If the status already failed,
+    /// the first code is kept.
+    pub fn fail(&mut self, code: i32) {
+        if self.code == EXIT_SUCCESS {
+            self.code = code;
+        }
+    }
+
+    /// The exit code so far.
+    pub fn code(&self) -> i32 {
+        self.code
+    }
+
+    /// Check whether nothing has failed.
+    pub fn is_success(&self) -> bool {
+        self.code == EXIT_SUCCESS
+    }
+}
+
+impl From<ExitStatus> for ExitCode {
+    fn from(status: ExitStatus) -> ExitCode {
+        ExitCode::from(u8::try_from(status.code).unwrap_or(u8::MAX))
+    }
+}
+
+/// Parse the command line with `clap`. If it's wrong, show clap's message and exit
+/// with `usage_code`, instead of the 2 that clap uses. `--help` and `--version`
+/// exit with success, and `--version` looks like GNU's (see `help::version_text`).
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments, starting with the program name.
+/// * `usage_code` - Exit code for a bad command line: `EXIT_FAILURE` for most
+///   utilities, or `EXIT_CANCELED` for ones that run another program.
+pub fn parse_args<P, I, T>(args: I, usage_code: i32) -> P
+where
+    P: clap::Parser,
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    P::try_parse_from(args).unwrap_or_else(|e| {
+        if e.kind() == clap::error::ErrorKind::DisplayVersion {
+            let command = P::command();
+            let authors = command.get_author().map(help::split_authors).unwrap_or_default();
+            let text = help::version_text(command.get_name(), command.get_version().unwrap_or(""), &authors);
+            let _ = io::stdout().write_all(text.as_bytes());
+            std::process::exit(EXIT_SUCCESS)
+        }
+        let code = if e.use_stderr() { usage_code } else { EXIT_SUCCESS };
+        // there's nowhere left to report a failure to print the message
+        let _ = e.print();
+        std::process::exit(code)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strerror_without_os_error() {
+        assert_eq!(strerror(&io::Error::from_raw_os_error(2)), "No such file or directory");
+        assert_eq!(strerror(&io::Error::other("plain (os error 2)")), "plain (os error 2)");
+    }
+
+    #[test]
+    fn format_with_context() {
+        let e = UError::io("notes.txt", &io::Error::from_raw_os_error(21));
+        assert_eq!(e.to_string(), "notes.txt: Is a directory");
+        assert_eq!(e.code(), EXIT_FAILURE);
+        assert_eq!(UError::new("missing operand").to_string(), "missing operand");
+
+        let input = InputError::new("-", io::Error::from_raw_os_error(9));
+        assert_eq!(UError::from(input).to_string(), "-: Bad file descriptor");
+        let input = InputError::new("a b", io::Error::from_raw_os_error(2));
+        assert_eq!(UError::from(input).to_string(), "'a b': No such file or directory");
+    }
+
+    #[test]
+    fn exit_status_sticks() {
+        let mut status = ExitStatus::new("test");
+        assert!(status.is_success());
+        status.fail(EXIT_CANCELED);
+        status.fail(EXIT_FAILURE);
+        status.fail(EXIT_SUCCESS);
+        assert_eq!(status.code(), EXIT_CANCELED);
+        assert!(!status.is_success());
+    }
+
+    #[test]
+    fn exec_codes() {
+        assert_eq!(exec_error_code(&io::Error::from(ErrorKind::NotFound)), EXIT_ENOENT);
+        assert_eq!(exec_error_code(&io::Error::from(ErrorKind::PermissionDenied)), EXIT_CANNOT_INVOKE);
+    }
+}
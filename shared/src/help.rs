@@ -0,0 +1,131 @@
+//! `--version` text in the shape GNU utilities use.
+//!
+//! `--version` is the program name and version on the first line, then who wrote it:
+//!
+//! ```text
+//! wc 0.1.0
+//! Written by Paul Rubin and David MacKenzie.
+//! ```
+//!
+//! Scripts look at the first line to tell which `wc` they have, so it must not say
+//! `GNU coreutils`. The project doesn't have a package name, copyright or license
+//! of its own yet, so there are no lines for them.
+//!
+//! `error::parse_args` prints it in place of `clap`'s.
+
+/// Most authors to name before saying "and others".
+const MAX_AUTHORS: usize = 9;
+
+/// Make the text `--version` shows.
+///
+/// # Arguments
+///
+/// * `name` - Name of the utility, like `wc`.
+/// * `version` - Its version.
+/// * `authors` - Who wrote it. An email address after a name, like `A <a@example.com>`,
+///   is left out, and so are authors that are only an email address. There's no
+///   "Written by" line if no names are left.
+pub fn version_text(name: &str, version: &str, authors: &[&str]) -> String {
+    let mut text = format!("{} {}\n", name, version);
+    let names: Vec<&str> = authors.iter().filter_map(|a| author_name(a)).collect();
+    if !names.is_empty() {
+        text.push_str(&written_by(&names));
+    }
+    text
+}
+
+/// Get the name of an author, without the email address after it. `None` if
+/// there's no name, only an address.
+///
+/// # Arguments
+///
+/// * `author` - The author, like `A <a@example.com>`.
+fn author_name(author: &str) -> Option<&str> {
+    let name = author.split('<').next().unwrap_or(author).trim();
+    (!name.is_empty() && !name.contains('@')).then_some(name)
+}
+
+/// Make the "Written by" line, wrapped and punctuated like GNU's `version_etc`:
+/// `A and B.`, `A, B, and C.`, with a line break after the third and seventh names,
+/// and "and others." after nine.
+///
+/// # Arguments
+///
+/// * `authors` - The names of who wrote the utility. Not empty.
+fn written_by(authors: &[&str]) -> String {
+    let mut names: Vec<&str> = authors.iter().copied().take(MAX_AUTHORS).collect();
+    if authors.len() > MAX_AUTHORS {
+        names.push("others");
+    }
+
+    let count = names.len();
+    let mut line = String::from("Written by ");
+    for (i, name) in names.iter().enumerate() {
+        if i + 1 == count && count > 1 {
+            line.push_str("and ");
+        }
+        line.push_str(name);
+        if i + 1 == count {
+            line.push_str(".\n");
+        } else if count == 2 {
+            line.push(' ');
+        } else if i == 2 || i == 6 {
+            line.push_str(",\n");
+        } else {
+            line.push_str(", ");
+        }
+    }
+    line
+}
+
+/// Split the authors `clap` has for a program, which are `Cargo.toml`'s authors
+/// joined with `, `.
+///
+/// # Arguments
+///
+/// * `authors` - The authors as `clap` has them.
+pub fn split_authors(authors: &str) -> Vec<&str> {
+    authors.split([',', ':']).map(str::trim).filter(|a| !a.is_empty()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version() {
+        assert_eq!(version_text("wc", "0.1.0", &["Paul Rubin", "David MacKenzie"]),
+            "wc 0.1.0\nWritten by Paul Rubin and David MacKenzie.\n");
+        assert_eq!(version_text("true", "1.0", &[]), "true 1.0\n");
+        assert_eq!(version_text("cat", "1.0", &["A <a@example.com>"]), "cat 1.0\nWritten by A.\n");
+    }
+
+    #[test]
+    fn address_only_authors() {
+        assert_eq!(author_name("A <a@example.com>"), Some("A"));
+        assert_eq!(author_name("someone@example.com"), None);
+        assert_eq!(author_name("<someone@example.com>"), None);
+        assert_eq!(version_text("wc", "1.0", &["someone@example.com"]), version_text("wc", "1.0", &[]));
+        assert_eq!(version_text("wc", "1.0", &["x@example.com", "B"]), "wc 1.0\nWritten by B.\n");
+    }
+
+    #[test]
+    fn many_authors() {
+        let names = ["A", "B", "C", "D", "E", "F", "G", "H", "I", "J"];
+        let written = |n: usize| written_by(&names[..n]);
+        assert_eq!(written(1), "Written by A.\n");
+        assert_eq!(written(3), "Written by A, B, and C.\n");
+        assert_eq!(written(4), "Written by A, B, C,\nand D.\n");
+        assert_eq!(written(7), "Written by A, B, C,\nD, E, F, and G.\n");
+        assert_eq!(written(8), "Written by A, B, C,\nD, E, F, G,\nand H.\n");
+        assert_eq!(written(9), "Written by A, B, C,\nD, E, F, G,\nH, and I.\n");
+        assert_eq!(written(10), "Written by A, B, C,\nD, E, F, G,\nH, I, and others.\n");
+    }
+
+    #[test]
+    fn authors_from_cargo() {
+        assert_eq!(split_authors("A <a@example.com>, B"), ["A <a@example.com>", "B"]);
+        assert_eq!(split_authors("A:B"), ["A", "B"]);
+        assert!(split_authors("").is_empty());
+    }
+}
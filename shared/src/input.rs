@@ -0,0 +1,314 @@
+//! Reading the files named on the command line.
+//!
+//! Most of the utilities take a list of files, where `-` means standard input, and
+//! standard input is read when the list is empty. `Operands` turns that list into
+//! `Input`s, one at a time, in order. A file that can't be opened gives an
+//! `InputError` instead, so the utility can report it and go on to the next one.
+//!
+//! Each `Input` is a buffered reader with the label to show for it. Operands are
+//! `OsString`s, since file names don't have to be UTF-8. Use the `display` module
+//! to show them.
+
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, StdinLock};
+use std::path::Path;
+
+/// Size of the buffer each input is read through.
+pub const BUFFER_SIZE: usize = 64 * 1024;
+
+/// One operand, opened for reading.
+pub struct Input {
+    /// The operand as it was given, or `None` if standard input is read because
+    /// there were no operands.
+    operand: Option<OsString>,
+    reader: Reader,
+}
+
+/// Where an `Input` reads from.
+enum Reader {
+    Stdin(StdinLock<'static>),
+    File(BufReader<File>),
+}
+
+impl Input {
+    /// Open one operand. `-` is standard input, anything else is a file name.
+    ///
+    /// # Arguments
+    ///
+    /// * `operand` - The operand, from the command line.
+    pub fn open(operand: impl AsRef<OsStr>) -> Result<Input, InputError> {
+        let operand = operand.as_ref();
+        let reader = if operand == "-" {
+            Reader::Stdin(io::stdin().lock())
+        } else {
+            let file = File::open(operand).map_err(|e| InputError::new(operand, e))?;
+            Reader::File(BufReader::with_capacity(BUFFER_SIZE, file))
+        };
+        Ok(Input { operand: Some(operand.to_owned()), reader })
+    }
+
+    /// Standard input, read because there were no operands.
+    pub fn stdin() -> Input {
+        Input { operand: None, reader: Reader::Stdin(io::stdin().lock()) }
+    }
+
+    /// Label to show for this input: the operand as it was given, or `-` for
+    /// standard input when there were no operands.
+    pub fn label(&self) -> &OsStr {
+        self.operand.as_deref().unwrap_or(OsStr::new("-"))
+    }
+
+    /// The operand as it was given, or `None` if there were no operands. The
+    /// standard utilities leave the label out altogether in that case.
+    pub fn operand(&self) -> Option<&OsStr> {
+        self.operand.as_deref()
+    }
+
+    /// Check whether this is standard input.
+    pub fn is_stdin(&self) -> bool {
+        matches!(self.reader, Reader::Stdin(_))
+    }
+
+    /// Path of the file, or `None` for standard input.
+    pub fn path(&self) -> Option<&Path> {
+        match self.reader {
+            Reader::File(_) => self.operand.as_deref().map(Path::new),
+            Reader::Stdin(_) => None,
+        }
+    }
+
+    /// The open file, or `None` for standard input. Reading from it directly skips
+    /// anything already in the buffer, so only do that before reading from the `Input`.
+    pub fn file(&self) -> Option<&File> {
+        match &self.reader {
+            Reader::File(file) => Some(file.get_ref()),
+            Reader::Stdin(_) => None,
+        }
+    }
+
+    /// Wrap an error from reading this input, so it has the label with it.
+    pub fn error(&self, error: io::Error) -> InputError {
+        InputError::new(self.label(), error)
+    }
+}
+
+impl fmt::Debug for Input {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Input")
+            .field("operand", &self.operand)
+            .field("stdin", &self.is_stdin())
+            .finish()
+    }
+}
+
+impl Read for Input {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match &mut self.reader {
+            Reader::Stdin(stdin) => stdin.read(buf),
+            Reader::File(file) => file.read(buf),
+        }
+    }
+}
+
+impl BufRead for Input {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        match &mut self.reader {
+            Reader::Stdin(stdin) => stdin.fill_buf(),
+            Reader::File(file) => file.fill_buf(),
+        }
+    }
+
+    fn consume(&mut self, amt: usize) {
+        match &mut self.reader {
+            Reader::Stdin(stdin) => stdin.consume(amt),
+            Reader::File(file) => file.consume(amt),
+        }
+    }
+}
+
+/// An operand that couldn't be opened or read.
+#[derive(Debug)]
+pub struct InputError {
+    /// The operand, as it was given.
+    pub operand: OsString,
+    /// What went wrong.
+    pub error: io::Error,
+}
+
+impl InputError {
+    pub fn new(operand: impl AsRef<OsStr>, error: io::Error) -> InputError {
+        InputError { operand: operand.as_ref().to_owned(), error }
+    }
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.operand.to_string_lossy(), self.error)
+    }
+}
+
+impl std::error::Error for InputError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Iterator over the operands from the command line, opening each one in turn.
+///
+/// With no operands, it gives standard input once. Files are opened as the
+/// iterator gets to them, so only one is open at a time.
+pub struct Operands<I> {
+    operands: I,
+    /// Whether anything was asked for yet. Used to tell that there were no operands.
+    started: bool,
+    /// Whether standard input was given already.
+    stdin_used: bool,
+    /// Whether giving standard input a second time is an error.
+    stdin_once: bool,
+}
+
+impl<I> Operands<I>
+where
+    I: Iterator,
+    I::Item: Into<OsString>,
+{
+    /// Make an iterator over `operands`.
+    ///
+    /// # Arguments
+    ///
+    /// * `operands` - File names from the command line, as `OsString`s or `String`s.
+    ///   `-` means standard input.
+    pub fn new<T: IntoIterator<IntoIter = I>>(operands: T) -> Operands<I> {
+        Operands { operands: operands.into_iter(), started: false, stdin_used: false, stdin_once: false }
+    }
+
+    /// Only let standard input be read once. After that, `-` gives an error. For
+    /// utilities that need all of an input, where a second read would just be empty.
+    pub fn stdin_once(mut self) -> Operands<I> {
+        self.stdin_once = true;
+        self
+    }
+}
+
+impl<I> Iterator for Operands<I>
+where
+    I: Iterator,
+    I::Item: Into<OsString>,
+{
+    type Item = Result<Input, InputError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first = !self.started;
+        self.started = true;
+        let Some(operand) = self.operands.next().map(Into::into) else {
+            if first {
+                self.stdin_used = true;
+                return Some(Ok(Input::stdin()));
+            }
+            return None;
+        };
+
+        if operand == "-" {
+            if self.stdin_once && self.stdin_used {
+                return Some(Err(InputError::new(&operand, io::Error::other(
+                    "standard input was already read"))));
+            }
+            self.stdin_used = true;
+        }
+        Some(Input::open(&operand))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use harness::Scene;
+    use std::io::ErrorKind;
+    use std::path::PathBuf;
+
+    #[test]
+    fn files_in_order_with_errors() {
+        let scene = Scene::new().file("first", b"one\ntwo\n").file("second", b"three\n");
+        let [first, missing, second] = ["first", "missing", "second"].map(|name| scene.join(name).into_os_string());
+        let operands = Operands::new(vec![first.clone(), missing.clone(), second.clone()]);
+
+        let results: Vec<_> = operands.map(|input| {
+            input.and_then(|mut input| {
+                let mut text = String::new();
+                input.read_to_string(&mut text).map_err(|e| input.error(e))?;
+                Ok((input.label().to_owned(), input.path().is_some(), text))
+            })
+        }).collect();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap(), &(first, true, "one\ntwo\n".to_owned()));
+        let error = results[1].as_ref().unwrap_err();
+        assert_eq!(error.operand, missing);
+        assert_eq!(error.error.kind(), ErrorKind::NotFound);
+        assert_eq!(results[2].as_ref().unwrap(), &(second, true, "three\n".to_owned()));
+    }
+
+    #[test]
+    fn lines_from_a_file() {
+        let scene = Scene::new().file("lines", b"a\nb");
+        let input = Input::open(scene.join("lines")).unwrap();
+        let lines: Vec<String> = input.lines().map(Result::unwrap).collect();
+        assert_eq!(lines, ["a", "b"]);
+    }
+
+    #[test]
+    fn stdin_with_no_operands() {
+        let inputs: Vec<_> = Operands::new(Vec::<OsString>::new()).collect();
+        assert_eq!(inputs.len(), 1);
+        let input = inputs[0].as_ref().unwrap();
+        assert!(input.is_stdin());
+        assert_eq!(input.label(), "-");
+        assert_eq!(input.operand(), None);
+        assert!(input.path().is_none() && input.file().is_none());
+    }
+
+    #[test]
+    fn stdin_operand() {
+        let labels: Vec<_> = Operands::new(vec!["-".to_owned(), "-".to_owned()])
+            .map(|input| input.unwrap().operand().map(OsStr::to_owned))
+            .collect();
+        assert_eq!(labels, [Some("-".into()), Some("-".into())]);
+
+        let mut once = Operands::new(vec!["-".to_owned(), "-".to_owned()]).stdin_once();
+        assert!(once.next().unwrap().unwrap().is_stdin());
+        let error = once.next().unwrap().unwrap_err();
+        assert_eq!(error.to_string(), "-: standard input was already read");
+        assert!(once.next().is_none());
+    }
+
+    #[test]
+    fn directory_fails_on_read() {
+        // a directory opens on Unix, and only fails when it's read
+        let dir = std::env::temp_dir().to_str().unwrap().to_owned();
+        if let Ok(mut input) = Input::open(&dir) {
+            let error = input.read(&mut [0; 16]).map_err(|e| input.error(e)).unwrap_err();
+            assert_eq!(error.operand, *dir);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn file_name_not_utf8() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let name = OsString::from_vec(b"caf\xe9".to_vec());
+        let scene = Scene::new().file(&name, b"x\n");
+        let path = scene.join(&name);
+        let missing = path.with_extension("missing");
+        let inputs: Vec<_> = Operands::new([path.clone(), missing.clone()].map(PathBuf::into_os_string)).collect();
+
+        let input = inputs[0].as_ref().unwrap();
+        assert_eq!(input.label(), path.as_os_str());
+        assert_eq!(input.path(), Some(path.as_path()));
+        let error = inputs[1].as_ref().unwrap_err();
+        assert_eq!(error.operand, missing.into_os_string());
+        assert!(error.to_string().ends_with("caf\u{fffd}.missing: No such file or directory (os error 2)"));
+    }
+}
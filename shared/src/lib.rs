@@ -1,21 +1,32 @@
 //! Shared code for core-utils
 //! 
-//! This module provides shared code (if needed) for all of the core-utils projects.
-//! 
-//! No shared code exists yet. The add() function is just a placeholder.
-
-/// Placeholder function that just adds two usize values.
-pub fn add(left: usize, right: usize) -> usize {
-    left + right
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
+//! This module provides shared code for all of the core-utils projects.
+//!
+//! * `display` - Writing operands and file names as they are, even when they aren't UTF-8.
+//! * `error` - Showing errors as `util: context: message`, and exit codes.
+//! * `help` - `--version`, the way GNU utilities show it.
+//! * `input` - Opening the files named on the command line, where `-` means standard input.
+//! * `mode` - File modes like `u+rwx,g-w` and `2755`, and showing them like `ls -l`.
+//! * `obsolete` - Old option forms like `head -5` and `sort +1 -2`, rewritten for `clap`.
+//! * `output` - Buffered standard output that stops quietly when a pipe is closed.
+//! * `quote` - Quoting file names in diagnostics and listings, like `ls --quoting-style`.
+//! * `time` - Dates and times: formatting like `date +FORMAT`, time zones from `TZ`, and reading dates.
+//! * `units` - Sizes like `10K` and durations like `1.5h`, read and shown the usual way.
+//! * `users` - Users and groups: names for IDs and IDs for names, and owners like `user:group`.
+//! * `utility` - Utilities as library entry points, for the `coreutils` binary.
+//! * `walk` - Walking directory trees, with `-H`, `-L`, and `-P`, like `du` and `chmod -R`.
 
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+pub mod display;
+pub mod error;
+pub mod help;
+pub mod input;
+pub mod mode;
+pub mod obsolete;
+pub mod output;
+pub mod quote;
+pub mod time;
+pub mod units;
+pub mod users;
+pub mod utility;
+#[cfg(unix)]
+pub mod walk;
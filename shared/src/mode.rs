@@ -0,0 +1,481 @@
+//! File modes, like `u+rwx,g-w,o=` and `2755`, and showing them like `ls -l`.
+//!
+//! `chmod`, `mkdir -m`, `mkfifo -m`, `mknod -m` and `install -m` all take a mode the
+//! same way. It's either octal, or a comma separated list of symbolic changes:
+//!
+//! * Who: any of `u`, `g`, `o` and `a` (for all three). With none, it's all three,
+//!   but bits in the umask aren't changed.
+//! * Then one or more operations: `+` adds bits, `-` takes them away, and `=` sets
+//!   them, clearing the rest of who's bits.
+//! * Each operation is followed by permissions: any of `r`, `w`, `x`, `X` (execute,
+//!   but only for directories and files someone can already execute), `s` (set user
+//!   or group ID) and `t` (sticky). Or a single `u`, `g` or `o`, to copy the bits
+//!   that one of them has now.
+//!
+//! An octal mode sets every bit, and the umask isn't used. On a directory, the set
+//! user and group ID bits are kept unless a mode with five digits, like `00755`,
+//! says otherwise, or a symbolic mode mentions them.
+//!
+//! `ModeChange::parse` reads a mode, and `ModeChange::apply` works out the new
+//! mode from the old one. The rules are the same as GNU's, so `chmod` gets the
+//! same answers.
+
+use std::fmt;
+
+use crate::error::UError;
+
+/// Set user ID on execution.
+const SET_UID: u32 = 0o4000;
+/// Set group ID on execution.
+const SET_GID: u32 = 0o2000;
+/// The sticky bit, like on `/tmp`.
+const STICKY: u32 = 0o1000;
+/// Read, write, and execute for the owner.
+const USER: u32 = 0o700;
+/// Read, write, and execute for the group.
+const GROUP: u32 = 0o070;
+/// Read, write, and execute for everyone else.
+const OTHER: u32 = 0o007;
+/// Read, for all three.
+const READ: u32 = 0o444;
+/// Write, for all three.
+const WRITE: u32 = 0o222;
+/// Execute, for all three.
+const EXECUTE: u32 = 0o111;
+
+/// Every bit a mode can change: the permissions, and the three special bits.
+pub const MODE_BITS: u32 = 0o7777;
+
+/// The bits of a mode from `stat` that have the type of file.
+const TYPE_BITS: u32 = 0o170000;
+
+/// A mode that couldn't be read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModeError;
+
+impl ModeError {
+    /// Make the error to show for `text`, the way `chmod` does:
+    /// `invalid mode: 'u+q'`.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The mode that couldn't be read, from the command line.
+    pub fn to_error(self, text: &str) -> UError {
+        UError::new(format!("{}: '{}'", self, text))
+    }
+}
+
+impl fmt::Display for ModeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid mode")
+    }
+}
+
+impl std::error::Error for ModeError {}
+
+/// A list of changes to make to a mode, read from an octal or symbolic mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModeChange {
+    changes: Vec<Change>,
+}
+
+/// One operation, like the `+x` in `u+x`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Change {
+    op: Op,
+    kind: Kind,
+    /// Bits for who it's for, or 0 if no one was named, so the umask is used.
+    affected: u32,
+    /// Bits to add, take away, or set.
+    value: u32,
+    /// Bits the mode talks about, which are changed even on a directory.
+    mentioned: u32,
+}
+
+/// What to do with the bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    /// `+`
+    Add,
+    /// `-`
+    Remove,
+    /// `=`
+    Set,
+}
+
+/// Where the bits come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    /// They're the bits in `value`.
+    Ordinary,
+    /// They're copied from the old mode: the `u`, `g` or `o` bits in `value`.
+    CopyExisting,
+    /// They're the bits in `value`, and execute too if it's a directory or
+    /// someone can already execute it, for `X`.
+    ExecuteIfAny,
+}
+
+impl ModeChange {
+    /// Read an octal or symbolic mode.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The mode, like `u+rwx,g-w,o=` or `2755`.
+    pub fn parse(text: &str) -> Result<ModeChange, ModeError> {
+        let bytes = text.as_bytes();
+        if bytes.first().is_some_and(|b| is_octal(*b)) {
+            let (mode, rest) = octal(bytes)?;
+            if !rest.is_empty() {
+                return Err(ModeError);
+            }
+            // with fewer than five digits, the set ID bits on a directory are kept
+            // unless they're being set
+            let mentioned = if bytes.len() < 5 { (mode & (SET_UID | SET_GID)) | STICKY | 0o777 } else { MODE_BITS };
+            return Ok(ModeChange::set(mode, mentioned));
+        }
+
+        let mut changes = Vec::new();
+        let mut rest = bytes;
+        loop {
+            let mut affected = 0;
+            while let Some((&who, after)) = rest.split_first() {
+                affected |= match who {
+                    b'u' => SET_UID | USER,
+                    b'g' => SET_GID | GROUP,
+                    b'o' => STICKY | OTHER,
+                    b'a' => MODE_BITS,
+                    _ => break,
+                };
+                rest = after;
+            }
+
+            // at least one operation
+            let first = changes.len();
+            while let Some((&op, after)) = rest.split_first() {
+                let op = match op {
+                    b'+' => Op::Add,
+                    b'-' => Op::Remove,
+                    b'=' => Op::Set,
+                    _ => break,
+                };
+                rest = after;
+                let (change, after) = permissions(op, affected, rest)?;
+                changes.push(change);
+                rest = after;
+            }
+            if changes.len() == first {
+                return Err(ModeError);
+            }
+
+            // another change has to come after a comma, so a list can't end with one
+            match rest.split_first() {
+                Some((b',', after)) => rest = after,
+                None => return Ok(ModeChange { changes }),
+                _ => return Err(ModeError),
+            }
+        }
+    }
+
+    /// Make a change that sets the mode to exactly `mode`, like `chmod --reference`.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The mode to copy, from `stat`. The type of file is left out.
+    pub fn from_reference(mode: u32) -> ModeChange {
+        ModeChange::set(mode & MODE_BITS, MODE_BITS)
+    }
+
+    /// Work out the new mode for a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The file's mode now. Only the permissions and special bits are
+    ///   used, and the type of file is left out of the result.
+    /// * `dir` - Whether it's a directory, which matters for `X` and the set ID bits.
+    /// * `umask` - The process's umask, for changes that don't say who they're for.
+    pub fn apply(&self, mode: u32, dir: bool, umask: u32) -> u32 {
+        self.apply_with_bits(mode, dir, umask).0
+    }
+
+    /// Work out the new mode for a file, and which bits the changes were meant to
+    /// set or clear. `chmod` uses those to warn when the umask got in the way, like
+    /// for `chmod -w` with a umask of `022`, which can't take away write for group
+    /// or other.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The file's mode now.
+    /// * `dir` - Whether it's a directory.
+    /// * `umask` - The process's umask.
+    pub fn apply_with_bits(&self, mode: u32, dir: bool, umask: u32) -> (u32, u32) {
+        let mut mode = mode & MODE_BITS;
+        let mut bits = 0;
+
+        for change in &self.changes {
+            let omitted = if dir { (SET_UID | SET_GID) & !change.mentioned } else { 0 };
+            let mut value = change.value;
+            match change.kind {
+                Kind::Ordinary => {},
+                Kind::CopyExisting => {
+                    // copy each of read, write and execute to all three, then the
+                    // affected bits pick out the ones to change
+                    value &= mode;
+                    for perm in [READ, WRITE, EXECUTE] {
+                        if value & perm != 0 {
+                            value |= perm;
+                        }
+                    }
+                },
+                Kind::ExecuteIfAny => if mode & EXECUTE != 0 || dir {
+                    value |= EXECUTE;
+                },
+            }
+
+            let allowed = if change.affected != 0 { change.affected } else { !umask };
+            value &= allowed & !omitted & MODE_BITS;
+
+            match change.op {
+                Op::Set => {
+                    let kept = (if change.affected != 0 { !change.affected } else { 0 }) | omitted;
+                    bits |= MODE_BITS & !kept;
+                    mode = (mode & kept) | value;
+                },
+                Op::Add => {
+                    bits |= value;
+                    mode |= value;
+                },
+                Op::Remove => {
+                    bits |= value;
+                    mode &= !value;
+                },
+            }
+        }
+        (mode, bits)
+    }
+
+    /// Helper method to make a change that sets every bit to `mode`.
+    fn set(mode: u32, mentioned: u32) -> ModeChange {
+        ModeChange { changes: vec![Change { op: Op::Set, kind: Kind::Ordinary, affected: MODE_BITS, value: mode, mentioned }] }
+    }
+}
+
+/// Show a mode from `stat` the way `ls -l` does, like `drwxr-sr-t`: the type of
+/// file, then read, write and execute for the owner, group, and everyone else.
+///
+/// The set ID bits show as `s` in place of the execute bit, or `S` if it isn't
+/// executable, and the sticky bit as `t` or `T`.
+///
+/// # Arguments
+///
+/// * `mode` - The whole mode, with the type of file.
+pub fn format_mode(mode: u32) -> String {
+    let mut text = String::with_capacity(10);
+    text.push(match mode & TYPE_BITS {
+        0o100000 => '-',
+        0o040000 => 'd',
+        0o120000 => 'l',
+        0o020000 => 'c',
+        0o060000 => 'b',
+        0o010000 => 'p',
+        0o140000 => 's',
+        _ => '?',
+    });
+    for (shift, special, letter) in [(6, SET_UID, 's'), (3, SET_GID, 's'), (0, STICKY, 't')] {
+        let perms = (mode >> shift) & 0o7;
+        text.push(if perms & 0o4 != 0 { 'r' } else { '-' });
+        text.push(if perms & 0o2 != 0 { 'w' } else { '-' });
+        text.push(match (perms & 0o1 != 0, mode & special != 0) {
+            (true, true) => letter,
+            (false, true) => letter.to_ascii_uppercase(),
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
+    text
+}
+
+/// Helper method to read the permissions after an operation, like the `rwx` in
+/// `u+rwx`. Returns the change, and what's after the permissions.
+///
+/// # Arguments
+///
+/// * `op` - The operation before the permissions.
+/// * `affected` - Bits for who it's for, or 0 for no one named.
+/// * `text` - What's after the operation.
+fn permissions(op: Op, affected: u32, text: &[u8]) -> Result<(Change, &[u8]), ModeError> {
+    let copy = |value: u32| (Kind::CopyExisting, value, &text[1..]);
+    let (kind, value, rest) = match text.first() {
+        Some(&b) if is_octal(b) => {
+            // an octal mode can follow an operation, but only on its own, like `=755`
+            let (mode, rest) = octal(text)?;
+            if affected != 0 || !matches!(rest.first(), Some(b',') | None) {
+                return Err(ModeError);
+            }
+            let change = Change { op, kind: Kind::Ordinary, affected: MODE_BITS, value: mode, mentioned: MODE_BITS };
+            return Ok((change, rest));
+        },
+        Some(b'u') => copy(USER),
+        Some(b'g') => copy(GROUP),
+        Some(b'o') => copy(OTHER),
+        _ => {
+            let mut kind = Kind::Ordinary;
+            let mut value = 0;
+            let end = text.iter().position(|b| !b"rwxXst".contains(b)).unwrap_or(text.len());
+            for b in &text[..end] {
+                match b {
+                    b'r' => value |= READ,
+                    b'w' => value |= WRITE,
+                    b'x' => value |= EXECUTE,
+                    b'X' => kind = Kind::ExecuteIfAny,
+                    // these only change anything for who they belong to
+                    b's' => value |= SET_UID | SET_GID,
+                    _ => value |= STICKY,
+                }
+            }
+            (kind, value, &text[end..])
+        },
+    };
+    let mentioned = if affected != 0 { affected & value } else { value };
+    Ok((Change { op, kind, affected, value, mentioned }, rest))
+}
+
+/// Helper method to check for an octal digit.
+fn is_octal(b: u8) -> bool {
+    (b'0'..=b'7').contains(&b)
+}
+
+/// Helper method to read octal digits from the start of `text`. Returns the mode,
+/// and what's after the digits.
+fn octal(text: &[u8]) -> Result<(u32, &[u8]), ModeError> {
+    let end = text.iter().position(|&b| !is_octal(b)).unwrap_or(text.len());
+    let mut mode = 0;
+    for &digit in &text[..end] {
+        mode = mode * 8 + u32::from(digit - b'0');
+        if mode > MODE_BITS {
+            return Err(ModeError);
+        }
+    }
+    Ok((mode, &text[end..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper method to parse `text`, and apply it with a umask of `022`.
+    fn apply(text: &str, mode: u32, dir: bool) -> u32 {
+        ModeChange::parse(text).unwrap().apply(mode, dir, 0o022)
+    }
+
+    #[test]
+    fn same_as_gnu_chmod() {
+        // made with GNU chmod and a umask of 022: the mode, the mode before, and
+        // the mode after for a file and for a directory
+        let cases = [
+            ("u+rwx", 0o644, 0o744, 0o744),
+            ("g-w", 0o644, 0o644, 0o644),
+            ("o=", 0o644, 0o640, 0o640),
+            ("a+X", 0o644, 0o644, 0o755),
+            ("+t", 0o644, 0o1644, 0o1644),
+            ("2755", 0o644, 0o2755, 0o2755),
+            ("00755", 0o644, 0o755, 0o755),
+            ("755", 0o644, 0o755, 0o755),
+            ("u=g", 0o644, 0o444, 0o444),
+            ("go=u-w", 0o644, 0o644, 0o644),
+            ("+s", 0o644, 0o6644, 0o6644),
+            ("u+s,g-s", 0o644, 0o4644, 0o4644),
+            ("=r", 0o644, 0o444, 0o444),
+            ("-w", 0o644, 0o444, 0o444),
+            ("u-x+s", 0o644, 0o4644, 0o4644),
+            ("g=o,o=u", 0o644, 0o646, 0o646),
+            ("ug=rwX", 0o644, 0o664, 0o774),
+            ("o=x,+X", 0o644, 0o751, 0o751),
+            ("u+rwx", 0o2750, 0o2750, 0o2750),
+            ("g-w", 0o2750, 0o2750, 0o2750),
+            ("o=", 0o2750, 0o2750, 0o2750),
+            ("a+X", 0o2750, 0o2751, 0o2751),
+            ("+t", 0o2750, 0o3750, 0o3750),
+            ("2755", 0o2750, 0o2755, 0o2755),
+            ("00755", 0o2750, 0o755, 0o755),
+            ("755", 0o2750, 0o755, 0o2755),
+            ("u=g", 0o2750, 0o2550, 0o2550),
+            ("go=u-w", 0o2750, 0o755, 0o2755),
+            ("+s", 0o2750, 0o6750, 0o6750),
+            ("u+s,g-s", 0o2750, 0o4750, 0o4750),
+            ("=r", 0o2750, 0o444, 0o2444),
+            ("-w", 0o2750, 0o2550, 0o2550),
+            ("u-x+s", 0o2750, 0o6650, 0o6650),
+            ("g=o,o=u", 0o2750, 0o707, 0o2707),
+            ("ug=rwX", 0o2750, 0o770, 0o2770),
+            ("o=x,+X", 0o2750, 0o2751, 0o2751),
+        ];
+        for (text, before, file, dir) in cases {
+            assert_eq!(apply(text, before, false), file, "chmod {} on a file with {:o}", text, before);
+            assert_eq!(apply(text, before, true), dir, "chmod {} on a directory with {:o}", text, before);
+        }
+    }
+
+    #[test]
+    fn umask() {
+        // with no one named, bits in the umask aren't changed
+        assert_eq!(apply("+w", 0o444, false), 0o644);
+        assert_eq!(apply("a+w", 0o444, false), 0o666);
+        assert_eq!(ModeChange::parse("=rwx").unwrap().apply(0, false, 0o077), 0o700);
+        // but an octal mode sets everything
+        assert_eq!(ModeChange::parse("777").unwrap().apply(0, false, 0o077), 0o777);
+        assert_eq!(ModeChange::parse("=777").unwrap().apply(0, false, 0o077), 0o777);
+    }
+
+    #[test]
+    fn bits_the_umask_kept() {
+        // chmod warns about this one, since group and other can still write
+        let change = ModeChange::parse("-w").unwrap();
+        assert_eq!(change.apply_with_bits(0o666, false, 0o022), (0o466, 0o200));
+        assert_eq!(ModeChange::parse("a-w").unwrap().apply_with_bits(0o666, false, 0o022), (0o444, 0o222));
+        assert_eq!(ModeChange::parse("u=r").unwrap().apply_with_bits(0o666, false, 0o022), (0o466, 0o4700));
+    }
+
+    #[test]
+    fn type_of_file_left_out() {
+        assert_eq!(apply("u+x", 0o100644, false), 0o744);
+        assert_eq!(ModeChange::from_reference(0o42750).apply(0o644, true, 0o022), 0o2750);
+        assert_eq!(ModeChange::from_reference(0o100600).apply(0o4777, false, 0o022), 0o600);
+    }
+
+    #[test]
+    fn valid() {
+        for text in ["07777", "00000", "=755", "+755,u+x", "a+rw-x=u", "uu+x", "-", "+", "=",
+                     "u+g+x", "ug+X", "+s-t", "u-", "u+rwx,g-w,o=", "a+X", "+t", "2755"] {
+            assert!(ModeChange::parse(text).is_ok(), "{} should be valid", text);
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        for text in ["", "u", "u+x,", ",u+x", "u+x,,g+x", "8", "10000", "17777", "7778", "u+q",
+                     "u=755", "=755x", "u+xu", "x", "0x", "u+gu", " 7", "u+x g+x"] {
+            assert_eq!(ModeChange::parse(text), Err(ModeError), "{} should be invalid", text);
+        }
+        assert_eq!(ModeError.to_error("q").to_string(), "invalid mode: 'q'");
+    }
+
+    #[test]
+    fn format() {
+        // checked against GNU stat -c %A
+        assert_eq!(format_mode(0o104755), "-rwsr-xr-x");
+        assert_eq!(format_mode(0o042755), "drwxr-sr-x");
+        assert_eq!(format_mode(0o041755), "drwxr-xr-t");
+        assert_eq!(format_mode(0o104644), "-rwSr--r--");
+        assert_eq!(format_mode(0o102644), "-rw-r-Sr--");
+        assert_eq!(format_mode(0o101644), "-rw-r--r-T");
+        assert_eq!(format_mode(0o040000), "d---------");
+        assert_eq!(format_mode(0o107777), "-rwsrwsrwt");
+        assert_eq!(format_mode(0o042751), "drwxr-s--x");
+        assert_eq!(format_mode(0o010644), "prw-r--r--");
+        assert_eq!(format_mode(0o120777), "lrwxrwxrwx");
+        assert_eq!(format_mode(0o020666), "crw-rw-rw-");
+        assert_eq!(format_mode(0o060600), "brw-------");
+        assert_eq!(format_mode(0o140755), "srwxr-xr-x");
+        assert_eq!(format_mode(0o644), "?rw-r--r--");
+    }
+}
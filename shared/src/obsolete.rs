@@ -0,0 +1,591 @@
+//! Old forms of options, rewritten into ones `clap` can read.
+//!
+//! Before POSIX 1003.1-2001, some utilities took options that don't look like options:
+//!
+//! * `head -5` for `head -n 5`, and `head -5c` for `head -c 5`.
+//! * `tail -5` and `tail +3` for `tail -n 5` and `tail -n +3`, with `c` or `f` after them too.
+//! * `sort +1 -2` for `sort -k 2,2`, counting fields from zero.
+//! * `uniq -2` for `uniq -f 2`, and `uniq +3` for `uniq -s 3`.
+//!
+//! 2001 took them out, and 2008 let them back in where they can't be confused with
+//! anything else. GNU decides which to follow from `_POSIX2_VERSION` (like `200112`),
+//! which is 2008 if it isn't set, so that's what `Posix::from_env` does too.
+//!
+//! `rewrite` turns the old forms into the new ones before `clap` sees the command
+//! line, so the rest of a utility only has to deal with the new ones. Some of them
+//! give an option more than once, so the parser should let the last one win.
+//! Anything that isn't a valid old form is left alone, for `clap` to complain about.
+
+use std::env;
+use std::ffi::OsString;
+
+/// The POSIX version to follow when `_POSIX2_VERSION` isn't set.
+const DEFAULT_POSIX2_VERSION: i64 = 200809;
+
+/// The version that took the old forms out.
+const POSIX_2001: i64 = 200112;
+
+/// The version that let some of them back in.
+const POSIX_2008: i64 = 200809;
+
+/// Short options of `sort` that take a value.
+const SORT_SHORT_VALUES: &str = "koStTy";
+
+/// Long options of `sort` that take a value, which can be the next argument.
+const SORT_LONG_VALUES: &[&str] = &[
+    "batch-size", "buffer-size", "compress-program", "field-separator", "files0-from",
+    "key", "output", "parallel", "random-source", "sort", "temporary-directory",
+];
+
+/// Ordering options that can come after a field in `sort +POS1 -POS2`.
+const SORT_ORDERING: &str = "bdfghiMnRrV";
+
+/// Short options of `uniq` that take a value.
+const UNIQ_SHORT_VALUES: &str = "fsw";
+
+/// Long options of `uniq` that take a value, which can be the next argument.
+const UNIQ_LONG_VALUES: &[&str] = &["check-chars", "skip-chars", "skip-fields"];
+
+/// Which POSIX rules to follow for the old forms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Posix {
+    /// The POSIX version, like `200809`.
+    pub version: i64,
+    /// `POSIXLY_CORRECT` is set.
+    pub correct: bool,
+}
+
+impl Posix {
+    /// Find the rules in the environment: the version in `_POSIX2_VERSION`, if it's
+    /// a number, and whether `POSIXLY_CORRECT` is set.
+    pub fn from_env() -> Posix {
+        let version = env::var("_POSIX2_VERSION").ok()
+            .and_then(|v| parse_version(&v))
+            .unwrap_or(DEFAULT_POSIX2_VERSION);
+        Posix { version, correct: env::var_os("POSIXLY_CORRECT").is_some() }
+    }
+
+    /// Check whether this is from before the old forms were taken out.
+    fn obsolete(self) -> bool {
+        self.version < POSIX_2001
+    }
+
+    /// Check whether the old forms that can't be confused with anything else work.
+    /// They do, except from 2001 up to 2008.
+    fn traditional(self) -> bool {
+        !(POSIX_2001..POSIX_2008).contains(&self.version)
+    }
+}
+
+impl Default for Posix {
+    fn default() -> Posix {
+        Posix { version: DEFAULT_POSIX2_VERSION, correct: false }
+    }
+}
+
+/// Read `_POSIX2_VERSION` like `strtol` does, and clamp it to an `int`. Anything
+/// that isn't all number is ignored.
+///
+/// # Arguments
+///
+/// * `text` - The value of `_POSIX2_VERSION`.
+fn parse_version(text: &str) -> Option<i64> {
+    let text = text.trim_start_matches([' ', '\t', '\n', '\x0b', '\x0c', '\r']);
+    let (negative, digits) = match text.as_bytes().first() {
+        Some(b'-') => (true, &text[1..]),
+        Some(b'+') => (false, &text[1..]),
+        _ => (false, text),
+    };
+    let (number, rest) = number(digits);
+    if !rest.is_empty() {
+        return None;
+    }
+    let number = i64::try_from(number?).unwrap_or(i64::MAX);
+    let number = if negative { -number } else { number };
+    Some(number.clamp(i32::MIN.into(), i32::MAX.into()))
+}
+
+/// Which utility's old forms to rewrite.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Syntax {
+    /// `head -5`, `head -5c`.
+    Head,
+    /// `tail -5`, `tail +3`, `tail -3cf`.
+    Tail,
+    /// `sort +1 -2`.
+    Sort,
+    /// `uniq -2`, `uniq +3`.
+    Uniq,
+}
+
+/// Rewrite the old forms in a command line into new ones.
+///
+/// # Arguments
+///
+/// * `syntax` - Which utility's old forms to look for.
+/// * `args` - Command line arguments, starting with the program name.
+/// * `posix` - Which POSIX rules to follow, usually `Posix::from_env()`.
+pub fn rewrite(syntax: Syntax, args: Vec<OsString>, posix: Posix) -> Vec<OsString> {
+    match syntax {
+        Syntax::Head => head(args),
+        Syntax::Tail => tail(args, posix),
+        Syntax::Sort => sort(args, posix),
+        Syntax::Uniq => uniq(args, posix),
+    }
+}
+
+/// Read a decimal number at the start of `text`. It's `None` if there are no digits,
+/// and as big as a `u64` can be if it's bigger than that.
+///
+/// # Arguments
+///
+/// * `text` - Where the number starts.
+fn number(text: &str) -> (Option<u64>, &str) {
+    let end = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let (digits, rest) = text.split_at(end);
+    let number = digits.bytes()
+        .fold(0u64, |n, d| n.saturating_mul(10).saturating_add(u64::from(d - b'0')));
+    ((!digits.is_empty()).then_some(number), rest)
+}
+
+/// Check whether a long option, without its `--`, takes a value. Long options can
+/// be shortened, so it's enough to start one that does.
+///
+/// # Arguments
+///
+/// * `option` - The option, without `--`.
+/// * `with_values` - The long options that take a value.
+fn long_takes_value(option: &str, with_values: &[&str]) -> bool {
+    !option.contains('=') && with_values.iter().any(|o| o.starts_with(option))
+}
+
+/// Put the new form in front of the arguments after the first one.
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments, starting with the program name.
+/// * `new` - What the first argument turns into.
+fn replace_first(mut args: Vec<OsString>, new: Vec<String>) -> Vec<OsString> {
+    args.splice(1..2, new.into_iter().map(OsString::from));
+    args
+}
+
+/// `head -NUMBER[bcklmqvz]`, as the first argument.
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments, starting with the program name.
+fn head(args: Vec<OsString>) -> Vec<OsString> {
+    let Some(first) = args.get(1).and_then(|a| a.to_str()) else { return args };
+    let Some(rest) = first.strip_prefix('-') else { return args };
+    let (Some(_), letters) = number(rest) else { return args };
+    let count = &rest[..rest.len() - letters.len()];
+
+    let mut lines = true;
+    let mut suffix = "";
+    let mut header = None;
+    let mut zero = false;
+    for letter in letters.chars() {
+        match letter {
+            'c' => (lines, suffix) = (false, ""),
+            'b' => (lines, suffix) = (false, "b"),
+            'k' => (lines, suffix) = (false, "k"),
+            'm' => (lines, suffix) = (false, "m"),
+            'l' => lines = true,
+            'q' | 'v' => header = Some(letter),
+            'z' => zero = true,
+            _ => return args,
+        }
+    }
+
+    let option = if lines { "-n" } else { "-c" };
+    let mut new = vec![option.to_owned(), format!("{}{}", count, suffix)];
+    new.extend(header.map(|h| format!("-{}", h)));
+    if zero {
+        new.push("-z".to_owned());
+    }
+    replace_first(args, new)
+}
+
+/// `tail [-+]NUMBER[bcl][f]`, when it's the only option.
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments, starting with the program name.
+/// * `posix` - Which POSIX rules to follow.
+fn tail(args: Vec<OsString>, posix: Posix) -> Vec<OsString> {
+    // only `tail OLD`, `tail OLD FILE`, and `tail OLD -- [FILE]`
+    let is_option = |a: &OsString| a.len() > 1 && a.as_encoded_bytes()[0] == b'-';
+    let alone = match args.len() {
+        2 => true,
+        3 => args[2] == "--" || !is_option(&args[2]),
+        4 => args[2] == "--",
+        _ => false,
+    };
+    if !alone {
+        return args;
+    }
+    let Some(first) = args[1].to_str() else { return args };
+
+    let (from_start, rest) = if let Some(rest) = first.strip_prefix('+') {
+        if !posix.traditional() {
+            return args;
+        }
+        (true, rest)
+    } else if let Some(rest) = first.strip_prefix('-') {
+        // `-` is standard input and `-c` is the option, unless they're from before 2001
+        let start = usize::from(rest.starts_with('c'));
+        if !posix.obsolete() && rest.len() <= start {
+            return args;
+        }
+        (false, rest)
+    } else {
+        return args;
+    };
+
+    let (count, letters) = number(rest);
+    let count = if count.is_some() { &rest[..rest.len() - letters.len()] } else { "10" };
+    let (lines, suffix, letters) = match letters.as_bytes().first() {
+        Some(b'b') => (false, "b", &letters[1..]),
+        Some(b'c') => (false, "", &letters[1..]),
+        Some(b'l') => (true, "", &letters[1..]),
+        _ => (true, "", letters),
+    };
+    let (follow, letters) = match letters.strip_prefix('f') {
+        Some(letters) => (true, letters),
+        None => (false, letters),
+    };
+    if !letters.is_empty() {
+        return args;
+    }
+
+    let option = if lines { "-n" } else { "-c" };
+    let sign = if from_start { "+" } else { "" };
+    let mut new = vec![option.to_owned(), format!("{}{}{}", sign, count, suffix)];
+    if follow {
+        new.push("-f".to_owned());
+    }
+    replace_first(args, new)
+}
+
+/// `sort +POS1 [-POS2]`, where a position is `FIELD[.CHAR][OPTIONS]` counting from zero.
+///
+/// `+POS1` alone works unless the version is from 2001 up to 2008, and with `-POS2`
+/// after it, it always works unless `POSIXLY_CORRECT` is set. Either way, it also
+/// works for the rest of the command line once one with `-POS2` has.
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments, starting with the program name.
+/// * `posix` - Which POSIX rules to follow.
+fn sort(args: Vec<OsString>, posix: Posix) -> Vec<OsString> {
+    let mut traditional = posix.traditional();
+    let mut new = Vec::with_capacity(args.len());
+    let mut args = args.into_iter().peekable();
+    new.extend(args.next());
+
+    while let Some(arg) = args.next() {
+        let Some(text) = arg.to_str() else {
+            new.push(arg);
+            continue;
+        };
+        if text == "--" {
+            new.push(arg);
+            new.extend(args);
+            break;
+        }
+        if let Some(long) = text.strip_prefix("--") {
+            let value = long_takes_value(long, SORT_LONG_VALUES);
+            new.push(arg);
+            if value {
+                new.extend(args.next());
+            }
+            continue;
+        }
+        if let Some(short) = text.strip_prefix('-').filter(|s| !s.is_empty()) {
+            let value = short.find(|c| SORT_SHORT_VALUES.contains(c))
+                .is_some_and(|i| i + 1 == short.len());
+            new.push(arg);
+            if value {
+                new.extend(args.next());
+            }
+            continue;
+        }
+        let Some(start) = text.strip_prefix('+') else {
+            new.push(arg);
+            continue;
+        };
+
+        let end = args.peek()
+            .and_then(|a| a.to_str())
+            .and_then(|a| a.strip_prefix('-'))
+            .filter(|a| a.starts_with(|c: char| c.is_ascii_digit()));
+        traditional |= end.is_some() && !posix.correct;
+        let key = traditional.then(|| sort_key(start, end)).flatten();
+        match key {
+            Some(key) => {
+                if end.is_some() {
+                    args.next();
+                }
+                new.push("-k".into());
+                new.push(key.into());
+            }
+            None => new.push(arg),
+        }
+    }
+    new
+}
+
+/// Turn `+POS1 -POS2` into the value for `-k`: `+a.x -b.y` is `a+1.x+1,b` if `y`
+/// is zero or isn't there, and `a+1.x+1,b+1.y` otherwise. `None` if they aren't
+/// valid positions.
+///
+/// # Arguments
+///
+/// * `start` - `POS1`, without the `+`.
+/// * `end` - `POS2`, without the `-`, if there's one.
+fn sort_key(start: &str, end: Option<&str>) -> Option<String> {
+    let (field, char, options) = sort_position(start)?;
+    let mut key = field.saturating_add(1).to_string();
+    if let Some(char) = char.filter(|&c| c > 0) {
+        key.push_str(&format!(".{}", char.saturating_add(1)));
+    }
+    key.push_str(options);
+
+    if let Some(end) = end {
+        let (field, char, options) = sort_position(end)?;
+        match char.filter(|&c| c > 0) {
+            Some(char) => key.push_str(&format!(",{}.{}", field.saturating_add(1), char)),
+            // field zero would be the end of nothing, which `-k` can't say
+            None if field == 0 => return None,
+            None => key.push_str(&format!(",{}", field)),
+        }
+        key.push_str(options);
+    }
+    Some(key)
+}
+
+/// Split a `sort` position into its field, character and ordering options.
+///
+/// # Arguments
+///
+/// * `position` - The position, without its `+` or `-`.
+fn sort_position(position: &str) -> Option<(u64, Option<u64>, &str)> {
+    let (field, rest) = number(position);
+    let (char, options) = match rest.strip_prefix('.') {
+        Some(rest) => {
+            let (char, options) = number(rest);
+            (Some(char?), options)
+        }
+        None => (None, rest),
+    };
+    options.chars().all(|c| SORT_ORDERING.contains(c)).then_some((field?, char, options))
+}
+
+/// `uniq -NUMBER` and `uniq +NUMBER`.
+///
+/// Digits given as options add up, so `-1 -2` skips twelve fields, unless `-f`
+/// came in between. Each of them becomes `-f` with the total so far. An operand
+/// `+N` is `-s N`, unless the version is from 2001 up to 2008.
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments, starting with the program name.
+/// * `posix` - Which POSIX rules to follow.
+fn uniq(args: Vec<OsString>, posix: Posix) -> Vec<OsString> {
+    let mut fields: Option<u64> = None;
+    let mut operands = 0;
+    let mut new = Vec::with_capacity(args.len());
+    let mut args = args.into_iter();
+    new.extend(args.next());
+
+    while let Some(arg) = args.next() {
+        // with POSIXLY_CORRECT, everything after the first file is a file
+        let options = !(posix.correct && operands > 0);
+        let Some(text) = arg.to_str().filter(|_| options) else {
+            operands += 1;
+            new.push(arg);
+            continue;
+        };
+        if text == "--" {
+            new.push(arg);
+            new.extend(args);
+            break;
+        }
+        if let Some(long) = text.strip_prefix("--") {
+            if long.split('=').next().is_some_and(|o| o.len() >= 6 && "skip-fields".starts_with(o)) {
+                fields = None;
+            }
+            let value = long_takes_value(long, UNIQ_LONG_VALUES);
+            new.push(arg);
+            if value {
+                new.extend(args.next());
+            }
+            continue;
+        }
+        if let Some(count) = text.strip_prefix('+') {
+            match number(count) {
+                (Some(_), "") if posix.traditional() => {
+                    new.push("-s".into());
+                    new.push(count.into());
+                }
+                _ => {
+                    operands += 1;
+                    new.push(arg);
+                }
+            }
+            continue;
+        }
+        let Some(short) = text.strip_prefix('-').filter(|s| !s.is_empty()) else {
+            operands += 1;
+            new.push(arg);
+            continue;
+        };
+
+        // split the cluster into its digits and the other options, in order
+        let mut flags = String::new();
+        let mut digits = false;
+        let mut value = None;
+        for (i, c) in short.char_indices() {
+            if let Some(d) = c.to_digit(10) {
+                fields = Some(fields.unwrap_or(0).saturating_mul(10).saturating_add(d.into()));
+                digits = true;
+                continue;
+            }
+            if digits {
+                push_fields(&mut new, &mut flags, fields);
+                digits = false;
+            }
+            flags.push(c);
+            if UNIQ_SHORT_VALUES.contains(c) {
+                if c == 'f' {
+                    fields = None;
+                }
+                value = Some(&short[i + c.len_utf8()..]);
+                break;
+            }
+        }
+        if digits {
+            push_fields(&mut new, &mut flags, fields);
+        }
+        match value {
+            Some(value) => {
+                flags.push_str(value);
+                new.push(format!("-{}", flags).into());
+                if value.is_empty() {
+                    new.extend(args.next());
+                }
+            }
+            None if !flags.is_empty() => new.push(format!("-{}", flags).into()),
+            None => (),
+        }
+    }
+    new
+}
+
+/// Add the options before some digits, then `-f` with the fields the digits add up to.
+///
+/// # Arguments
+///
+/// * `new` - The rewritten command line.
+/// * `flags` - Options without values that came before the digits. It's emptied.
+/// * `fields` - The number of fields the digits add up to.
+fn push_fields(new: &mut Vec<OsString>, flags: &mut String, fields: Option<u64>) {
+    if !flags.is_empty() {
+        new.push(format!("-{}", flags).into());
+        flags.clear();
+    }
+    new.push("-f".into());
+    new.push(fields.unwrap_or(0).to_string().into());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(syntax: Syntax, args: &[&str], version: i64) -> Vec<String> {
+        let args = std::iter::once("prog").chain(args.iter().copied()).map(OsString::from).collect();
+        let posix = Posix { version, correct: false };
+        rewrite(syntax, args, posix).into_iter().skip(1).map(|a| a.into_string().unwrap()).collect()
+    }
+
+    #[test]
+    fn versions() {
+        assert_eq!(parse_version("200112"), Some(200112));
+        assert_eq!(parse_version(" +199209"), Some(199209));
+        assert_eq!(parse_version("99999999999999999999"), Some(i32::MAX.into()));
+        assert_eq!(parse_version("-5"), Some(-5));
+        assert_eq!(parse_version("2008x"), None);
+        assert_eq!(parse_version(""), None);
+        assert!(Posix { version: 199209, correct: false }.traditional());
+        assert!(!Posix { version: 200112, correct: false }.traditional());
+        assert!(Posix::default().traditional());
+    }
+
+    #[test]
+    fn head() {
+        assert_eq!(run(Syntax::Head, &["-5", "a"], 200809), ["-n", "5", "a"]);
+        assert_eq!(run(Syntax::Head, &["-5c"], 200112), ["-c", "5"]);
+        assert_eq!(run(Syntax::Head, &["-2k"], 200809), ["-c", "2k"]);
+        assert_eq!(run(Syntax::Head, &["-3cl"], 200809), ["-n", "3"]);
+        assert_eq!(run(Syntax::Head, &["-3vqz"], 200809), ["-n", "3", "-q", "-z"]);
+        // not an old form, or not valid, or not first
+        assert_eq!(run(Syntax::Head, &["-n", "5"], 200809), ["-n", "5"]);
+        assert_eq!(run(Syntax::Head, &["-5x"], 200809), ["-5x"]);
+        assert_eq!(run(Syntax::Head, &["a", "-5"], 200809), ["a", "-5"]);
+    }
+
+    #[test]
+    fn tail() {
+        assert_eq!(run(Syntax::Tail, &["-2"], 200112), ["-n", "2"]);
+        assert_eq!(run(Syntax::Tail, &["+3", "a"], 200809), ["-n", "+3", "a"]);
+        assert_eq!(run(Syntax::Tail, &["+3", "a"], 199209), ["-n", "+3", "a"]);
+        assert_eq!(run(Syntax::Tail, &["+3", "a"], 200112), ["+3", "a"]);
+        assert_eq!(run(Syntax::Tail, &["-2c"], 200809), ["-c", "2"]);
+        assert_eq!(run(Syntax::Tail, &["-2bf", "--", "a"], 200809), ["-c", "2b", "-f", "--", "a"]);
+        assert_eq!(run(Syntax::Tail, &["+f"], 200809), ["-n", "+10", "-f"]);
+        // `-c` is only an old form before 2001
+        assert_eq!(run(Syntax::Tail, &["-c", "4"], 200809), ["-c", "4"]);
+        assert_eq!(run(Syntax::Tail, &["-c", "4"], 199209), ["-c", "10", "4"]);
+        assert_eq!(run(Syntax::Tail, &["-"], 200809), ["-"]);
+        // only when it's the only option
+        assert_eq!(run(Syntax::Tail, &["-2", "-v"], 200809), ["-2", "-v"]);
+        assert_eq!(run(Syntax::Tail, &["-2", "a", "b"], 200809), ["-2", "a", "b"]);
+        assert_eq!(run(Syntax::Tail, &["-2x"], 200809), ["-2x"]);
+    }
+
+    #[test]
+    fn sort() {
+        assert_eq!(run(Syntax::Sort, &["+1", "a"], 200809), ["-k", "2", "a"]);
+        assert_eq!(run(Syntax::Sort, &["+1", "a"], 200112), ["+1", "a"]);
+        assert_eq!(run(Syntax::Sort, &["+1", "-2", "a"], 200112), ["-k", "2,2", "a"]);
+        assert_eq!(run(Syntax::Sort, &["+1.2n", "-3.1r"], 200809), ["-k", "2.3n,4.1r"]);
+        assert_eq!(run(Syntax::Sort, &["+0.0", "-1.0"], 200809), ["-k", "1,1"]);
+        assert_eq!(run(Syntax::Sort, &["-r", "+2b", "-3", "+0"], 200112), ["-r", "-k", "3b,3", "-k", "1"]);
+        // values of options, after `--`, and things that aren't positions are files
+        assert_eq!(run(Syntax::Sort, &["-o", "+1", "-t", "+", "--output", "+2"], 200809),
+            ["-o", "+1", "-t", "+", "--output", "+2"]);
+        assert_eq!(run(Syntax::Sort, &["-ro+1", "--", "+1"], 200809), ["-ro+1", "--", "+1"]);
+        assert_eq!(run(Syntax::Sort, &["+1x", "+.1", "+1."], 200809), ["+1x", "+.1", "+1."]);
+
+        let args = ["prog", "+1", "-2"].map(OsString::from).to_vec();
+        let correct = Posix { version: 200112, correct: true };
+        assert_eq!(rewrite(Syntax::Sort, args.clone(), correct), args);
+    }
+
+    #[test]
+    fn uniq() {
+        assert_eq!(run(Syntax::Uniq, &["-1", "a"], 200809), ["-f", "1", "a"]);
+        assert_eq!(run(Syntax::Uniq, &["-1", "-2"], 200112), ["-f", "1", "-f", "12"]);
+        assert_eq!(run(Syntax::Uniq, &["-1", "-f", "3", "-2"], 200809), ["-f", "1", "-f", "3", "-f", "2"]);
+        assert_eq!(run(Syntax::Uniq, &["-c2u", "-1s", "4"], 200809), ["-c", "-f", "2", "-u", "-f", "21", "-s", "4"]);
+        assert_eq!(run(Syntax::Uniq, &["-2f3"], 200809), ["-f", "2", "-f3"]);
+        assert_eq!(run(Syntax::Uniq, &["+2", "a"], 199209), ["-s", "2", "a"]);
+        assert_eq!(run(Syntax::Uniq, &["+2", "a"], 200809), ["-s", "2", "a"]);
+        assert_eq!(run(Syntax::Uniq, &["+2", "a"], 200112), ["+2", "a"]);
+        // values of options and operands after `--` aren't old forms
+        assert_eq!(run(Syntax::Uniq, &["-w", "-2", "--skip-chars", "+3"], 200809), ["-w", "-2", "--skip-chars", "+3"]);
+        assert_eq!(run(Syntax::Uniq, &["--", "-2", "+3"], 200809), ["--", "-2", "+3"]);
+        assert_eq!(run(Syntax::Uniq, &["+2x", "-"], 200809), ["+2x", "-"]);
+    }
+}
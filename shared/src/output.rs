@@ -0,0 +1,155 @@
+//! Writing to standard output.
+//!
+//! `Output` locks standard output once and writes through a big buffer, instead of
+//! locking and flushing for every line like `println!` does. `println!` also panics
+//! when it can't write, which happens every time a pipe is closed early, like
+//! `wc * | head -1`.
+//!
+//! The standard utilities are ended quietly by `SIGPIPE` in that case. Rust ignores
+//! `SIGPIPE`, so here the write fails with `EPIPE` instead. `Output::finish()`
+//! treats that the same way: whoever was reading has all they want, so it isn't an
+//! error. Any other write error, like `ENOSPC` writing to `/dev/full`, is reported
+//! as `util: write error: ...` and the utility fails.
+
+use std::io::{self, BufWriter, ErrorKind, StdoutLock, Write};
+
+use crate::error::{strerror, ExitStatus, UError};
+
+/// Size of the buffer in front of standard output.
+pub const BUFFER_SIZE: usize = 64 * 1024;
+
+/// Buffered output. Usually standard output, but tests can use anything that
+/// can be written to.
+pub struct Output<W: Write> {
+    writer: BufWriter<W>,
+}
+
+impl Output<StdoutLock<'static>> {
+    /// Lock standard output, and write to it through a buffer of `BUFFER_SIZE` bytes.
+    pub fn stdout() -> Output<StdoutLock<'static>> {
+        Output::new(io::stdout().lock())
+    }
+}
+
+impl<W: Write> Output<W> {
+    /// Write to `writer` through a buffer of `BUFFER_SIZE` bytes.
+    pub fn new(writer: W) -> Output<W> {
+        Output { writer: BufWriter::with_capacity(BUFFER_SIZE, writer) }
+    }
+
+    /// Flush anything left in the buffer, and report the first write error, if
+    /// there was one. Writing should stop at the first error, since the output
+    /// is gone, and the result of the writing is passed in here.
+    ///
+    /// Returns `false` if the pipe was closed, in case there's more work that
+    /// doesn't need doing.
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - How the writing went.
+    /// * `status` - Exit status to fail, for any error but a closed pipe.
+    pub fn finish(mut self, result: io::Result<()>, status: &mut ExitStatus) -> bool {
+        let result = result.and_then(|_| self.writer.flush());
+        // if the flush failed, don't let the BufWriter try again when it's dropped
+        let _ = self.writer.into_parts();
+        match result {
+            Ok(()) => true,
+            Err(e) => {
+                if let Some(e) = write_error(&e) {
+                    status.report(&e);
+                }
+                false
+            },
+        }
+    }
+}
+
+impl<W: Write> Write for Output<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.writer.write_all(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Turn an error writing to standard output into the error to show: `write error:`
+/// and the message. Returns `None` if the pipe was closed, which isn't an error.
+pub fn write_error(e: &io::Error) -> Option<UError> {
+    match e.kind() {
+        ErrorKind::BrokenPipe => None,
+        _ => Some(UError::new(format!("write error: {}", strerror(e)))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A writer that takes `room` bytes, and then fails with `error`.
+    struct Failing {
+        room: usize,
+        error: fn() -> io::Error,
+    }
+
+    impl Write for Failing {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.room == 0 {
+                return Err((self.error)());
+            }
+            let n = buf.len().min(self.room);
+            self.room -= n;
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Helper method to write `lines` lines to a `Failing` writer, stopping at the
+    /// first error. Returns what `finish()` gave, and the exit status.
+    fn write_lines(room: usize, error: fn() -> io::Error, lines: usize) -> (bool, ExitStatus) {
+        let mut status = ExitStatus::new("test");
+        let mut out = Output::new(Failing { room, error });
+        let result = (0..lines).try_for_each(|i| writeln!(out, "line {}", i));
+        (out.finish(result, &mut status), status)
+    }
+
+    #[test]
+    fn everything_written() {
+        let mut status = ExitStatus::new("test");
+        let mut out = Output::new(Vec::new());
+        writeln!(out, "one").unwrap();
+        out.write_all(b"two\n").unwrap();
+        assert!(out.finish(Ok(()), &mut status));
+        assert!(status.is_success());
+    }
+
+    #[test]
+    fn closed_pipe_is_not_an_error() {
+        let (done, status) = write_lines(10, || io::Error::from(ErrorKind::BrokenPipe), 100_000);
+        assert!(!done);
+        assert!(status.is_success());
+    }
+
+    #[test]
+    fn full_device_is_an_error() {
+        // no space left on device
+        let (done, status) = write_lines(10, || io::Error::from_raw_os_error(28), 3);
+        assert!(!done);
+        assert_eq!(status.code(), crate::error::EXIT_FAILURE);
+    }
+
+    #[test]
+    fn write_error_text() {
+        assert_eq!(write_error(&io::Error::from(ErrorKind::BrokenPipe)), None);
+        assert_eq!(write_error(&io::Error::from_raw_os_error(28)).unwrap().to_string(),
+            "write error: No space left on device");
+    }
+}
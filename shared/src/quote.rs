@@ -0,0 +1,400 @@
+//! Quoting file names, so they can be shown safely.
+//!
+//! A file name can have anything in it but `/` and NUL: spaces, newlines, escape
+//! sequences, and bytes that aren't UTF-8. Printed as is, a name like that can
+//! break up a line of output, or mess with the terminal. The standard utilities
+//! quote names in their messages, and `ls` has `--quoting-style` to pick how.
+//! These are the same styles. For the names `a b`, `it's`, and `a` and `b` with a
+//! newline between them:
+//!
+//! ```text
+//! literal              a b    it's      the newline as it is
+//! shell                'a b'  "it's"    the newline as it is, in single quotes
+//! shell-always         'a b'  "it's"    the newline as it is, in single quotes
+//! shell-escape         'a b'  "it's"    'a'$'\n''b'
+//! shell-escape-always  'a b'  "it's"    'a'$'\n''b'
+//! c                    "a b"  "it's"    "a\nb"
+//! escape               a b    it's      a\nb
+//! locale               ‘a b’  ‘it's’    ‘a\nb’
+//! ```
+//!
+//! The shell styles only add quotes when the name needs them, unless they're the
+//! `-always` kind. The escaping styles show characters that can't be printed as
+//! escapes, like `\n`, or `\377` for a byte that isn't part of a UTF-8 character.
+//!
+//! Diagnostics use `quote_name()`, which is `shell-escape`, and quotes names with
+//! a `:` in them too, so they can't be mixed up with the message after them.
+
+use std::ffi::OsStr;
+
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+
+/// How to quote a name, like `ls --quoting-style`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotingStyle {
+    /// Show the name as it is.
+    Literal,
+    /// Put the name in single quotes if the shell would need them.
+    Shell,
+    /// Always put the name in single quotes.
+    ShellAlways,
+    /// Like `Shell`, and show characters that can't be printed as `$'\n'`.
+    ShellEscape,
+    /// Like `ShellAlways`, and show characters that can't be printed as `$'\n'`.
+    ShellEscapeAlways,
+    /// Put the name in double quotes, with escapes like in C.
+    C,
+    /// Escapes like in C, without the quotes.
+    Escape,
+    /// Put the name in the quotes for the language, with escapes like in C.
+    /// Only UTF-8 is supported, so those are always `‘` and `’`.
+    Locale,
+}
+
+impl QuotingStyle {
+    /// Name of the style, like it's given to `--quoting-style`.
+    pub fn name(self) -> &'static str {
+        match self {
+            QuotingStyle::Literal => "literal",
+            QuotingStyle::Shell => "shell",
+            QuotingStyle::ShellAlways => "shell-always",
+            QuotingStyle::ShellEscape => "shell-escape",
+            QuotingStyle::ShellEscapeAlways => "shell-escape-always",
+            QuotingStyle::C => "c",
+            QuotingStyle::Escape => "escape",
+            QuotingStyle::Locale => "locale",
+        }
+    }
+
+    /// Quote `name` in this style. The result is raw bytes, since the `literal` and
+    /// `shell` styles leave bytes that aren't UTF-8 as they are.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name to quote, usually a file name.
+    pub fn quote(self, name: &OsStr) -> Vec<u8> {
+        quote_bytes(self, name.as_encoded_bytes(), false)
+    }
+}
+
+impl ValueEnum for QuotingStyle {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[QuotingStyle::Literal, QuotingStyle::Shell, QuotingStyle::ShellAlways,
+            QuotingStyle::ShellEscape, QuotingStyle::ShellEscapeAlways, QuotingStyle::C,
+            QuotingStyle::Escape, QuotingStyle::Locale]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(PossibleValue::new(self.name()))
+    }
+}
+
+/// Quote a file name for a diagnostic, like `wc: 'a b': No such file or directory`.
+/// Names that don't need quotes are left as they are.
+///
+/// The result is always UTF-8, since anything else is escaped.
+///
+/// # Arguments
+///
+/// * `name` - The name to quote, usually a file name.
+pub fn quote_name(name: impl AsRef<OsStr>) -> String {
+    let quoted = quote_bytes(QuotingStyle::ShellEscape, name.as_ref().as_encoded_bytes(), true);
+    String::from_utf8_lossy(&quoted).into_owned()
+}
+
+/// One piece of a name: a character, or a byte that isn't part of a UTF-8 character.
+#[derive(Debug, Clone, Copy)]
+enum Unit<'a> {
+    Char(char, &'a [u8]),
+    Byte(u8),
+}
+
+impl Unit<'_> {
+    /// Check whether this can be shown as it is.
+    fn is_printable(self) -> bool {
+        matches!(self, Unit::Char(c, _) if !c.is_control())
+    }
+
+    /// The bytes of this piece of the name.
+    fn bytes(&self) -> &[u8] {
+        match self {
+            Unit::Char(_, bytes) => bytes,
+            Unit::Byte(b) => std::slice::from_ref(b),
+        }
+    }
+
+    /// Write the escape for something that can't be printed, like `\n` or `\377`.
+    ///
+    /// # Arguments
+    ///
+    /// * `out` - Where to write it.
+    /// * `next` - The byte after this, if there is one. A NUL is `\000` instead
+    ///   of `\0` before a digit, so the digit isn't taken as part of it.
+    fn escape(self, out: &mut Vec<u8>, next: Option<u8>) {
+        let letter = match self {
+            Unit::Char('\0', _) if next.is_some_and(|b| b.is_ascii_digit()) => None,
+            Unit::Char(c, _) => match c {
+                '\0' => Some(b'0'),
+                '\x07' => Some(b'a'),
+                '\x08' => Some(b'b'),
+                '\x0c' => Some(b'f'),
+                '\n' => Some(b'n'),
+                '\r' => Some(b'r'),
+                '\t' => Some(b't'),
+                '\x0b' => Some(b'v'),
+                _ => None,
+            },
+            Unit::Byte(_) => None,
+        };
+        match letter {
+            Some(letter) => out.extend_from_slice(&[b'\\', letter]),
+            None => for &b in self.bytes() {
+                out.extend_from_slice(&[b'\\', b'0' + (b >> 6), b'0' + ((b >> 3) & 7), b'0' + (b & 7)]);
+            },
+        }
+    }
+}
+
+/// Split `bytes` into characters, and bytes that aren't part of one.
+fn units(bytes: &[u8]) -> Vec<Unit<'_>> {
+    let mut units = Vec::with_capacity(bytes.len());
+    for chunk in bytes.utf8_chunks() {
+        let valid = chunk.valid();
+        for (i, c) in valid.char_indices() {
+            units.push(Unit::Char(c, &valid.as_bytes()[i..i + c.len_utf8()]));
+        }
+        units.extend(chunk.invalid().iter().map(|&b| Unit::Byte(b)));
+    }
+    units
+}
+
+/// Quote `bytes` in `style`.
+///
+/// # Arguments
+///
+/// * `style` - How to quote it.
+/// * `bytes` - The name to quote.
+/// * `colon` - Whether a `:` means the name needs quotes, for the shell styles.
+fn quote_bytes(style: QuotingStyle, bytes: &[u8], colon: bool) -> Vec<u8> {
+    let units = units(bytes);
+    match style {
+        QuotingStyle::Literal => bytes.to_vec(),
+        QuotingStyle::Shell => quote_shell(&units, bytes, false, false, colon),
+        QuotingStyle::ShellAlways => quote_shell(&units, bytes, false, true, colon),
+        QuotingStyle::ShellEscape => quote_shell(&units, bytes, true, false, colon),
+        QuotingStyle::ShellEscapeAlways => quote_shell(&units, bytes, true, true, colon),
+        QuotingStyle::C => quote_escaped(&units, "\"", "\""),
+        QuotingStyle::Escape => quote_escaped(&units, "", ""),
+        QuotingStyle::Locale => quote_escaped(&units, "\u{2018}", "\u{2019}"),
+    }
+}
+
+/// Quote a name with C escapes, between `left` and `right`. A `right` in the name
+/// gets a backslash in front of it.
+fn quote_escaped(units: &[Unit], left: &str, right: &str) -> Vec<u8> {
+    let mut out = left.as_bytes().to_vec();
+    for (i, &unit) in units.iter().enumerate() {
+        match unit {
+            Unit::Char(c, _) if right.starts_with(c) => {
+                out.push(b'\\');
+                out.extend_from_slice(unit.bytes());
+            },
+            Unit::Char('\\', _) => out.extend_from_slice(b"\\\\"),
+            _ if unit.is_printable() => out.extend_from_slice(unit.bytes()),
+            _ => unit.escape(&mut out, units.get(i + 1).map(|u| u.bytes()[0])),
+        }
+    }
+    out.extend_from_slice(right.as_bytes());
+    out
+}
+
+/// Quote a name for the shell. It goes in single quotes, or in double quotes if
+/// the only thing in it that needs quotes is a single quote, like `"it's"`.
+///
+/// # Arguments
+///
+/// * `units` - The name, split up.
+/// * `bytes` - The name.
+/// * `escape` - Whether to show characters that can't be printed as `$'\n'`.
+/// * `always` - Whether to quote names that don't need it.
+/// * `colon` - Whether a `:` means the name needs quotes.
+fn quote_shell(units: &[Unit], bytes: &[u8], escape: bool, always: bool, colon: bool) -> Vec<u8> {
+    let special = |i: usize, unit: Unit| match unit {
+        Unit::Char(c, _) => match c {
+            ' ' | '!' | '"' | '$' | '&' | '\'' | '(' | ')' | '*' | ';' | '<' | '=' | '>' | '?'
+            | '[' | '\\' | '^' | '`' | '|' | '\n' | '\t' | '\r' | '\x08' | '\x0c' => true,
+            '#' | '~' => i == 0,
+            '{' | '}' => units.len() == 1,
+            ':' => colon,
+            _ => escape && !unit.is_printable(),
+        },
+        Unit::Byte(_) => escape,
+    };
+    if !always && !bytes.is_empty() && !units.iter().enumerate().any(|(i, &u)| special(i, u)) {
+        return bytes.to_vec();
+    }
+
+    // characters that are the same in double quotes as they are in single quotes
+    let plain = |i: usize, unit: Unit| match unit {
+        Unit::Char(c, _) => match c {
+            ' ' | '\'' | '%' | '+' | ',' | '-' | '.' | '/' | ':' | ']' | '_' | '@' => true,
+            '#' | '~' => i == 0,
+            _ => c.is_ascii_alphanumeric() || (!c.is_ascii() && unit.is_printable()),
+        },
+        Unit::Byte(_) => false,
+    };
+    if bytes.contains(&b'\'') && units.iter().enumerate().all(|(i, &u)| plain(i, u)) {
+        return [b"\"", bytes, b"\""].concat();
+    }
+
+    let mut out = vec![b'\''];
+    // whether a $'...' part is open, for escapes
+    let mut in_escape = false;
+    for &unit in units {
+        if matches!(unit, Unit::Char('\'', _)) {
+            out.extend_from_slice(b"'\\''");
+            in_escape = false;
+        } else if escape && !unit.is_printable() {
+            if !in_escape {
+                out.extend_from_slice(b"'$'");
+                in_escape = true;
+            }
+            unit.escape(&mut out, None);
+        } else {
+            if in_escape {
+                out.extend_from_slice(b"''");
+                in_escape = false;
+            }
+            out.extend_from_slice(unit.bytes());
+        }
+    }
+    out.push(b'\'');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Names to quote, with a bit of everything.
+    const NAMES: [&[u8]; 22] = [b"plain", b"sp ace", b"a\nb", b"t\tab", b"it's", b"it's \"x\"",
+        b"q\"d", b"b\\s", b"$x", b"*", b"?q", b"a=b", b"a:b", b"-d", b"#h", b"h#", b"~t", b"x~",
+        b"bel\x07", b"del\x7f", b"u\xffv", "\u{e9}".as_bytes()];
+
+    /// Helper method to quote every name in `NAMES`, and join them with spaces.
+    fn quote_all(style: QuotingStyle) -> String {
+        let quoted: Vec<_> = NAMES.iter()
+            .map(|name| String::from_utf8_lossy(&quote_bytes(style, name, false)).into_owned())
+            .collect();
+        quoted.join(" ")
+    }
+
+    // The expected output for each style is what `ls --quoting-style` shows, in a
+    // UTF-8 locale. ls escapes spaces in the escape style too, but that's ls.
+
+    #[test]
+    fn literal() {
+        assert_eq!(quote_all(QuotingStyle::Literal),
+            "plain sp ace a\nb t\tab it's it's \"x\" q\"d b\\s $x * ?q a=b a:b -d #h h# ~t x~ bel\x07 \
+            del\x7f u\u{fffd}v \u{e9}");
+    }
+
+    #[test]
+    fn shell() {
+        assert_eq!(quote_all(QuotingStyle::Shell),
+            "plain 'sp ace' 'a\nb' 't\tab' \"it's\" 'it'\\''s \"x\"' 'q\"d' 'b\\s' '$x' '*' '?q' 'a=b' \
+            a:b -d '#h' h# '~t' x~ bel\x07 del\x7f u\u{fffd}v \u{e9}");
+        assert_eq!(quote_all(QuotingStyle::ShellAlways),
+            "'plain' 'sp ace' 'a\nb' 't\tab' \"it's\" 'it'\\''s \"x\"' 'q\"d' 'b\\s' '$x' '*' '?q' \
+            'a=b' 'a:b' '-d' '#h' 'h#' '~t' 'x~' 'bel\x07' 'del\x7f' 'u\u{fffd}v' '\u{e9}'");
+    }
+
+    #[test]
+    fn shell_escape() {
+        assert_eq!(quote_all(QuotingStyle::ShellEscape),
+            "plain 'sp ace' 'a'$'\\n''b' 't'$'\\t''ab' \"it's\" 'it'\\''s \"x\"' 'q\"d' 'b\\s' '$x' \
+            '*' '?q' 'a=b' a:b -d '#h' h# '~t' x~ 'bel'$'\\a' 'del'$'\\177' 'u'$'\\377''v' \u{e9}");
+        assert_eq!(quote_all(QuotingStyle::ShellEscapeAlways),
+            "'plain' 'sp ace' 'a'$'\\n''b' 't'$'\\t''ab' \"it's\" 'it'\\''s \"x\"' 'q\"d' 'b\\s' \
+            '$x' '*' '?q' 'a=b' 'a:b' '-d' '#h' 'h#' '~t' 'x~' 'bel'$'\\a' 'del'$'\\177' \
+            'u'$'\\377''v' '\u{e9}'");
+    }
+
+    #[test]
+    fn c() {
+        assert_eq!(quote_all(QuotingStyle::C),
+            "\"plain\" \"sp ace\" \"a\\nb\" \"t\\tab\" \"it's\" \"it's \\\"x\\\"\" \"q\\\"d\" \
+            \"b\\\\s\" \"$x\" \"*\" \"?q\" \"a=b\" \"a:b\" \"-d\" \"#h\" \"h#\" \"~t\" \"x~\" \
+            \"bel\\a\" \"del\\177\" \"u\\377v\" \"\u{e9}\"");
+    }
+
+    #[test]
+    fn escape() {
+        assert_eq!(quote_all(QuotingStyle::Escape),
+            "plain sp ace a\\nb t\\tab it's it's \"x\" q\"d b\\\\s $x * ?q a=b a:b -d #h h# ~t x~ \
+            bel\\a del\\177 u\\377v \u{e9}");
+    }
+
+    #[test]
+    fn locale() {
+        assert_eq!(quote_all(QuotingStyle::Locale),
+            "‘plain’ ‘sp ace’ ‘a\\nb’ ‘t\\tab’ ‘it's’ ‘it's \"x\"’ ‘q\"d’ ‘b\\\\s’ ‘$x’ ‘*’ ‘?q’ \
+            ‘a=b’ ‘a:b’ ‘-d’ ‘#h’ ‘h#’ ‘~t’ ‘x~’ ‘bel\\a’ ‘del\\177’ ‘u\\377v’ ‘\u{e9}’");
+        assert_eq!(quote_bytes(QuotingStyle::Locale, "a’b".as_bytes(), false), "‘a\\’b’".as_bytes());
+    }
+
+    #[test]
+    fn empty() {
+        let quoted: Vec<_> = QuotingStyle::value_variants().iter()
+            .map(|style| String::from_utf8(style.quote(OsStr::new(""))).unwrap())
+            .collect();
+        assert_eq!(quoted, ["", "''", "''", "''", "''", "\"\"", "", "‘’"]);
+    }
+
+    #[test]
+    fn nul_and_other_controls() {
+        assert_eq!(quote_bytes(QuotingStyle::C, b"a\0b\x001\x1b[m", false), b"\"a\\0b\\0001\\033[m\"");
+        assert_eq!(quote_bytes(QuotingStyle::ShellEscape, b"\x1b[m\0", false), b"''$'\\033''[m'$'\\0'");
+        assert_eq!(quote_bytes(QuotingStyle::ShellEscape, b"\n'", false), b"''$'\\n'\\'''");
+        // C1 controls can't be printed either
+        assert_eq!(quote_bytes(QuotingStyle::Escape, "a\u{85}".as_bytes(), false), b"a\\302\\205");
+    }
+
+    #[test]
+    fn shell_specials() {
+        for name in ["{", "}", "a b", "a|b", "a&b", "a;b", "(a)", "a<b", "a>b", "a^b", "a`b", "a!b", "[a"] {
+            let quoted = quote_bytes(QuotingStyle::Shell, name.as_bytes(), false);
+            assert_eq!(quoted, format!("'{}'", name).as_bytes(), "{:?}", name);
+        }
+        for name in ["{}", "a{", "a}", "a]", "a@b", "a%b", "a+b", "a,b", "a.b", "a/b", "a_b"] {
+            assert_eq!(quote_bytes(QuotingStyle::Shell, name.as_bytes(), false), name.as_bytes(), "{:?}", name);
+        }
+        // a single quote, with something that doesn't work the same in double quotes
+        assert_eq!(quote_bytes(QuotingStyle::Shell, b"it's$", false), b"'it'\\''s$'");
+        assert_eq!(quote_bytes(QuotingStyle::Shell, b"h#'", false), b"'h#'\\'''");
+        assert_eq!(quote_bytes(QuotingStyle::Shell, b"~'#", false), b"'~'\\''#'");
+        assert_eq!(quote_bytes(QuotingStyle::Shell, b"#'~", false), b"'#'\\''~'");
+    }
+
+    #[test]
+    fn names_for_diagnostics() {
+        assert_eq!(quote_name("notes.txt"), "notes.txt");
+        assert_eq!(quote_name("a:b"), "'a:b'");
+        assert_eq!(quote_name("a b"), "'a b'");
+        assert_eq!(quote_name("n\nx"), "'n'$'\\n''x'");
+        assert_eq!(quote_name("it's2"), "\"it's2\"");
+        assert_eq!(quote_name("it's:2"), "\"it's:2\"");
+        assert_eq!(quote_name("\u{e9}2"), "\u{e9}2");
+        assert_eq!(quote_name(""), "''");
+    }
+
+    #[test]
+    fn style_names() {
+        let names: Vec<_> = QuotingStyle::value_variants().iter().map(|style| style.name()).collect();
+        assert_eq!(names, ["literal", "shell", "shell-always", "shell-escape", "shell-escape-always", "c",
+            "escape", "locale"]);
+        assert_eq!(QuotingStyle::from_str("shell-escape", false), Ok(QuotingStyle::ShellEscape));
+        assert!(QuotingStyle::from_str("clocale", false).is_err());
+    }
+}
@@ -0,0 +1,520 @@
+//! Showing a time with a format like `date +FORMAT`, the way GNU `date` does.
+//!
+//! A directive is `%`, then maybe some flags, a width, a modifier, and colons,
+//! then a letter:
+//!
+//! * Flags: `-` (don't pad), `_` (pad with spaces), `0` (pad with zeros), `+` (like
+//!   `0`, with a sign on years that need more digits), `^` (upper case), and `#`
+//!   (the opposite case: upper case for names, lower case for `%p` and `%Z`).
+//! * Width: the least number of characters. Numbers are padded with zeros, except
+//!   `%e`, `%k` and `%l`, which use spaces. Everything else uses spaces.
+//! * Modifier: `E` or `O`, which ask for a locale's other way of showing it. This
+//!   is the C locale, so they don't change much: `%EC`, `%Ey` and `%EY` show years
+//!   the way the C library does, so year -1 is `-1`, `99` and `-1`.
+//! * Colons: only for `%z`, so `%:z` is `+hh:mm`, `%::z` is `+hh:mm:ss`, and `%:::z`
+//!   leaves off what's zero at the end.
+//!
+//! A directive that isn't one of these is shown as it is. `%N` is nanoseconds,
+//! where the width says how many digits, so `%3N` is milliseconds.
+
+use super::{is_leap, DateTime};
+
+const WEEKDAYS: [&str; 7] = ["Sunday", "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday"];
+const MONTHS: [&str; 12] = ["January", "February", "March", "April", "May", "June", "July", "August",
+                            "September", "October", "November", "December"];
+
+/// Conversions that can't have an `E` modifier.
+const NOT_WITH_E: &str = "aAbBdDeFgGhHIjklmMNSUVwW";
+/// Conversions that can't have an `O` modifier.
+const NOT_WITH_O: &str = "aAcDFxXY";
+
+/// The flags and width in front of a conversion.
+#[derive(Debug, Clone, Copy, Default)]
+struct Spec {
+    /// `-`, `_`, `0` or `+`, if one was given. The last one counts.
+    pad: Option<char>,
+    upper: bool,
+    swap_case: bool,
+    width: Option<usize>,
+    /// `E` or `O`, if one was given. `E` shows years the way the C library does,
+    /// and `O` shows numbers that way, which is padded with spaces.
+    modifier: Option<char>,
+}
+
+/// Show `time` with `format`, like `date +FORMAT`.
+///
+/// ```
+/// use shared::time::{format_time, Time, TimeZone};
+///
+/// let time = TimeZone::utc().at(Time::new(1700000000, 0));
+/// assert_eq!(format_time("%a %F %T %:z", &time), "Tue 2023-11-14 22:13:20 +00:00");
+/// ```
+///
+/// # Arguments
+///
+/// * `format` - The format, with directives like `%Y` in it.
+/// * `time` - What to show.
+pub fn format_time(format: &str, time: &DateTime) -> String {
+    let mut out = String::new();
+    let mut rest = format;
+    while let Some(start) = rest.find('%') {
+        out.push_str(&rest[..start]);
+        let text = &rest[start..];
+        let (directive, after) = directive(text);
+        rest = after;
+        match directive {
+            Ok((spec, colons, conversion)) => {
+                if !convert(&mut out, spec, colons, conversion, time) {
+                    pad(&mut out, spec, &text[..text.len() - after.len()]);
+                }
+            },
+            Err((spec, text)) => pad(&mut out, spec, text),
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Helper method to read one directive from the start of `text`, which starts with
+/// `%`. Returns the flags, the number of colons, and the conversion, or the text
+/// to show as it is if it isn't a directive. Either way, it returns what's after it.
+#[allow(clippy::type_complexity)]
+fn directive(text: &str) -> (Result<(Spec, usize, char), (Spec, &str)>, &str) {
+    let mut spec = Spec::default();
+    let mut chars = text.char_indices().skip(1).peekable();
+
+    while let Some(&(_, c)) = chars.peek() {
+        match c {
+            '-' | '_' | '0' | '+' => spec.pad = Some(c),
+            '^' => spec.upper = true,
+            '#' => spec.swap_case = true,
+            _ => break,
+        }
+        chars.next();
+    }
+    let mut width: Option<usize> = None;
+    while let Some(&(_, c)) = chars.peek().filter(|(_, c)| c.is_ascii_digit()) {
+        width = Some(width.unwrap_or(0).saturating_mul(10).saturating_add(c as usize - '0' as usize));
+        chars.next();
+    }
+    spec.width = width;
+    let modifier = chars.next_if(|&(_, c)| c == 'E' || c == 'O').map(|(_, c)| c);
+    spec.modifier = modifier;
+    let mut colons = 0;
+    let mut first_colon = None;
+    while let Some((at, _)) = chars.next_if(|&(_, c)| c == ':') {
+        first_colon.get_or_insert(at);
+        colons += 1;
+    }
+    if let Some(at) = first_colon.filter(|_| chars.peek().is_none_or(|&(_, c)| c != 'z')) {
+        // colons are only for `%z`, and otherwise the first one ends the directive
+        return (Err((spec, &text[..=at])), &text[at + 1..]);
+    }
+
+    let Some((at, conversion)) = chars.next() else {
+        // `%` at the end, maybe with flags
+        return (Err((spec, text)), "");
+    };
+    let end = at + conversion.len_utf8();
+    let plain = modifier.is_none() && colons == 0 && spec.pad.is_none() && width.is_none() && !spec.upper && !spec.swap_case;
+    let bad = match (conversion, modifier) {
+        // `%%` can't have anything else, and then the `%` starts the next directive
+        ('%', _) if !plain => return (Err((spec, &text[..at])), &text[at..]),
+        (_, Some('E')) => NOT_WITH_E.contains(conversion),
+        (_, Some(_)) => NOT_WITH_O.contains(conversion),
+        _ => false,
+    };
+    if bad {
+        // `#` makes month names upper case before the modifier is checked
+        spec.upper |= spec.swap_case && "bBh".contains(conversion);
+        return (Err((spec, &text[..end])), &text[end..]);
+    }
+    (Ok((spec, colons, conversion)), &text[end..])
+}
+
+/// Helper method to show one conversion. Returns `false` if it isn't one.
+fn convert(out: &mut String, spec: Spec, colons: usize, conversion: char, time: &DateTime) -> bool {
+    if spec.modifier == Some('O') && "CdegGHIjklmMqSuUVwWyz".contains(conversion) {
+        // the C library shows these, so its padding is what's padded
+        let text = match conversion {
+            'q' => "%Oq".to_owned(),
+            'y' => format_time("%Ey", time),
+            'g' => format!("{:02}", iso_week(time).0.rem_euclid(100)),
+            _ => format_time(&format!("%{}", conversion), time),
+        };
+        if !text.starts_with('-') {
+            pad(out, spec, &text);
+            return true;
+        }
+    }
+    let hour12 = (time.hour + 11) % 12 + 1;
+    match conversion {
+        'a' => name(out, spec, &WEEKDAYS[time.weekday as usize][..3], true),
+        'A' => name(out, spec, WEEKDAYS[time.weekday as usize], true),
+        'b' | 'h' => name(out, spec, &MONTHS[time.month as usize - 1][..3], true),
+        'B' => name(out, spec, MONTHS[time.month as usize - 1], true),
+        'c' => composite(out, spec, "%a %b %e %H:%M:%S %-Y", time),
+        'C' if spec.modifier == Some('E') => pad(out, spec, &time.year.div_euclid(100).to_string()),
+        'C' => {
+            // like C, dividing rounds toward zero, counting from 1900
+            let since_1900 = time.year - 1900;
+            let century = (since_1900 - if (-1900..0).contains(&since_1900) { 99 } else { 0 }) / 100 + 19;
+            year_number(out, spec, century.abs(), time.year < 0, 2);
+        },
+        'd' => number(out, spec, time.day.into(), 2, '0'),
+        'D' => {
+            // a flag is for the year, too
+            let year = spec.pad.map_or("%y".to_owned(), |pad| format!("%{}y", pad));
+            composite(out, spec, &format!("%m/%d/{}", year), time);
+        },
+        'e' => number(out, spec, time.day.into(), 2, '_'),
+        'F' => {
+            // a width or flag is for the whole thing, but only the year is padded
+            let year = match (spec.pad, spec.width) {
+                (None, None) => Spec { pad: Some('+'), ..Spec::default() },
+                (pad, width) => Spec { pad, width: Some(width.unwrap_or(0).saturating_sub(6)), ..Spec::default() },
+            };
+            let start = out.len();
+            year_number(out, year, time.year.abs(), time.year < 0, 4);
+            out.push_str(&format_time("-%m-%d", time));
+            if spec.upper {
+                let text = out.split_off(start);
+                out.push_str(&text.to_uppercase());
+            }
+        },
+        'g' => year_number(out, spec, (iso_week(time).0 % 100).abs(), false, 2),
+        'G' => {
+            let year = iso_week(time).0;
+            year_number(out, spec, year.abs(), year < 0, 4);
+        },
+        'H' => number(out, spec, time.hour.into(), 2, '0'),
+        'I' => number(out, spec, hour12.into(), 2, '0'),
+        'j' => number(out, spec, i64::from(time.yday) + 1, 3, '0'),
+        'k' => number(out, spec, time.hour.into(), 2, '_'),
+        'l' => number(out, spec, hour12.into(), 2, '_'),
+        'm' => number(out, spec, time.month.into(), 2, '0'),
+        'M' => number(out, spec, time.minute.into(), 2, '0'),
+        'n' => pad(out, spec, "\n"),
+        'N' => {
+            // the width is the number of digits, and `-` and `_` leave off zeros at
+            // the end, where `_` puts spaces instead
+            let mut digits = format!("{:09}", time.time.nanos);
+            let width = spec.width.unwrap_or(9);
+            digits.truncate(width);
+            digits.extend(std::iter::repeat_n('0', width.saturating_sub(9)));
+            if let Some(fill @ ('-' | '_')) = spec.pad {
+                let kept = digits.trim_end_matches('0').len().max(1);
+                digits.truncate(kept);
+                if fill == '_' {
+                    digits.extend(std::iter::repeat_n(' ', width.saturating_sub(kept)));
+                }
+            }
+            out.push_str(&digits);
+        },
+        'p' | 'P' => {
+            let text = if time.hour < 12 { "AM" } else { "PM" };
+            let lower = conversion == 'P' || spec.swap_case;
+            let text = if lower { text.to_ascii_lowercase() } else { text.to_owned() };
+            pad(out, Spec { upper: spec.upper && !lower, ..spec }, &text);
+        },
+        'q' => number(out, spec, i64::from((time.month - 1) / 3 + 1), 1, '0'),
+        'r' => composite(out, spec, "%I:%M:%S %p", time),
+        'R' => composite(out, spec, "%H:%M", time),
+        's' => number(out, spec, time.time.secs, 1, '0'),
+        'S' => number(out, spec, time.second.into(), 2, '0'),
+        't' => pad(out, spec, "\t"),
+        'T' | 'X' => composite(out, spec, "%H:%M:%S", time),
+        'u' => number(out, spec, i64::from((time.weekday + 6) % 7 + 1), 1, '0'),
+        'U' => number(out, spec, i64::from((time.yday + 7 - time.weekday) / 7), 2, '0'),
+        'V' => number(out, spec, iso_week(time).1, 2, '0'),
+        'w' => number(out, spec, time.weekday.into(), 1, '0'),
+        'W' => number(out, spec, i64::from((time.yday + 7 - (time.weekday + 6) % 7) / 7), 2, '0'),
+        'x' => composite(out, spec, "%m/%d/%Ey", time),
+        'y' if spec.modifier == Some('E') => pad(out, spec, &format!("{:02}", time.year.rem_euclid(100))),
+        'y' => year_number(out, spec, (time.year % 100).abs(), false, 2),
+        'Y' if spec.modifier == Some('E') => pad(out, spec, &time.year.to_string()),
+        'Y' => year_number(out, spec, time.year.abs(), time.year < 0, 4),
+        // a zone called `-00` has no offset, so it's shown as `-0000`
+        'z' => return offset(out, spec, colons, time.offset, time.offset < 0 || time.zone.starts_with('-')),
+        'Z' if spec.swap_case => pad(out, Spec { upper: false, ..spec }, &time.zone.to_ascii_lowercase()),
+        'Z' => pad(out, spec, &time.zone),
+        '%' => out.push('%'),
+        _ => return false,
+    }
+    true
+}
+
+/// Helper method to show the name of a day or month. `#` makes it upper case.
+fn name(out: &mut String, spec: Spec, text: &str, swap_to_upper: bool) {
+    pad(out, Spec { upper: spec.upper || (spec.swap_case && swap_to_upper), ..spec }, text);
+}
+
+/// Helper method to show another format, like `%T` for `%H:%M:%S`, with the width
+/// and case of this one.
+fn composite(out: &mut String, spec: Spec, format: &str, time: &DateTime) {
+    let text = format_time(format, time);
+    pad(out, spec, &text);
+}
+
+/// Helper method to add `text`, padded on the left to the width. It's padded with
+/// zeros for `0` and `+`, not at all for `-`, and with spaces otherwise.
+fn pad(out: &mut String, spec: Spec, text: &str) {
+    let len = text.chars().count();
+    let width = spec.width.unwrap_or(0);
+    if width > len && spec.pad != Some('-') {
+        let fill = if matches!(spec.pad, Some('0' | '+')) { '0' } else { ' ' };
+        out.extend(std::iter::repeat_n(fill, width - len));
+    }
+    if spec.upper {
+        out.push_str(&text.to_uppercase());
+    } else {
+        out.push_str(text);
+    }
+}
+
+/// Helper method to show a number.
+///
+/// # Arguments
+///
+/// * `spec` - The flags and width.
+/// * `value` - The number.
+/// * `digits` - The width, if none was given.
+/// * `default_pad` - `0` or `_`, for what to pad with if no flag says.
+fn number(out: &mut String, spec: Spec, value: i64, digits: usize, default_pad: char) {
+    signed(out, spec, &value.unsigned_abs().to_string(), value < 0, false, digits, default_pad);
+}
+
+/// Helper method to show a year, or something like one. With the `+` flag, it
+/// gets a sign if it has more digits than usual, or the width is wider than usual.
+///
+/// # Arguments
+///
+/// * `spec` - The flags and width.
+/// * `value` - The number, without its sign.
+/// * `negative` - Whether it's negative.
+/// * `digits` - The usual number of digits.
+fn year_number(out: &mut String, spec: Spec, value: i64, negative: bool, digits: usize) {
+    let limit = if digits == 2 { 99 } else { 9999 };
+    let plus = spec.pad == Some('+') && (value > limit || digits < spec.width.unwrap_or(0));
+    signed(out, spec, &value.to_string(), negative, plus, digits, '0');
+}
+
+/// Helper method to show digits with a sign, padded to the width. The sign counts
+/// toward the width, and zeros go after it, but spaces go in front of it.
+///
+/// # Arguments
+///
+/// * `spec` - The flags and width.
+/// * `digits` - The number, without its sign.
+/// * `negative` - Whether there's a `-` in front.
+/// * `plus` - Whether there's a `+` in front, if it isn't negative.
+/// * `default_width` - The width, if none was given.
+/// * `default_pad` - `0` or `_`, for what to pad with if no flag says.
+fn signed(out: &mut String, spec: Spec, digits: &str, negative: bool, plus: bool, default_width: usize, default_pad: char) {
+    let pad = spec.pad.unwrap_or(default_pad);
+    let sign = if negative { "-" } else if plus { "+" } else { "" };
+    let width = spec.width.unwrap_or(default_width);
+    let shortage = width.saturating_sub(sign.len() + digits.len());
+    match pad {
+        '-' => {},
+        '_' => out.extend(std::iter::repeat_n(' ', shortage)),
+        _ => {
+            out.push_str(sign);
+            out.extend(std::iter::repeat_n('0', shortage));
+            out.push_str(digits);
+            return;
+        },
+    }
+    out.push_str(sign);
+    out.push_str(digits);
+}
+
+/// Helper method to show the offset from UTC for `%z`. The number has colons in
+/// it, so padding it with spaces, or not at all, leaves off its leading zeros.
+/// Returns `false` if there are too many colons.
+///
+/// # Arguments
+///
+/// * `spec` - The flags and width.
+/// * `colons` - How many colons came before the `z`.
+/// * `offset` - Seconds east of UTC.
+/// * `negative` - Whether it has a `-` in front.
+fn offset(out: &mut String, spec: Spec, colons: usize, offset: i32, negative: bool) -> bool {
+    let seconds = offset.unsigned_abs();
+    let (hours, minutes, secs) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    let colons = match colons {
+        3 if secs != 0 => 2,
+        3 if minutes != 0 => 1,
+        _ => colons,
+    };
+    // the number, how many digits it has with its sign, and where the colons go
+    let (value, width, places): (u32, usize, &[usize]) = match colons {
+        0 => (hours * 100 + minutes, 5, &[]),
+        1 => (hours * 100 + minutes, 6, &[2]),
+        2 => (hours * 10000 + minutes * 100 + secs, 9, &[2, 4]),
+        3 => (hours, 3, &[]),
+        _ => return false,
+    };
+
+    // digits from the right, with colons between, and at least enough for the colons
+    let mut digits = Vec::new();
+    let mut value = value;
+    let mut count = 0;
+    loop {
+        if places.contains(&count) {
+            digits.push(':');
+        }
+        digits.push(char::from(b'0' + (value % 10) as u8));
+        value /= 10;
+        count += 1;
+        if value == 0 && places.iter().all(|&place| place < count) {
+            break;
+        }
+    }
+    let digits: String = digits.into_iter().rev().collect();
+    signed(out, spec, &digits, negative, !negative, width, '0');
+    true
+}
+
+/// Helper method to get the ISO 8601 week-based year, and the week in it. Weeks
+/// start on Monday, and week 1 is the one with the year's first Thursday.
+fn iso_week(time: &DateTime) -> (i64, i64) {
+    /// Days since the Monday of week 1, which can be negative.
+    fn days_since_week_1(yday: i64, weekday: i64) -> i64 {
+        // a multiple of 7 big enough to keep the remainder positive
+        let big_enough = (366 / 7 + 2) * 7;
+        yday - (yday - weekday + 4 + big_enough) % 7 + 3
+    }
+
+    let (yday, weekday) = (i64::from(time.yday), i64::from(time.weekday));
+    let mut year = time.year;
+    let mut days = days_since_week_1(yday, weekday);
+    if days < 0 {
+        year -= 1;
+        days = days_since_week_1(yday + 365 + i64::from(is_leap(year)), weekday);
+    } else {
+        let next = days_since_week_1(yday - 365 - i64::from(is_leap(year)), weekday);
+        if next >= 0 {
+            year += 1;
+            days = next;
+        }
+    }
+    (year, days / 7 + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::{days_from_civil, Time, TimeZone, SECONDS_PER_DAY};
+
+    /// Show the time `secs` seconds after the epoch in UTC.
+    fn show(format: &str, secs: i64, nanos: u32) -> String {
+        format_time(format, &TimeZone::utc().at(Time::new(secs, nanos)))
+    }
+
+    #[test]
+    fn conversions() {
+        // from `TZ=UTC date -d @1700000000.123456789 +FORMAT`
+        let cases = [
+            ("%a %A %b %B %h", "Tue Tuesday Nov November Nov"),
+            ("%c", "Tue Nov 14 22:13:20 2023"),
+            ("%C %y %Y %G %g", "20 23 2023 2023 23"),
+            ("%d %e %j %m", "14 14 318 11"),
+            ("%D %F %T %R %r %x %X", "11/14/23 2023-11-14 22:13:20 22:13 10:13:20 PM 11/14/23 22:13:20"),
+            ("%H %I %k %l %M %S %p %P", "22 10 22 10 13 20 PM pm"),
+            ("%s %N %3N %12N %-N", "1700000000 123456789 123 123456789000 123456789"),
+            ("%u %w %U %W %V %q", "2 2 46 46 46 4"),
+            ("%n%t|", "\n\t|"),
+            ("%z %:z %::z %:::z %Z", "+0000 +00:00 +00:00:00 +00 UTC"),
+            ("%Ey %EY %Od %Oe %EC %Ex %EX %Ec", "23 2023 14 14 20 11/14/23 22:13:20 Tue Nov 14 22:13:20 2023"),
+            ("100%% done", "100% done"),
+        ];
+        for (format, expected) in cases {
+            assert_eq!(show(format, 1700000000, 123456789), expected, "{}", format);
+        }
+    }
+
+    #[test]
+    fn flags() {
+        let cases = [
+            ("%-d %_d %05d %_5d %-j", "14 14 00014    14 318"),
+            ("%10Y %+6Y %_Y %-Y %+Y", "0000002023 +02023 2023 2023 2023"),
+            ("%Z %#Z %^a %#a %#p %^P", "UTC utc TUE TUE pm pm"),
+            ("%^#b %#B %^B %-5a %_a", "NOV NOVEMBER NOVEMBER Tue Tue"),
+            ("%_z %-z %10:z %_10z %-:::z", "   +0 +0 +000000:00         +0 +0"),
+            ("%^c|%20F|%_10T|%010R", "TUE NOV 14 22:13:20 2023|00000000002023-11-14|  22:13:20|0000022:13"),
+            ("%_F|%_12F|%012F|%+12F|%8F", "2023-11-14|  2023-11-14|002023-11-14|+02023-11-14|2023-11-14"),
+        ];
+        for (format, expected) in cases {
+            assert_eq!(show(format, 1700000000, 0), expected, "{}", format);
+        }
+    }
+
+    #[test]
+    fn not_directives() {
+        let cases = [
+            ("%Ed %Oa %OY %E%", "%Ed %Oa %OY %E%"),
+            ("%5% %-% %", "   %5% %-% %"),
+            ("%Q %:y %5", "%Q %:y    %5"),
+            ("%", "%"),
+        ];
+        for (format, expected) in cases {
+            assert_eq!(show(format, 1700000000, 0), expected, "{}", format);
+        }
+    }
+
+    #[test]
+    fn odd_years() {
+        // years -1 and 10000, from GNU `date`
+        let june_15_of_1_bc = (days_from_civil(-1, 6, 15)) * SECONDS_PER_DAY;
+        let cases = [
+            ("%c", "Tue Jun 15 00:00:00 -1"),
+            ("%C %y %Y %G %g", "-0 01 -001 -001 01"),
+            ("%D %F %x", "06/15/01 -001-06-15 06/15/99"),
+            ("%10Y %+6Y %_Y %-Y %+Y", "-000000001 -00001   -1 -1 -001"),
+            ("%20F %Ey %EY %EC", "-0000000000001-06-15 99 -1 -1"),
+        ];
+        for (format, expected) in cases {
+            assert_eq!(show(format, june_15_of_1_bc, 0), expected, "{}", format);
+        }
+
+        let cases = [
+            ("%c", "Tue Jul  4 03:04:05 10000"),
+            ("%C %y %Y %G %g", "100 00 10000 10000 00"),
+            ("%D %F %x", "07/04/00 +10000-07-04 07/04/00"),
+            ("%+6Y %+Y %_F %012F %11F", "+10000 +10000 10000-07-04 010000-07-04 10000-07-04"),
+        ];
+        for (format, expected) in cases {
+            assert_eq!(show(format, 253418295845, 0), expected, "{}", format);
+        }
+    }
+
+    #[test]
+    fn offsets() {
+        // from `TZ=... date -d @1700000000`
+        let cases = [
+            (19800, "+0530|+05:30|+05:30:00|+05:30|+530|+5:30| +5:30|+5:30:00|+0000530|   +5:30"),
+            (-28800, "-0800|-08:00|-08:00:00|-08|-800|-8:00| -8:00|-8:00:00|-0000800|   -8:00"),
+            (-12310, "-0325|-03:25|-03:25:10|-03:25:10|-325|-3:25| -3:25|-3:25:10|-0000325|   -3:25"),
+        ];
+        for (offset, expected) in cases {
+            let time = TimeZone::fixed(offset, "XXX").at(Time::new(1700000000, 0));
+            assert_eq!(format_time("%z|%:z|%::z|%:::z|%-z|%-:z|%_:z|%-::z|%8z|%_8:z", &time), expected, "{}", offset);
+        }
+    }
+
+    #[test]
+    fn iso_weeks() {
+        // the week-based year starts on the Monday of the week with January 4
+        let cases = [
+            ((2021, 1, 3), "2020 53 2020-W53-7 01 00"),
+            ((2021, 1, 4), "2021 01 2021-W01-1 01 01"),
+            ((2024, 12, 30), "2025 01 2025-W01-1 52 53"),
+            ((2026, 12, 31), "2026 53 2026-W53-4 52 52"),
+        ];
+        for ((year, month, day), expected) in cases {
+            let secs = days_from_civil(year, month, day) * SECONDS_PER_DAY;
+            assert_eq!(show("%G %V %G-W%V-%u %U %W", secs, 0), expected, "{}-{}-{}", year, month, day);
+        }
+    }
+}
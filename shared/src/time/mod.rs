@@ -0,0 +1,199 @@
+//! Dates and times: showing them like `date +FORMAT`, time zones from `TZ`, and
+//! reading dates like `date -d` takes.
+//!
+//! * `Time` is a moment, in seconds and nanoseconds since the epoch.
+//! * `TimeZone` turns a `Time` into a `DateTime`, which is what a clock in that
+//!   zone shows. See the `zone` module for how `TZ` is read.
+//! * `format_time` shows a `DateTime` with the same directives as GNU `date`,
+//!   like `%Y-%m-%d %H:%M:%S.%N %:z`. See the `format` module.
+//! * `parse_date` reads a date like `2024-03-15 10:00`, `@1700000000`, or
+//!   `next friday`. See the `parse` module.
+//!
+//! Names of days and months are in English, like the C locale. Leap seconds are
+//! left out, like they are everywhere else.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+mod format;
+mod parse;
+mod zone;
+
+pub use format::format_time;
+pub use parse::{parse_date, DateError};
+pub use zone::TimeZone;
+
+const SECONDS_PER_DAY: i64 = 86400;
+
+/// A moment in time, in seconds and nanoseconds since 1970-01-01 00:00:00 UTC.
+/// Before that, the seconds are negative, and the nanoseconds still count forward.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Time {
+    pub secs: i64,
+    /// Less than a second, so 0 to 999,999,999.
+    pub nanos: u32,
+}
+
+impl Time {
+    /// Make a time from seconds and nanoseconds since the epoch.
+    pub const fn new(secs: i64, nanos: u32) -> Time {
+        Time { secs, nanos }
+    }
+
+    /// The time now.
+    pub fn now() -> Time {
+        Time::from(SystemTime::now())
+    }
+}
+
+impl From<SystemTime> for Time {
+    fn from(time: SystemTime) -> Time {
+        match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => Time::new(since.as_secs() as i64, since.subsec_nanos()),
+            Err(e) => {
+                let before = e.duration();
+                match before.subsec_nanos() {
+                    0 => Time::new(-(before.as_secs() as i64), 0),
+                    nanos => Time::new(-(before.as_secs() as i64) - 1, 1_000_000_000 - nanos),
+                }
+            },
+        }
+    }
+}
+
+/// What a clock shows at some time, in some time zone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DateTime {
+    /// The moment it is.
+    pub time: Time,
+    pub year: i64,
+    /// 1 to 12.
+    pub month: u32,
+    /// 1 to 31.
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    /// Day of the week, where 0 is Sunday.
+    pub weekday: u32,
+    /// Day of the year, where 0 is January 1.
+    pub yday: u32,
+    /// Seconds east of UTC.
+    pub offset: i32,
+    /// Whether it's daylight saving time.
+    pub dst: bool,
+    /// Abbreviation of the time zone, like `CET`. It can be empty.
+    pub zone: String,
+}
+
+impl DateTime {
+    /// Work out what a clock shows at `time`, in a zone with these properties.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The moment.
+    /// * `offset` - Seconds east of UTC.
+    /// * `dst` - Whether it's daylight saving time.
+    /// * `zone` - Abbreviation of the time zone.
+    pub fn new(time: Time, offset: i32, dst: bool, zone: &str) -> DateTime {
+        let local = time.secs + i64::from(offset);
+        let days = local.div_euclid(SECONDS_PER_DAY);
+        let seconds = local.rem_euclid(SECONDS_PER_DAY) as u32;
+        let (year, month, day) = civil_from_days(days);
+        DateTime {
+            time,
+            year,
+            month,
+            day,
+            hour: seconds / 3600,
+            minute: seconds / 60 % 60,
+            second: seconds % 60,
+            // 1970-01-01 was a Thursday
+            weekday: (days + 4).rem_euclid(7) as u32,
+            yday: (days - days_from_civil(year, 1, 1)) as u32,
+            offset,
+            dst,
+            zone: zone.to_owned(),
+        }
+    }
+}
+
+/// Helper method to check for a leap year.
+fn is_leap(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// Helper method to get the number of days in a month.
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Helper method to get the number of days from 1970-01-01 to a date, in the
+/// proleptic Gregorian calendar. Months past 12 go on into the next years.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    // count from March, so February 29 is at the end of the year
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = i64::from(month);
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Helper method to get the date that's `days` after 1970-01-01, as the year,
+/// month, and day.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u32;
+    let month = if month_from_march < 10 { month_from_march + 3 } else { month_from_march - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_days() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+        assert_eq!(days_from_civil(2024, 13, 1), days_from_civil(2025, 1, 1));
+        for days in [-800000, -1, 0, 59, 11016, 11017, 19782, 2932896] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+        assert_eq!(civil_from_days(19797), (2024, 3, 15));
+        assert_eq!(civil_from_days(-719528), (0, 1, 1));
+    }
+
+    #[test]
+    fn clock() {
+        let local = DateTime::new(Time::new(1700000000, 5), 3600, false, "CET");
+        assert_eq!((local.year, local.month, local.day, local.hour, local.minute, local.second), (2023, 11, 14, 23, 13, 20));
+        assert_eq!((local.weekday, local.yday), (2, 317));
+
+        // before the epoch
+        let local = DateTime::new(Time::new(-1, 0), 0, false, "UTC");
+        assert_eq!((local.year, local.month, local.day, local.hour, local.weekday, local.yday), (1969, 12, 31, 23, 3, 364));
+    }
+
+    #[test]
+    fn from_system_time() {
+        use std::time::Duration;
+        assert_eq!(Time::from(UNIX_EPOCH + Duration::new(5, 7)), Time::new(5, 7));
+        assert_eq!(Time::from(UNIX_EPOCH - Duration::new(5, 7)), Time::new(-6, 999_999_993));
+        assert_eq!(Time::from(UNIX_EPOCH - Duration::new(5, 0)), Time::new(-5, 0));
+    }
+}
@@ -0,0 +1,1066 @@
+//! Reading dates like `date -d` takes, the way GNU `date` reads them.
+//!
+//! A date is a list of items, in any order, each at most once, except relative
+//! ones, which add up:
+//!
+//! * A calendar date: `2024-03-15`, `3/15/2024`, `15 March 2024`, `Mar 15, 2024`,
+//!   `15-Mar-2024`, `20240315`, or `Mar 15` for this year. A year with two digits
+//!   is 1969 to 2068.
+//! * A time of day: `10:00`, `10:00:30.5`, `3pm`, or `12:30 am`, maybe followed by
+//!   an offset from UTC like `+0530` or `-05:00`.
+//! * A time zone: `UTC`, `Z`, `EST`, `CEST`, and the like, maybe with an offset
+//!   after it, like `UTC+2`, or a military one like `T`. The names the local time
+//!   zone uses come first, and say whether daylight saving time is on, so `EST`
+//!   in New York has to be in winter.
+//! * A day of the week: `friday`, `next fri`, `last friday`, `third fri`. It's
+//!   left out if there's a calendar date too.
+//! * Relative items: `2 days`, `-1 week`, `3 hours ago`, `next month`, `tomorrow`,
+//!   `yesterday`, `today`, `now`. Units are `year`, `month`, `fortnight`, `week`,
+//!   `day`, `hour`, `minute` or `min`, and `second` or `sec`, maybe plural.
+//! * A number on its own: a year after a date, a date if it has more than four
+//!   digits, and otherwise a time, like `1030`.
+//!
+//! `@1700000000.5` is seconds since the epoch, and must be all there is. A date
+//! can start with `TZ="Europe/Paris"` to read it in that time zone.
+//!
+//! Whatever isn't given comes from now, except that the time is midnight if a
+//! date or day of the week is given without it. Years, months, and days are added
+//! to the calendar, so a month after January 31 is March 2 or 3. Like GNU `date`,
+//! the clock stays on daylight saving time or off it while they're added, if it
+//! was now and only relative items are given. Hours, minutes, and seconds are
+//! added to the time it ends up being.
+
+use std::fmt;
+
+use crate::error::UError;
+use super::{days_from_civil, days_in_month, Time, TimeZone, SECONDS_PER_DAY};
+
+const MONTHS: [&str; 12] = ["JANUARY", "FEBRUARY", "MARCH", "APRIL", "MAY", "JUNE", "JULY", "AUGUST",
+                            "SEPTEMBER", "OCTOBER", "NOVEMBER", "DECEMBER"];
+const WEEKDAYS: [&str; 7] = ["SUNDAY", "MONDAY", "TUESDAY", "WEDNESDAY", "THURSDAY", "FRIDAY", "SATURDAY"];
+
+/// Time zones that can be given by name, their offsets east of UTC in minutes,
+/// and whether they're daylight saving time. These are the ones GNU `date`
+/// knows.
+const ZONES: [(&str, i32, bool); 50] = [
+    ("GMT", 0, false), ("UT", 0, false), ("UTC", 0, false), ("WET", 0, false), ("WEST", 60, true),
+    ("BST", 60, true), ("ART", -180, false), ("BRT", -180, false), ("BRST", -120, true),
+    ("NST", -210, false), ("NDT", -150, true), ("AST", -240, false), ("ADT", -180, true),
+    ("CLT", -240, false), ("CLST", -180, true), ("EST", -300, false), ("EDT", -240, true),
+    ("CST", -360, false), ("CDT", -300, true), ("MST", -420, false), ("MDT", -360, true),
+    ("PST", -480, false), ("PDT", -420, true), ("AKST", -540, false), ("AKDT", -480, true),
+    ("HST", -600, false), ("HAST", -600, false), ("HADT", -540, true), ("SST", -660, false),
+    ("WAT", 60, false), ("CET", 60, false), ("CEST", 120, true), ("MET", 60, false),
+    ("MEZ", 60, false), ("MEST", 120, true), ("MESZ", 120, true), ("EET", 120, false),
+    ("EEST", 180, true), ("CAT", 120, false), ("SAST", 120, false), ("EAT", 180, false),
+    ("MSK", 180, false), ("MSD", 240, true), ("IST", 330, false), ("SGT", 480, false),
+    ("KST", 540, false), ("JST", 540, false), ("GST", 600, false), ("NZST", 720, false),
+    ("NZDT", 780, true),
+];
+
+/// The military time zones, `A` to `Z` without `J`, by how many hours east of
+/// UTC they are. `T` is in there too, but it's looked up on its own, since it
+/// can also come between a date and a time.
+const MILITARY: &str = "YXWVUTSRQPONZABCDEFGHIKLM";
+
+/// Words for the first, third, and so on. `second` is a unit instead.
+const ORDINALS: [(&str, i64); 14] = [
+    ("LAST", -1), ("THIS", 0), ("NEXT", 1), ("FIRST", 1), ("THIRD", 3), ("FOURTH", 4),
+    ("FIFTH", 5), ("SIXTH", 6), ("SEVENTH", 7), ("EIGHTH", 8), ("NINTH", 9), ("TENTH", 10),
+    ("ELEVENTH", 11), ("TWELFTH", 12),
+];
+
+/// The biggest year that can be worked out without overflowing, before seeing
+/// whether the time fits.
+const MAX_YEAR: i64 = 1 << 40;
+
+/// A date that couldn't be read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateError;
+
+impl DateError {
+    /// Make the error to show for `text`, the way `date` does:
+    /// `invalid date 'noon'`.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The date that couldn't be read, from the command line.
+    pub fn to_error(self, text: &str) -> UError {
+        UError::new(format!("{} '{}'", self, text))
+    }
+}
+
+impl fmt::Display for DateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid date")
+    }
+}
+
+impl std::error::Error for DateError {}
+
+/// A number in a date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Number {
+    /// The value, with its sign.
+    value: i64,
+    /// How many digits it has, which tells `1030` from `20240315`.
+    digits: usize,
+    /// Whether it starts with `+` or `-`.
+    signed: bool,
+    /// Nanoseconds after a decimal point, with the same sign as the value.
+    nanos: Option<i64>,
+}
+
+/// One piece of a date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Number(Number),
+    Word(Word),
+    Colon,
+    Slash,
+    Comma,
+    At,
+}
+
+/// A word in a date, by what it means.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Word {
+    /// 1 to 12.
+    Month(i64),
+    /// 0 to 6, where 0 is Sunday.
+    Weekday(i64),
+    /// `am` or `pm`, as the hours to add: 0 or 12.
+    Meridian(i64),
+    /// Minutes east of UTC, and whether it's daylight saving time, which can't
+    /// have an offset after it.
+    Zone(i32, bool),
+    /// A name the local time zone uses, and whether it's daylight saving time,
+    /// if the name says.
+    LocalZone(Option<bool>),
+    Unit(Unit),
+    /// A unit and how many of it, like `tomorrow`.
+    Relative(Unit, i64),
+    Ordinal(i64),
+    /// `ago`, which makes what's before it go the other way, or `hence`.
+    Ago(i64),
+    /// The `T` between a date and a time, like in `2024-03-15T10:00`, or
+    /// otherwise the military time zone seven hours west of UTC.
+    T,
+}
+
+/// A unit of relative time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    Years,
+    Months,
+    /// A number of days, like 7 for a week.
+    Days(i64),
+    /// A number of seconds, like 3600 for an hour.
+    Seconds(i64),
+}
+
+/// What a date says, as it's read.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Items {
+    /// The year, and how many digits it has.
+    year: Option<(i64, usize)>,
+    /// The month and day.
+    date: Option<(i64, i64)>,
+    dates: usize,
+    /// The hour, minute, second, and nanoseconds.
+    time: Option<(i64, i64, i64, i64)>,
+    times: usize,
+    /// The ordinal and the day of the week.
+    weekday: Option<(i64, i64)>,
+    weekdays: usize,
+    /// Seconds east of UTC.
+    zone: Option<i32>,
+    /// Whether daylight saving time is on, from a name the local time zone uses.
+    dst: Option<bool>,
+    zones: usize,
+    relative: Relative,
+    relatives: bool,
+}
+
+/// Relative items, added up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Relative {
+    years: i64,
+    months: i64,
+    days: i64,
+    seconds: i64,
+    nanos: i64,
+}
+
+/// Read a date like `date -d` takes.
+///
+/// ```
+/// use shared::time::{parse_date, Time, TimeZone};
+///
+/// let now = Time::new(1700000000, 0);
+/// let zone = TimeZone::utc();
+/// assert_eq!(parse_date("2024-03-15 10:00", now, &zone), Ok(Time::new(1710496800, 0)));
+/// assert_eq!(parse_date("@1700000000.5", now, &zone), Ok(Time::new(1700000000, 500_000_000)));
+/// assert_eq!(parse_date("1 hour ago", now, &zone), Ok(Time::new(1699996400, 0)));
+/// ```
+///
+/// # Arguments
+///
+/// * `text` - The date.
+/// * `now` - The time now, for what the date leaves out.
+/// * `zone` - The time zone to read it in, unless it starts with `TZ="..."`.
+pub fn parse_date(text: &str, now: Time, zone: &TimeZone) -> Result<Time, DateError> {
+    let (tz, rest) = tz_prefix(text)?;
+    let zone = tz.as_ref().unwrap_or(zone);
+    let tokens = tokens(rest, &local_zones(now, zone))?;
+
+    if let Some((Token::At, number)) = tokens.split_first() {
+        return match number {
+            [Token::Number(n)] => add(Time::new(n.value, 0), 0, n.nanos.unwrap_or(0)),
+            _ => Err(DateError),
+        };
+    }
+
+    let mut parser = Parser { tokens: &tokens, items: Items::default() };
+    while !parser.tokens.is_empty() {
+        parser.item()?;
+    }
+    resolve(&parser.items, now, zone)
+}
+
+/// Helper method to read `TZ="..."` at the start of a date, where `\"` and `\\`
+/// are a quote and a backslash. Returns the time zone, if there is one, and the
+/// rest of the date.
+fn tz_prefix(text: &str) -> Result<(Option<TimeZone>, &str), DateError> {
+    let Some(quoted) = text.trim_start().strip_prefix("TZ=\"") else {
+        return Ok((None, text));
+    };
+    let mut value = String::new();
+    let mut chars = quoted.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((Some(TimeZone::from_tz(Some(&value))), &quoted[i + 1..])),
+            '\\' => match chars.next() {
+                Some((_, c @ ('"' | '\\'))) => value.push(c),
+                _ => return Err(DateError),
+            },
+            _ => value.push(c),
+        }
+    }
+    Err(DateError)
+}
+
+/// Helper method to get the names `zone` uses over the year from `now`, and
+/// whether each is daylight saving time. That's `None` if the name is used for
+/// both.
+fn local_zones(now: Time, zone: &TimeZone) -> Vec<(String, Option<bool>)> {
+    let mut names: Vec<(String, Option<bool>)> = Vec::new();
+    for quarter in 0..4 {
+        let time = zone.at(Time::new(now.secs.saturating_add(quarter * 90 * SECONDS_PER_DAY), 0));
+        match names.iter_mut().find(|(name, _)| *name == time.zone) {
+            Some((_, dst)) if *dst != Some(time.dst) => *dst = None,
+            Some(_) => {},
+            None => names.push((time.zone, Some(time.dst))),
+        }
+    }
+    names
+}
+
+/// Helper method to split a date into tokens. A `+` or `-` that isn't in front of
+/// a number is left out, and so is anything in parentheses.
+///
+/// # Arguments
+///
+/// * `text` - The date.
+/// * `local_zones` - The names the local time zone uses, from `local_zones()`.
+fn tokens(text: &str, local_zones: &[(String, Option<bool>)]) -> Result<Vec<Token>, DateError> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        match c {
+            b'0'..=b'9' | b'+' | b'-' => {
+                let negative = c == b'-';
+                let signed = !c.is_ascii_digit();
+                if signed {
+                    i += 1;
+                    while bytes.get(i).is_some_and(u8::is_ascii_whitespace) {
+                        i += 1;
+                    }
+                    if !bytes.get(i).is_some_and(u8::is_ascii_digit) {
+                        continue;
+                    }
+                }
+                let (number, end) = number(bytes, i, negative, signed)?;
+                tokens.push(Token::Number(number));
+                i = end;
+            },
+            b'a'..=b'z' | b'A'..=b'Z' => {
+                let start = i;
+                while bytes.get(i).is_some_and(|&b| b.is_ascii_alphabetic() || b == b'.') {
+                    i += 1;
+                }
+                tokens.push(Token::Word(word(&text[start..i], local_zones).ok_or(DateError)?));
+            },
+            b'(' => {
+                let mut depth = 0;
+                while let Some(&b) = bytes.get(i) {
+                    depth += match b {
+                        b'(' => 1,
+                        b')' => -1,
+                        _ => 0,
+                    };
+                    i += 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+            },
+            b':' | b'/' | b',' | b'@' => {
+                tokens.push(match c {
+                    b':' => Token::Colon,
+                    b'/' => Token::Slash,
+                    b',' => Token::Comma,
+                    _ => Token::At,
+                });
+                i += 1;
+            },
+            _ if c.is_ascii_whitespace() => i += 1,
+            _ => return Err(DateError),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Helper method to read a number starting at `bytes[start]`, maybe with a
+/// decimal point or comma and digits after it. Returns it, and where it ends.
+fn number(bytes: &[u8], start: usize, negative: bool, signed: bool) -> Result<(Number, usize), DateError> {
+    let sign = if negative { -1 } else { 1 };
+    let mut end = start;
+    let mut value: i64 = 0;
+    while let Some(&b) = bytes.get(end).filter(|b| b.is_ascii_digit()) {
+        value = value.checked_mul(10).and_then(|value| value.checked_add(sign * i64::from(b - b'0'))).ok_or(DateError)?;
+        end += 1;
+    }
+    let digits = end - start;
+
+    let mut nanos = None;
+    if matches!(bytes.get(end), Some(b'.' | b',')) && bytes.get(end + 1).is_some_and(u8::is_ascii_digit) {
+        // more than nine digits are left off
+        end += 1;
+        let mut fraction = 0;
+        for place in 0..9 {
+            let digit = match bytes.get(end) {
+                Some(&b) if b.is_ascii_digit() => {
+                    end += 1;
+                    i64::from(b - b'0')
+                },
+                _ => 0,
+            };
+            fraction += digit * 10_i64.pow(8 - place);
+        }
+        while bytes.get(end).is_some_and(u8::is_ascii_digit) {
+            end += 1;
+        }
+        nanos = Some(sign * fraction);
+    }
+    Ok((Number { value, digits, signed, nanos }, end))
+}
+
+/// Helper method to look up a word, in any case. Periods are left out if it
+/// isn't anything with them, so `a.m.` is `am`.
+fn word(text: &str, local_zones: &[(String, Option<bool>)]) -> Option<Word> {
+    let upper = text.to_ascii_uppercase();
+    lookup(&upper, local_zones).or_else(|| {
+        if upper.contains('.') {
+            lookup(&upper.replace('.', ""), local_zones)
+        } else {
+            None
+        }
+    })
+}
+
+/// Helper method to look up a word in upper case.
+fn lookup(word: &str, local_zones: &[(String, Option<bool>)]) -> Option<Word> {
+    // names can be their first three letters, maybe with a period
+    let abbreviation = match word.len() {
+        3 => Some(word),
+        4 if word.ends_with('.') => Some(&word[..3]),
+        _ => None,
+    };
+    let matches = |name: &str| word == name || abbreviation.is_some_and(|abbreviation| name.starts_with(abbreviation));
+    if let Some(month) = MONTHS.iter().position(|name| matches(name)) {
+        return Some(Word::Month(month as i64 + 1));
+    }
+    if let Some(weekday) = WEEKDAYS.iter().position(|name| matches(name)) {
+        return Some(Word::Weekday(weekday as i64));
+    }
+
+    let found = match word {
+        "AM" => Word::Meridian(0),
+        "PM" => Word::Meridian(12),
+        "SEPT" => Word::Month(9),
+        "TUES" => Word::Weekday(2),
+        "WEDNES" => Word::Weekday(3),
+        "THUR" | "THURS" => Word::Weekday(4),
+        "T" => Word::T,
+        "AGO" => Word::Ago(-1),
+        "HENCE" => Word::Ago(1),
+        "TOMORROW" => Word::Relative(Unit::Days(1), 1),
+        "YESTERDAY" => Word::Relative(Unit::Days(1), -1),
+        "TODAY" | "NOW" => Word::Relative(Unit::Days(1), 0),
+        // UTC comes before the local names, which come before the others
+        "GMT" | "UT" | "UTC" => Word::Zone(0, false),
+        _ if word.len() == 1 => Word::Zone(MILITARY.find(word)? as i32 * 60 - 12 * 60, false),
+        _ => {
+            if let Some((_, dst)) = local_zones.iter().find(|(name, _)| name == word) {
+                return Some(Word::LocalZone(*dst));
+            }
+            if let Some(&(_, minutes, dst)) = ZONES.iter().find(|(name, ..)| *name == word) {
+                return Some(Word::Zone(minutes, dst));
+            }
+            if let Some(&(_, ordinal)) = ORDINALS.iter().find(|(name, _)| *name == word) {
+                return Some(Word::Ordinal(ordinal));
+            }
+            // units can be plural
+            let unit = if word.len() > 1 { word.strip_suffix('S').unwrap_or(word) } else { word };
+            Word::Unit(match unit {
+                "YEAR" => Unit::Years,
+                "MONTH" => Unit::Months,
+                "FORTNIGHT" => Unit::Days(14),
+                "WEEK" => Unit::Days(7),
+                "DAY" => Unit::Days(1),
+                "HOUR" => Unit::Seconds(3600),
+                "MINUTE" | "MIN" => Unit::Seconds(60),
+                "SECOND" | "SEC" => Unit::Seconds(1),
+                _ => return None,
+            })
+        },
+    };
+    Some(found)
+}
+
+/// Reads the items of a date from its tokens.
+struct Parser<'a> {
+    tokens: &'a [Token],
+    items: Items,
+}
+
+impl Parser<'_> {
+    /// Helper method to read the next item.
+    fn item(&mut self) -> Result<(), DateError> {
+        match self.next()? {
+            Token::Number(n) if n.signed => match self.next_word() {
+                Some(Word::Unit(unit)) => self.relative(n, unit),
+                _ => Err(DateError),
+            },
+            Token::Number(n) => self.starts_with_number(n),
+            Token::Word(Word::Month(month)) => {
+                if let [Token::Number(day), Token::Number(year), ..] = *self.tokens {
+                    if day.signed && year.signed && day.nanos.is_none() && year.nanos.is_none() {
+                        // like Mar-15-2024
+                        self.tokens = &self.tokens[2..];
+                        return self.set_date(Some((-year.value, year.digits)), month, -day.value);
+                    }
+                }
+                let day = self.unsigned()?;
+                match self.tokens {
+                    [Token::Comma, Token::Number(year), ..] if !year.signed && year.nanos.is_none() => {
+                        self.tokens = &self.tokens[2..];
+                        self.set_date(Some((year.value, year.digits)), month, day.value)
+                    },
+                    _ => self.set_date(None, month, day.value),
+                }
+            },
+            Token::Word(Word::Weekday(weekday)) => {
+                self.skip(Token::Comma);
+                self.set_weekday(0, weekday)
+            },
+            Token::Word(Word::Ordinal(ordinal)) => match self.next_word() {
+                Some(Word::Weekday(weekday)) => self.set_weekday(ordinal, weekday),
+                Some(Word::Unit(unit)) => {
+                    self.relative(Number { value: ordinal, digits: 0, signed: false, nanos: None }, unit)
+                },
+                _ => Err(DateError),
+            },
+            Token::Word(Word::Unit(unit)) => self.relative(Number { value: 1, digits: 0, signed: false, nanos: None }, unit),
+            Token::Word(Word::Relative(unit, count)) => self.add_relative(unit, count, 0),
+            Token::Word(Word::Zone(minutes, dst)) => {
+                let mut offset = minutes * 60;
+                // a signed number and a unit after it aren't an offset
+                if !dst && !self.signed_relative()? {
+                    if let [Token::Number(n), ..] = self.tokens {
+                        if n.signed {
+                            self.tokens = &self.tokens[1..];
+                            offset += self.zone_offset(*n)?;
+                        }
+                    }
+                }
+                self.set_zone(offset)
+            },
+            Token::Word(Word::LocalZone(dst)) => {
+                self.items.dst = dst;
+                self.set_zone_count()
+            },
+            Token::Word(Word::T) => {
+                self.signed_relative()?;
+                self.set_zone(-7 * 3600)
+            },
+            _ => Err(DateError),
+        }
+    }
+
+    /// Helper method to read an item that starts with an unsigned number.
+    fn starts_with_number(&mut self, n: Number) -> Result<(), DateError> {
+        if let Some(nanos) = n.nanos {
+            // only seconds can have a fraction
+            return match self.next_word() {
+                Some(Word::Unit(Unit::Seconds(1))) => self.add_relative(Unit::Seconds(1), n.value, nanos),
+                _ => Err(DateError),
+            };
+        }
+
+        match *self.tokens {
+            [Token::Colon, ..] => {
+                self.tokens = &self.tokens[1..];
+                self.time(n)
+            },
+            [Token::Slash, ..] => {
+                self.tokens = &self.tokens[1..];
+                let second = self.unsigned()?;
+                if !self.skip(Token::Slash) {
+                    return self.set_date(None, n.value, second.value);
+                }
+                let third = self.unsigned()?;
+                if n.digits >= 4 {
+                    self.set_date(Some((n.value, n.digits)), second.value, third.value)
+                } else {
+                    self.set_date(Some((third.value, third.digits)), n.value, second.value)
+                }
+            },
+            [Token::Number(month), Token::Number(day), ..] if month.signed && day.signed && month.nanos.is_none() && day.nanos.is_none() => {
+                // like 2024-03-15, where the month and day look negative, maybe
+                // with a T and a time after it
+                self.tokens = &self.tokens[2..];
+                match self.tokens {
+                    [Token::Word(Word::T), Token::Number(_), Token::Colon, ..] => self.tokens = &self.tokens[1..],
+                    [Token::Word(Word::T), ..] => return Err(DateError),
+                    _ => {},
+                }
+                self.set_date(Some((n.value, n.digits)), -month.value, -day.value)
+            },
+            [Token::Word(Word::Month(month)), ..] => {
+                self.tokens = &self.tokens[1..];
+                match self.tokens {
+                    [Token::Number(year), ..] if year.nanos.is_none() => {
+                        self.tokens = &self.tokens[1..];
+                        self.set_date(Some((year.value.abs(), year.digits)), month, n.value)
+                    },
+                    _ => self.set_date(None, month, n.value),
+                }
+            },
+            [Token::Word(Word::Weekday(weekday)), ..] => {
+                self.tokens = &self.tokens[1..];
+                self.set_weekday(n.value, weekday)
+            },
+            [Token::Word(Word::Unit(unit)), ..] => {
+                self.tokens = &self.tokens[1..];
+                self.relative(n, unit)
+            },
+            [Token::Word(Word::Meridian(meridian)), ..] => {
+                self.tokens = &self.tokens[1..];
+                self.set_time(meridian_hour(n.value, meridian)?, 0, 0, 0)
+            },
+            _ => {
+                self.plain_number(n);
+                // like 20240315 +1 day
+                self.signed_relative()?;
+                Ok(())
+            },
+        }
+    }
+
+    /// Helper method to read a number that's on its own. It's a year if it comes
+    /// after a date without one, a date if it's long enough, and otherwise a time.
+    fn plain_number(&mut self, n: Number) {
+        let items = &mut self.items;
+        if items.dates > 0 && items.year.is_none() && !items.relatives && (items.times > 0 || n.digits > 2) {
+            items.year = Some((n.value, n.digits));
+        } else if n.digits > 4 {
+            items.dates += 1;
+            items.year = Some((n.value / 10000, n.digits - 4));
+            items.date = Some((n.value / 100 % 100, n.value % 100));
+        } else {
+            let (hour, minute) = if n.digits <= 2 { (n.value, 0) } else { (n.value / 100, n.value % 100) };
+            items.times += 1;
+            items.time = Some((hour, minute, 0, 0));
+        }
+    }
+
+    /// Helper method to read the rest of a time of day, after the hour and a colon.
+    fn time(&mut self, hour: Number) -> Result<(), DateError> {
+        let minute = self.unsigned()?;
+        let (second, nanos) = if self.skip(Token::Colon) {
+            match self.next()? {
+                Token::Number(n) if !n.signed => (n.value, n.nanos.unwrap_or(0)),
+                _ => return Err(DateError),
+            }
+        } else {
+            (0, 0)
+        };
+
+        match self.tokens {
+            [Token::Word(Word::Meridian(meridian)), ..] => {
+                self.tokens = &self.tokens[1..];
+                self.set_time(meridian_hour(hour.value, *meridian)?, minute.value, second, nanos)
+            },
+            [Token::Number(n), ..] if n.signed => {
+                self.tokens = &self.tokens[1..];
+                let offset = self.zone_offset(*n)?;
+                self.set_zone(offset)?;
+                self.set_time(hour.value, minute.value, second, nanos)
+            },
+            _ => self.set_time(hour.value, minute.value, second, nanos),
+        }
+    }
+
+    /// Helper method to read an offset from UTC like `+05`, `+0530`, or `+05:30`,
+    /// after its first number. Returns it in seconds.
+    fn zone_offset(&mut self, n: Number) -> Result<i32, DateError> {
+        if n.nanos.is_some() {
+            return Err(DateError);
+        }
+        let hours = n.value.abs();
+        let minutes = if self.skip(Token::Colon) {
+            hours.saturating_mul(60).saturating_add(self.unsigned()?.value)
+        } else if n.digits <= 2 {
+            hours * 60
+        } else {
+            hours / 100 * 60 + hours % 100
+        };
+        if minutes > 24 * 60 {
+            return Err(DateError);
+        }
+        Ok(n.value.signum() as i32 * minutes as i32 * 60)
+    }
+
+    /// Helper method to add `count` of `unit`, or take it away if `ago` comes
+    /// after.
+    fn relative(&mut self, count: Number, unit: Unit) -> Result<(), DateError> {
+        let sign = match self.tokens {
+            [Token::Word(Word::Ago(sign)), ..] => {
+                self.tokens = &self.tokens[1..];
+                *sign
+            },
+            _ => 1,
+        };
+        self.add_count(count, unit, sign)
+    }
+
+    /// Helper method to read a signed number and a unit, if they come next. They
+    /// follow a time zone or a number, and can't have `ago` after them. Returns
+    /// whether they were there.
+    fn signed_relative(&mut self) -> Result<bool, DateError> {
+        match *self.tokens {
+            [Token::Number(count), Token::Word(Word::Unit(unit)), ..] if count.signed => {
+                self.tokens = &self.tokens[2..];
+                self.add_count(count, unit, 1)?;
+                if matches!(self.tokens.first(), Some(Token::Word(Word::Ago(_)))) {
+                    return Err(DateError);
+                }
+                Ok(true)
+            },
+            _ => Ok(false),
+        }
+    }
+
+    /// Helper method to add `count` of `unit`, times `sign`. Only seconds can
+    /// have a fraction.
+    fn add_count(&mut self, count: Number, unit: Unit, sign: i64) -> Result<(), DateError> {
+        let nanos = match (count.nanos, unit) {
+            (Some(nanos), Unit::Seconds(1)) => nanos,
+            (Some(_), _) => return Err(DateError),
+            (None, _) => 0,
+        };
+        let count = count.value.checked_mul(sign).ok_or(DateError)?;
+        self.add_relative(unit, count, nanos * sign)
+    }
+
+    /// Helper method to add to the relative items.
+    fn add_relative(&mut self, unit: Unit, count: i64, nanos: i64) -> Result<(), DateError> {
+        let relative = &mut self.items.relative;
+        let (total, factor) = match unit {
+            Unit::Years => (&mut relative.years, 1),
+            Unit::Months => (&mut relative.months, 1),
+            Unit::Days(days) => (&mut relative.days, days),
+            Unit::Seconds(seconds) => (&mut relative.seconds, seconds),
+        };
+        *total = count.checked_mul(factor).and_then(|count| total.checked_add(count)).ok_or(DateError)?;
+        relative.nanos += nanos;
+        self.items.relatives = true;
+        Ok(())
+    }
+
+    fn set_date(&mut self, year: Option<(i64, usize)>, month: i64, day: i64) -> Result<(), DateError> {
+        self.items.dates += 1;
+        if year.is_some() {
+            self.items.year = year;
+        }
+        self.items.date = Some((month, day));
+        Ok(())
+    }
+
+    fn set_time(&mut self, hour: i64, minute: i64, second: i64, nanos: i64) -> Result<(), DateError> {
+        self.items.times += 1;
+        self.items.time = Some((hour, minute, second, nanos));
+        Ok(())
+    }
+
+    fn set_weekday(&mut self, ordinal: i64, weekday: i64) -> Result<(), DateError> {
+        self.items.weekdays += 1;
+        self.items.weekday = Some((ordinal, weekday));
+        Ok(())
+    }
+
+    fn set_zone(&mut self, offset: i32) -> Result<(), DateError> {
+        self.items.zone = Some(offset);
+        self.set_zone_count()
+    }
+
+    fn set_zone_count(&mut self) -> Result<(), DateError> {
+        self.items.zones += 1;
+        Ok(())
+    }
+
+    /// Helper method to take the next token, which has to be there.
+    fn next(&mut self) -> Result<Token, DateError> {
+        let (&token, rest) = self.tokens.split_first().ok_or(DateError)?;
+        self.tokens = rest;
+        Ok(token)
+    }
+
+    /// Helper method to take the next token, if it's a word.
+    fn next_word(&mut self) -> Option<Word> {
+        match self.tokens {
+            [Token::Word(word), rest @ ..] => {
+                self.tokens = rest;
+                Some(*word)
+            },
+            _ => None,
+        }
+    }
+
+    /// Helper method to take the next token, which has to be a whole number
+    /// without a sign.
+    fn unsigned(&mut self) -> Result<Number, DateError> {
+        match self.next()? {
+            Token::Number(n) if !n.signed && n.nanos.is_none() => Ok(n),
+            _ => Err(DateError),
+        }
+    }
+
+    /// Helper method to take the next token if it's `token`. Returns whether it was.
+    fn skip(&mut self, token: Token) -> bool {
+        match self.tokens.split_first() {
+            Some((&first, rest)) if first == token => {
+                self.tokens = rest;
+                true
+            },
+            _ => false,
+        }
+    }
+}
+
+/// Helper method to get the hour on a 24-hour clock, from one on a 12-hour clock
+/// and `am` or `pm`.
+fn meridian_hour(hour: i64, meridian: i64) -> Result<i64, DateError> {
+    if (1..=12).contains(&hour) {
+        Ok(hour % 12 + meridian)
+    } else {
+        Err(DateError)
+    }
+}
+
+/// Helper method to work out the time the items of a date say.
+fn resolve(items: &Items, now: Time, zone: &TimeZone) -> Result<Time, DateError> {
+    if items.dates > 1 || items.times > 1 || items.weekdays > 1 || items.zones > 1 {
+        return Err(DateError);
+    }
+    // what isn't given comes from the local time, even in another time zone
+    let local_zone = zone;
+    let now = local_zone.at(now);
+    let fixed;
+    let zone = match items.zone {
+        Some(offset) => {
+            fixed = TimeZone::fixed(offset, "");
+            &fixed
+        },
+        None => local_zone,
+    };
+
+    let year = match items.year {
+        // two digits are 1969 to 2068
+        Some((year, 2)) if year >= 0 => year + if year < 69 { 2000 } else { 1900 },
+        Some((year, _)) => year,
+        None => now.year,
+    };
+    let (month, day) = items.date.unwrap_or((now.month.into(), now.day.into()));
+    if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month as u32).into() {
+        return Err(DateError);
+    }
+    let keep_time = items.relatives && items.dates == 0 && items.weekdays == 0;
+    let (hour, minute, second, nanos) = match items.time {
+        Some(time) => time,
+        None if keep_time => (now.hour.into(), now.minute.into(), now.second.into(), now.time.nanos.into()),
+        None => (0, 0, 0, 0),
+    };
+    if !(0..24).contains(&hour) || !(0..60).contains(&minute) || !(0..60).contains(&second) {
+        return Err(DateError);
+    }
+
+    // like mktime(), whether daylight saving time is on comes from a local name,
+    // or from now if nothing says when
+    let dst = items.dst.or((items.dates == 0 && items.weekdays == 0 && items.times == 0).then_some(now.dst));
+    let first_dst = dst.filter(|_| items.zone.is_none());
+    let clock = |day: i64, months: i64, years: i64| {
+        local_seconds(year.checked_add(years)?, month.checked_add(months)?, day, hour * 3600 + minute * 60 + second)
+    };
+    let mut day = day;
+    let mut first = clock(day, 0, 0).ok_or(DateError)?;
+    let mut secs = zone.from_local(first, first_dst).ok_or(DateError)?;
+    if let (Some((ordinal, weekday)), 0) = (items.weekday, items.dates) {
+        let today = i64::from(zone.at(Time::new(secs, 0)).weekday);
+        let weeks = ordinal - i64::from(ordinal > 0 && today != weekday);
+        day = weeks.checked_mul(7).and_then(|days| days.checked_add((weekday - today + 7) % 7 + day)).ok_or(DateError)?;
+        first = clock(day, 0, 0).ok_or(DateError)?;
+        secs = zone.from_local(first, first_dst).ok_or(DateError)?;
+    }
+    let relative = items.relative;
+    if relative.years != 0 || relative.months != 0 || relative.days != 0 {
+        let day = day.checked_add(relative.days).ok_or(DateError)?;
+        let later = clock(day, relative.months, relative.years).ok_or(DateError)?;
+        // daylight saving time stays on or off, and if it can't, the clock is
+        // read with the offset it had
+        let dst = dst.filter(|&dst| local_zone.from_local(first, Some(dst)).is_some());
+        let kept = dst.filter(|&dst| local_zone.from_local(later, Some(dst)).is_some());
+        secs = zone.from_local(later, kept.filter(|_| items.zone.is_none())).ok_or(DateError)?;
+        if let (Some(dst), None) = (dst, kept) {
+            let offset = |local: i64, dst| local_zone.from_local(local, dst).map(|utc| i64::from(local_zone.at(Time::new(utc, 0)).offset));
+            secs -= offset(first, Some(dst)).zip(offset(later, None)).map_or(0, |(then, now)| then - now);
+        }
+    }
+    let secs = secs.checked_add(relative.seconds).ok_or(DateError)?;
+    add(Time::new(secs, 0), 0, nanos + relative.nanos)
+}
+
+/// Helper method to get the seconds since 1970-01-01 00:00:00 on a clock,
+/// for a date that can be past the end of its month, or a month past the end of
+/// its year. Returns `None` if it's too far away.
+///
+/// # Arguments
+///
+/// * `year` - The year.
+/// * `month` - The month, where 13 is January of the next year.
+/// * `day` - The day of the month, where 0 is the last day of the month before.
+/// * `seconds` - Seconds since midnight.
+fn local_seconds(year: i64, month: i64, day: i64, seconds: i64) -> Option<i64> {
+    let months = year.checked_mul(12)?.checked_add(month - 1)?;
+    let (year, month) = (months.div_euclid(12), months.rem_euclid(12) + 1);
+    if year.abs() > MAX_YEAR {
+        return None;
+    }
+    let days = days_from_civil(year, month as u32, 1).checked_add(day - 1)?;
+    let local = i128::from(days) * i128::from(SECONDS_PER_DAY) + i128::from(seconds);
+    // leave room for the offset
+    i64::try_from(local).ok().filter(|local| local.unsigned_abs() < i64::MAX as u64 / 2)
+}
+
+/// Helper method to add seconds and nanoseconds to a time, which can be negative.
+fn add(time: Time, secs: i64, nanos: i64) -> Result<Time, DateError> {
+    let nanos = i64::from(time.nanos) + nanos;
+    let secs = time.secs.checked_add(secs).and_then(|s| s.checked_add(nanos.div_euclid(1_000_000_000))).ok_or(DateError)?;
+    Ok(Time::new(secs, nanos.rem_euclid(1_000_000_000) as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2026-10-18 12:34:56.789 UTC, a Sunday.
+    const NOW: Time = Time::new(1792326896, 789_000_000);
+
+    /// Read `text` at `NOW`, and show it as seconds since the epoch.
+    fn parse(text: &str, zone: &TimeZone) -> Option<(i64, u32)> {
+        parse_date(text, NOW, zone).ok().map(|time| (time.secs, time.nanos))
+    }
+
+    #[test]
+    fn dates() {
+        // from `TZ=UTC date -d TEXT +%s`
+        let utc = TimeZone::utc();
+        let cases = [
+            ("2024-03-15", 1710460800),
+            ("2024-3-5", 1709596800),
+            ("20240315", 1710460800),
+            ("3/15/2024", 1710460800),
+            ("3/15/24", 1710460800),
+            ("2024/3/15", 1710460800),
+            ("15 March 2024", 1710460800),
+            ("15-Mar-2024", 1710460800),
+            ("Mar-15-2024", 1710460800),
+            ("March 15 2024", 1710460800),
+            ("Mar. 15, 2024", 1710460800),
+            ("Sept 5 2024", 1725494400),
+            ("24-03-15", 1710460800),
+            ("0024-01-01", -61409836800),
+            ("Mar 15", 1773532800),
+            ("1/2", 1767312000),
+            ("", 1792281600),
+            ("sunday, 2024-01-01", 1704067200),
+        ];
+        for (text, secs) in cases {
+            assert_eq!(parse(text, &utc), Some((secs, 0)), "{}", text);
+        }
+    }
+
+    #[test]
+    fn times() {
+        let utc = TimeZone::utc();
+        let cases = [
+            ("2024-03-15 10:00", 1710496800, 0),
+            ("2024-03-15T10:00:30", 1710496830, 0),
+            ("2024-03-15 T10:00", 1710496800, 0),
+            ("2024-03-15 10:00:30.5", 1710496830, 500_000_000),
+            ("2024-03-15 10:00:30,123456789123", 1710496830, 123_456_789),
+            ("2024-03-15 1030", 1710498600, 0),
+            ("2024-03-15 10", 1710496800, 0),
+            ("2024-03-15 3pm", 1710514800, 0),
+            ("2024-03-15 12am", 1710460800, 0),
+            ("2024-03-15 12:30 pm", 1710505800, 0),
+            ("10 a.m. 2024-03-15", 1710496800, 0),
+            ("10:00 2024-03-15", 1710496800, 0),
+            ("1/2/2024 3:04", 1704164640, 0),
+            // today
+            ("10:00", 1792317600, 0),
+            ("@1700000000", 1700000000, 0),
+            ("@ -1.5", -2, 500_000_000),
+        ];
+        for (text, secs, nanos) in cases {
+            assert_eq!(parse(text, &utc), Some((secs, nanos)), "{}", text);
+        }
+    }
+
+    #[test]
+    fn zones() {
+        let utc = TimeZone::utc();
+        let cases = [
+            ("2024-01-01 10:00Z", 1704103200),
+            ("2024-01-01 10:00 EST", 1704121200),
+            ("2024-01-01 10:00 cest", 1704096000),
+            ("2024-01-01 10:00 UTC+2", 1704096000),
+            ("2024-01-01 10:00 GMT-3:30", 1704115800),
+            ("2024-01-01 10:00 EST +1", 1704117600),
+            ("2024-01-01 10:00 +0530", 1704083400),
+            ("2024-01-01 10:00 +05:30", 1704083400),
+            ("2024-01-01 10:00 -5", 1704121200),
+            ("2024-01-01 10:00 +2400", 1704016800),
+            ("2024-01-01 10:00 A", 1704099600),
+            ("2024-01-01 10:00 y", 1704146400),
+            ("2024-01-01 10:00 T", 1704128400),
+            ("3/15/24 T10:00", 1710522000),
+            // a signed number and a unit after a time zone aren't an offset
+            ("2024-01-01 EST -1 month", 1701406800),
+            // the +2 is an offset, so it's one day
+            ("2024-01-01 10:00 +2 days", 1704182400),
+            ("TZ=\"EST5EDT\" 2024-07-01 10:00", 1719842400),
+            ("TZ=\"EST5EDT\" 2024-07-01 10:00 UTC", 1719828000),
+        ];
+        for (text, secs) in cases {
+            assert_eq!(parse(text, &utc), Some((secs, 0)), "{}", text);
+        }
+
+        // the earlier of two, when the clocks go back
+        let eastern = TimeZone::from_tz(Some("EST5EDT,M3.2.0,M11.1.0"));
+        assert_eq!(parse("2024-11-03 01:30", &eastern), Some((1730611800, 0)));
+        assert_eq!(parse("2024-11-03 01:30 EST", &eastern), Some((1730615400, 0)));
+        assert_eq!(parse("2024-03-10 02:30", &eastern), None);
+        // its own names say whether daylight saving time is on
+        assert_eq!(parse("2024-11-03 01:30 EDT", &eastern), Some((1730611800, 0)));
+        assert_eq!(parse("2024-07-01 10:00 EST", &eastern), None);
+        assert_eq!(parse("2024-01-01 10:00 EST -1", &eastern), None);
+        // and so does now, so a month later is read as EDT, like GNU does
+        assert_eq!(parse("+1 month", &eastern), Some((1795005296, 789_000_000)));
+    }
+
+    #[test]
+    fn relative() {
+        let utc = TimeZone::utc();
+        let cases = [
+            ("now", 1792326896, 789_000_000),
+            ("today", 1792326896, 789_000_000),
+            ("tomorrow", 1792413296, 789_000_000),
+            ("1 hour ago", 1792323296, 789_000_000),
+            ("twelfth day", 1793363696, 789_000_000),
+            ("2024-01-31 +1 month", 1709337600, 0),
+            ("2024-02-29 1 year", 1740787200, 0),
+            ("2024-01-01 3 days hence", 1704326400, 0),
+            ("2024-01-01 - 1 day", 1703980800, 0),
+            ("2024-01-01 +1 day ago", 1703980800, 0),
+            ("1 week ago 2024-01-01", 1703462400, 0),
+            ("2024-01-01 fortnight", 1705276800, 0),
+            ("2024-01-01 last year", 1672531200, 0),
+            ("2024-01-01 2 days 3 hours", 1704250800, 0),
+            ("2024-01-01 5 -3 days", 1703826000, 0),
+            ("2024-01-01 -1.5 seconds", 1704067198, 500_000_000),
+            ("2024-01-01 second", 1704067201, 0),
+            ("tomorrow 10:00", 1792404000, 0),
+            ("10:00 yesterday", 1792231200, 0),
+        ];
+        for (text, secs, nanos) in cases {
+            assert_eq!(parse(text, &utc), Some((secs, nanos)), "{}", text);
+        }
+    }
+
+    #[test]
+    fn weekdays() {
+        // today is a Sunday
+        let utc = TimeZone::utc();
+        let cases = [
+            ("sunday", 1792281600),
+            ("next sunday", 1792886400),
+            ("mon", 1792368000),
+            ("next mon", 1792368000),
+            ("last mon", 1791763200),
+            ("2 mon", 1792972800),
+            ("Tues", 1792454400),
+            ("monday 10:00", 1792404000),
+            ("next monday 10:00 +1 day", 1792486800),
+            // the date wins
+            ("2024-01-01 friday", 1704067200),
+        ];
+        for (text, secs) in cases {
+            assert_eq!(parse(text, &utc), Some((secs, 0)), "{}", text);
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        let utc = TimeZone::utc();
+        let cases = [
+            "noon", "midnight", "in 3 days", "2024-02-30", "2024-13-01", "2024-01-01 24:00",
+            "2024-01-01 10:61", "2024-01-01 23:59:60", "2024-01-01 0am", "2024-01-01 13pm",
+            "2024-01-01 a.m.", "2024-01-01, 10:00", "10:00 10:00", "2024-01-01 2024-01-01",
+            "monday tuesday", "UTC EST", "Mar", "Mar-15", "mar 2024", "2024 mar 15", "jan 1 69",
+            "2024-01-01 -5", "2024-01-01 next", "2024-01-01 ago", "1 day ago ago",
+            "2024-01-01 10:00 +25", "2024-01-01 1.5 days", "@5 days", "@", "10:00 TZ=\"UTC\"",
+            "TZ=\"UTC", "99999999999999999999", "2024-01-01 99999999999999 years", "10:00 €",
+            "2024-01-01 10:00 J", "2024-01-01 T", "2024-01-01T10", "2024-01-01 10:00 CEST +1",
+            "2024-01-01 EST -1 day ago", "20240101 -1 day ago",
+        ];
+        for text in cases {
+            assert_eq!(parse(text, &utc), None, "{}", text);
+        }
+        assert_eq!(DateError.to_error("noon").to_string(), "invalid date 'noon'");
+    }
+}
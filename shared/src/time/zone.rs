@@ -0,0 +1,503 @@
+//! Time zones, from the `TZ` environment variable and the system's zoneinfo files.
+//!
+//! `TZ` is read the way the C library reads it:
+//!
+//! * Not set: the zone in `/etc/localtime`, or UTC if there isn't one.
+//! * Empty: UTC.
+//! * A name, maybe with a `:` in front, like `Europe/Paris`: the zoneinfo file
+//!   with that name, under `TZDIR` or `/usr/share/zoneinfo`. A path starting with
+//!   `/` is used as it is.
+//! * Otherwise, a POSIX rule, like `EST5EDT,M3.2.0,M11.1.0`: a name and an offset
+//!   west of UTC, then maybe a name for daylight saving time, its offset, and
+//!   the rules for when it starts and ends.
+//!
+//! A `TZ` that's none of those is UTC, with no abbreviation, like the C library.
+//!
+//! Zoneinfo files are in the TZif format (RFC 8536). They list every change of
+//! offset, and end with a POSIX rule for the times after the last one.
+
+use std::path::{Path, PathBuf};
+
+use super::{days_from_civil, days_in_month, is_leap, DateTime, Time, SECONDS_PER_DAY};
+
+/// Where zoneinfo files are, if `TZDIR` doesn't say.
+const ZONEINFO: &str = "/usr/share/zoneinfo";
+
+/// When daylight saving time starts and ends in a rule with no dates, like
+/// `EST5EDT`: the second Sunday in March, and the first Sunday in November.
+const DEFAULT_RULE: &str = "M3.2.0,M11.1.0";
+
+/// A time zone: the offsets from UTC it uses, and when.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeZone {
+    /// Times when the offset changes, in seconds since the epoch.
+    transitions: Vec<i64>,
+    /// Index in `types` for the time starting at each of `transitions`.
+    transition_types: Vec<usize>,
+    /// The kinds of local time. The first one is for times before the first
+    /// transition.
+    types: Vec<LocalType>,
+    /// Rule for the times after the last transition.
+    rule: Option<Rule>,
+}
+
+/// One kind of local time, like Central European Summer Time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LocalType {
+    /// Seconds east of UTC.
+    offset: i32,
+    dst: bool,
+    /// Abbreviation, like `CEST`.
+    abbr: String,
+}
+
+/// A POSIX `TZ` rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    standard: LocalType,
+    /// Daylight saving time, and when it starts and ends.
+    dst: Option<(LocalType, Transition, Transition)>,
+}
+
+/// When daylight saving time starts or ends, in local time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Transition {
+    day: RuleDay,
+    /// Seconds after midnight. It can be negative, or more than a day.
+    time: i64,
+}
+
+/// A day of the year, in one of the ways a POSIX rule can give it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RuleDay {
+    /// `Jn`: day 1 to 365, not counting February 29.
+    Julian(u32),
+    /// `n`: day 0 to 365, counting February 29.
+    Zero(u32),
+    /// `Mm.w.d`: weekday `d` (0 is Sunday) of week `w` (5 is the last) of month `m`.
+    Month(u32, u32, u32),
+}
+
+impl TimeZone {
+    /// Coordinated Universal Time.
+    pub fn utc() -> TimeZone {
+        TimeZone::fixed(0, "UTC")
+    }
+
+    /// The time zone that `TZ` says, like the C library uses.
+    pub fn from_env() -> TimeZone {
+        TimeZone::from_tz(std::env::var("TZ").ok().as_deref())
+    }
+
+    /// The time zone for a value of `TZ`.
+    ///
+    /// # Arguments
+    ///
+    /// * `tz` - The value, or `None` if `TZ` isn't set.
+    pub fn from_tz(tz: Option<&str>) -> TimeZone {
+        let Some(tz) = tz else {
+            return TimeZone::from_file(Path::new("/etc/localtime")).unwrap_or_else(TimeZone::utc);
+        };
+        if tz.is_empty() {
+            return TimeZone::utc();
+        }
+        let name = tz.strip_prefix(':').unwrap_or(tz);
+        if let Some(zone) = TimeZone::named(name) {
+            return zone;
+        }
+        match parse_rule(tz) {
+            Some(rule) => TimeZone { transitions: Vec::new(), transition_types: Vec::new(), types: vec![rule.standard.clone()], rule: Some(rule) },
+            None => TimeZone::fixed(0, ""),
+        }
+    }
+
+    /// The time zone in the zoneinfo file called `name`, like `Europe/Paris`.
+    /// Returns `None` if there isn't one.
+    pub fn named(name: &str) -> Option<TimeZone> {
+        if name.is_empty() || name.split('/').any(|part| part == "..") {
+            return None;
+        }
+        let path = if name.starts_with('/') {
+            PathBuf::from(name)
+        } else {
+            let dir = std::env::var_os("TZDIR").filter(|dir| !dir.is_empty()).unwrap_or_else(|| ZONEINFO.into());
+            Path::new(&dir).join(name)
+        };
+        TimeZone::from_file(&path)
+    }
+
+    /// A time zone that's always `offset` seconds east of UTC, like `+0530`.
+    pub fn fixed(offset: i32, abbr: &str) -> TimeZone {
+        let standard = LocalType { offset, dst: false, abbr: abbr.to_owned() };
+        TimeZone { transitions: Vec::new(), transition_types: Vec::new(), types: vec![standard], rule: None }
+    }
+
+    /// What a clock in this time zone shows at `time`.
+    pub fn at(&self, time: Time) -> DateTime {
+        let local = self.local_type(time.secs);
+        DateTime::new(time, local.offset, local.dst, &local.abbr)
+    }
+
+    /// Find the time when a clock in this time zone shows `local`, like
+    /// `mktime()`. When the clocks go back, and it shows the same thing twice,
+    /// the first one is used, unless `dst` picks the other. Returns `None` if it
+    /// never shows it, since it was skipped when the clocks went forward, or
+    /// never shows it with daylight saving time on or off like `dst` says.
+    ///
+    /// # Arguments
+    ///
+    /// * `local` - The time on the clock, in seconds since 1970-01-01 00:00:00.
+    /// * `dst` - Whether daylight saving time has to be on, if it matters.
+    pub fn from_local(&self, local: i64, dst: Option<bool>) -> Option<i64> {
+        // the offset is the one on either side of a change, or the one that's
+        // in effect all day
+        let mut candidates = [-SECONDS_PER_DAY, 0, SECONDS_PER_DAY]
+            .map(|delta| i64::from(self.local_type(local.saturating_add(delta)).offset));
+        candidates.sort_unstable();
+        candidates.iter().rev()
+            .map(|offset| local - offset)
+            .filter(|&utc| {
+                let local_type = self.local_type(utc);
+                i64::from(local_type.offset) == local - utc && dst.is_none_or(|dst| local_type.dst == dst)
+            })
+            .min()
+    }
+
+    /// Helper method to get the kind of local time at `secs`.
+    fn local_type(&self, secs: i64) -> &LocalType {
+        match self.transitions.partition_point(|&at| at <= secs) {
+            i if i == self.transitions.len() && self.rule.is_some() => self.rule.as_ref().unwrap().local_type(secs),
+            0 => &self.types[0],
+            i => &self.types[self.transition_types[i - 1]],
+        }
+    }
+
+    /// Helper method to read a zoneinfo file.
+    fn from_file(path: &Path) -> Option<TimeZone> {
+        parse_tzif(&std::fs::read(path).ok()?)
+    }
+}
+
+impl Rule {
+    /// Helper method to get the kind of local time at `secs`.
+    fn local_type(&self, secs: i64) -> &LocalType {
+        let Some((dst, start, end)) = &self.dst else {
+            return &self.standard;
+        };
+        // the year is worked out in standard time, which is close enough, since
+        // daylight saving time doesn't start or end at New Year
+        let year = super::civil_from_days((secs + i64::from(self.standard.offset)).div_euclid(SECONDS_PER_DAY)).0;
+        let starts = start.local(year) - i64::from(self.standard.offset);
+        let ends = end.local(year) - i64::from(dst.offset);
+        let in_dst = if starts < ends {
+            starts <= secs && secs < ends
+        } else {
+            // the southern hemisphere, where it's over New Year
+            !(ends <= secs && secs < starts)
+        };
+        if in_dst { dst } else { &self.standard }
+    }
+}
+
+impl Transition {
+    /// Helper method to get the local time of this transition in `year`, in seconds
+    /// since 1970-01-01 00:00:00.
+    fn local(&self, year: i64) -> i64 {
+        let days = match self.day {
+            RuleDay::Julian(n) => {
+                let day = i64::from(n) - 1;
+                days_from_civil(year, 1, 1) + day + i64::from(is_leap(year) && n >= 60)
+            },
+            RuleDay::Zero(n) => days_from_civil(year, 1, 1) + i64::from(n),
+            RuleDay::Month(month, week, weekday) => {
+                let first = days_from_civil(year, month, 1);
+                // 1970-01-01 was a Thursday
+                let first_weekday = (first + 4).rem_euclid(7) as u32;
+                let mut day = 1 + (weekday + 7 - first_weekday) % 7 + (week - 1) * 7;
+                while day > days_in_month(year, month) {
+                    day -= 7;
+                }
+                first + i64::from(day) - 1
+            },
+        };
+        days * SECONDS_PER_DAY + self.time
+    }
+}
+
+/// Helper method to read a TZif file. Returns `None` if it isn't one.
+fn parse_tzif(data: &[u8]) -> Option<TimeZone> {
+    let header = Header::read(data)?;
+    let (header, data, time_size) = if header.version >= b'2' {
+        // skip the old data with 32 bit times, and use the second part
+        let rest = data.get(44 + header.data_len(4)..)?;
+        (Header::read(rest)?, rest, 8)
+    } else {
+        (header, data, 4)
+    };
+
+    let mut at = 44;
+    let mut take = |len: usize| -> Option<&[u8]> {
+        let bytes = data.get(at..at + len)?;
+        at += len;
+        Some(bytes)
+    };
+    let transitions: Vec<i64> = take(header.times * time_size)?.chunks(time_size).map(|chunk| match time_size {
+        4 => i64::from(i32::from_be_bytes(chunk.try_into().unwrap())),
+        _ => i64::from_be_bytes(chunk.try_into().unwrap()),
+    }).collect();
+    let transition_types: Vec<usize> = take(header.times)?.iter().map(|&i| usize::from(i)).collect();
+    let raw_types: Vec<&[u8]> = take(header.types * 6)?.chunks(6).collect();
+    let abbrs = take(header.chars)?;
+    take(header.leaps * (time_size + 4) + header.std_flags + header.ut_flags)?;
+
+    let types: Vec<LocalType> = raw_types.iter().map(|raw| {
+        let start = usize::from(raw[5]).min(abbrs.len());
+        let end = abbrs[start..].iter().position(|&b| b == 0).map_or(abbrs.len(), |len| start + len);
+        LocalType {
+            offset: i32::from_be_bytes(raw[..4].try_into().unwrap()),
+            dst: raw[4] != 0,
+            abbr: String::from_utf8_lossy(&abbrs[start..end]).into_owned(),
+        }
+    }).collect();
+    if types.is_empty() || transition_types.iter().any(|&i| i >= types.len()) {
+        return None;
+    }
+
+    // the footer is a POSIX rule between newlines, for after the last transition
+    let rule = match data.get(at..) {
+        Some([b'\n', footer @ ..]) if time_size == 8 => {
+            let end = footer.iter().position(|&b| b == b'\n')?;
+            std::str::from_utf8(&footer[..end]).ok().and_then(parse_rule)
+        },
+        _ => None,
+    };
+    Some(TimeZone { transitions, transition_types, types, rule })
+}
+
+/// The counts at the start of each part of a TZif file.
+struct Header {
+    version: u8,
+    ut_flags: usize,
+    std_flags: usize,
+    leaps: usize,
+    times: usize,
+    types: usize,
+    chars: usize,
+}
+
+impl Header {
+    /// Helper method to read a header from the start of `data`.
+    fn read(data: &[u8]) -> Option<Header> {
+        if data.get(..4)? != b"TZif" {
+            return None;
+        }
+        let count = |i: usize| -> Option<usize> {
+            let bytes = data.get(20 + 4 * i..24 + 4 * i)?;
+            usize::try_from(u32::from_be_bytes(bytes.try_into().ok()?)).ok()
+        };
+        Some(Header {
+            version: *data.get(4)?,
+            ut_flags: count(0)?,
+            std_flags: count(1)?,
+            leaps: count(2)?,
+            times: count(3)?,
+            types: count(4)?,
+            chars: count(5)?,
+        })
+    }
+
+    /// Helper method to get the size of the data after the header, when times
+    /// take `time_size` bytes.
+    fn data_len(&self, time_size: usize) -> usize {
+        self.times * (time_size + 1) + self.types * 6 + self.chars + self.leaps * (time_size + 4)
+            + self.std_flags + self.ut_flags
+    }
+}
+
+/// Helper method to read a POSIX `TZ` rule, like `CET-1CEST,M3.5.0,M10.5.0/3`.
+/// A name on its own, like `GMT`, is that name with no offset. Returns `None`
+/// if it isn't a rule.
+fn parse_rule(text: &str) -> Option<Rule> {
+    let (name, rest) = zone_name(text)?;
+    if rest.is_empty() {
+        return Some(Rule { standard: LocalType { offset: 0, dst: false, abbr: name.to_owned() }, dst: None });
+    }
+    let (west, rest) = offset(rest)?;
+    let standard = LocalType { offset: -west, dst: false, abbr: name.to_owned() };
+    if rest.is_empty() {
+        return Some(Rule { standard, dst: None });
+    }
+
+    let (dst_name, rest) = zone_name(rest)?;
+    // daylight saving time is an hour ahead, unless it says otherwise
+    let (dst_west, rest) = match rest.chars().next() {
+        Some(',') | None => (west - 3600, rest),
+        _ => offset(rest)?,
+    };
+    let rules = match rest {
+        "" => DEFAULT_RULE,
+        _ => rest.strip_prefix(',')?,
+    };
+    let (start, end) = rules.split_once(',')?;
+    let dst = LocalType { offset: -dst_west, dst: true, abbr: dst_name.to_owned() };
+    Some(Rule { standard, dst: Some((dst, transition(start)?, transition(end)?)) })
+}
+
+/// Helper method to read a zone name from the start of `text`: three or more
+/// letters, or anything in `<>`, like `<+0530>`. Returns the name, and what's
+/// after it.
+fn zone_name(text: &str) -> Option<(&str, &str)> {
+    let (name, rest) = match text.strip_prefix('<') {
+        Some(quoted) => {
+            let end = quoted.find('>')?;
+            (&quoted[..end], &quoted[end + 1..])
+        },
+        None => {
+            let end = text.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(text.len());
+            text.split_at(end)
+        },
+    };
+    (name.len() >= 3).then_some((name, rest))
+}
+
+/// Helper method to read an offset like `-5:30` from the start of `text`, in
+/// seconds. Returns it, and what's after it.
+fn offset(text: &str) -> Option<(i32, &str)> {
+    let (seconds, rest) = clock_time(text, 24)?;
+    Some((i32::try_from(seconds).ok()?, rest))
+}
+
+/// Helper method to read a time like `[+|-]hh[:mm[:ss]]` from the start of
+/// `text`, in seconds. Returns it, and what's after it.
+///
+/// # Arguments
+///
+/// * `text` - Where to read it from.
+/// * `max_hours` - The most hours there can be.
+fn clock_time(text: &str, max_hours: i64) -> Option<(i64, &str)> {
+    let (sign, mut rest) = match text.as_bytes().first() {
+        Some(b'-') => (-1, &text[1..]),
+        Some(b'+') => (1, &text[1..]),
+        _ => (1, text),
+    };
+    let mut seconds = 0;
+    for (i, (max, unit)) in [(max_hours, 3600), (59, 60), (59, 1)].into_iter().enumerate() {
+        if i > 0 {
+            match rest.strip_prefix(':') {
+                Some(after) => rest = after,
+                None => break,
+            }
+        }
+        let end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        let value: i64 = rest[..end].parse().ok().filter(|&value| value <= max)?;
+        seconds += value * unit;
+        rest = &rest[end..];
+    }
+    Some((sign * seconds, rest))
+}
+
+/// Helper method to read when daylight saving time starts or ends, like
+/// `M3.2.0/2`.
+fn transition(text: &str) -> Option<Transition> {
+    let (day, time) = match text.split_once('/') {
+        Some((day, time)) => (day, clock_time(time, 167).filter(|(_, rest)| rest.is_empty())?.0),
+        None => (text, 2 * 3600),
+    };
+    let number = |text: &str, min: u32, max: u32| text.parse().ok().filter(|n| (min..=max).contains(n));
+    let day = if let Some(month) = day.strip_prefix('M') {
+        let mut parts = month.split('.');
+        let (month, week, weekday) = (parts.next()?, parts.next()?, parts.next()?);
+        if parts.next().is_some() {
+            return None;
+        }
+        RuleDay::Month(number(month, 1, 12)?, number(week, 1, 5)?, number(weekday, 0, 6)?)
+    } else if let Some(julian) = day.strip_prefix('J') {
+        RuleDay::Julian(number(julian, 1, 365)?)
+    } else {
+        RuleDay::Zero(number(day, 0, 365)?)
+    };
+    Some(Transition { day, time })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper method to get the offset and abbreviation at `secs`.
+    fn at(zone: &TimeZone, secs: i64) -> (i32, String) {
+        let local = zone.at(Time::new(secs, 0));
+        (local.offset, local.zone)
+    }
+
+    #[test]
+    fn posix_rules() {
+        // checked against the C library's localtime()
+        let cases = [
+            ("UTC0", 1700000000, 0, "UTC"),
+            ("EST+5", 1700000000, -5 * 3600, "EST"),
+            ("Foo1", 1700000000, -3600, "Foo"),
+            ("Foo", 1700000000, 0, "Foo"),
+            ("ab", 1700000000, 0, ""),
+            ("<x>", 1700000000, 0, ""),
+            ("<+0530>-5:30", 1700000000, 19800, "+0530"),
+            ("AAA3BBB,J60/3,300/-1", 1700000000, -3 * 3600, "AAA"),
+            ("NZST-12NZDT,M9.5.0,M4.1.0/3", 1700000000, 13 * 3600, "NZDT"),
+            ("NZST-12NZDT,M9.5.0,M4.1.0/3", 1690000000, 12 * 3600, "NZST"),
+            // the US rules, with no dates given
+            ("EST5EDT", 1700000000, -5 * 3600, "EST"),
+            ("EST5EDT", 1690000000, -4 * 3600, "EDT"),
+        ];
+        for (tz, secs, offset, abbr) in cases {
+            assert_eq!(at(&TimeZone::from_tz(Some(tz)), secs), (offset, abbr.to_owned()), "TZ={}", tz);
+        }
+        assert_eq!(at(&TimeZone::from_tz(Some("")), 0), (0, "UTC".to_owned()));
+    }
+
+    #[test]
+    fn rule_transitions() {
+        // 2024-03-10 02:00 EST is 07:00 UTC, and 2024-11-03 02:00 EDT is 06:00 UTC
+        let zone = TimeZone::from_tz(Some("EST5EDT,M3.2.0,M11.1.0"));
+        assert_eq!(at(&zone, 1710054000 - 1).1, "EST");
+        assert_eq!(at(&zone, 1710054000).1, "EDT");
+        assert_eq!(at(&zone, 1730613600 - 1).1, "EDT");
+        assert_eq!(at(&zone, 1730613600).1, "EST");
+    }
+
+    #[test]
+    fn zoneinfo_files() {
+        let Some(paris) = TimeZone::named("Europe/Paris") else {
+            return;
+        };
+        // from the transitions, and from the rule at the end
+        assert_eq!(at(&paris, 1700000000), (3600, "CET".to_owned()));
+        assert_eq!(at(&paris, 1690000000), (7200, "CEST".to_owned()));
+        assert_eq!(at(&paris, 5000000000), (7200, "CEST".to_owned()));
+        // before the first transition, it's local mean time
+        assert_eq!(at(&paris, -5000000000), (561, "LMT".to_owned()));
+        assert_eq!(TimeZone::from_tz(Some(":Europe/Paris")), paris);
+        assert_eq!(TimeZone::named("Europe/Nowhere"), None);
+        assert_eq!(TimeZone::named("../zoneinfo/Europe/Paris"), None);
+    }
+
+    #[test]
+    fn local_to_utc() {
+        let zone = TimeZone::from_tz(Some("EST5EDT,M3.2.0,M11.1.0"));
+        let local = |y, m, d, h: i64, min: i64| days_from_civil(y, m, d) * SECONDS_PER_DAY + h * 3600 + min * 60;
+        assert_eq!(zone.from_local(local(2024, 1, 15, 12, 0), None), Some(local(2024, 1, 15, 17, 0)));
+        assert_eq!(zone.from_local(local(2024, 7, 15, 12, 0), None), Some(local(2024, 7, 15, 16, 0)));
+        // 01:30 happens twice when the clocks go back, and the first is in EDT
+        assert_eq!(zone.from_local(local(2024, 11, 3, 1, 30), None), Some(local(2024, 11, 3, 5, 30)));
+        assert_eq!(zone.from_local(local(2024, 11, 3, 1, 30), Some(false)), Some(local(2024, 11, 3, 6, 30)));
+        assert_eq!(zone.from_local(local(2024, 7, 15, 12, 0), Some(false)), None);
+        // 02:30 never happens when they go forward
+        assert_eq!(zone.from_local(local(2024, 3, 10, 2, 30), None), None);
+        assert_eq!(TimeZone::fixed(19800, "IST").from_local(local(2024, 1, 1, 5, 30), None), Some(local(2024, 1, 1, 0, 0)));
+    }
+
+    #[test]
+    fn broken_files() {
+        assert_eq!(parse_tzif(b""), None);
+        assert_eq!(parse_tzif(b"TZif2"), None);
+        assert_eq!(parse_tzif(b"not a zoneinfo file at all, but long enough to have a header"), None);
+    }
+}
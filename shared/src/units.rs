@@ -0,0 +1,636 @@
+//! Sizes and durations with unit suffixes, like `10K`, `1MiB` and `1.5h`.
+//!
+//! Sizes follow the rules the standard utilities use for options like `head -c`,
+//! `truncate -s` and `sort -S`:
+//!
+//! * A decimal number, maybe after some white space and a `+`. `010` is ten, and
+//!   there are no hex numbers or fractions, unless fractions are turned on.
+//! * Then maybe a suffix: `K`, `M`, `G`, `T`, `P`, `E`, `Z`, `Y`, `R` or `Q`, for
+//!   powers of 1024. `k`, `m`, `g` and `t` work too. After the letter, `iB` keeps
+//!   1024, and `B` or `D` makes it powers of 1000, so `1KiB` is 1024 and `1KB` is 1000.
+//! * `b` is 512, for the utilities that count in blocks.
+//! * A suffix on its own means one of it, so `K` is 1024.
+//!
+//! Nothing can come after the suffix, and sizes that don't fit in 64 bits are errors.
+//!
+//! Durations are for `sleep` and `timeout`. They're a floating point number, as
+//! `strtod` reads it in the C locale (so `1e3`, `0x10`, and `inf` work too), and
+//! maybe one of the suffixes `s`, `m`, `h` or `d`, for seconds, minutes, hours and days.
+//! Durations too long to hold are made as long as possible instead of failing.
+//!
+//! `format_size` and `format_duration` go the other way, for showing them.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::error::UError;
+
+/// Letters for the powers of 1024 or 1000, starting at 1024 (or 1000).
+const PREFIXES: [u8; 10] = *b"KMGTPEZYRQ";
+
+/// Why a size or duration couldn't be read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitError {
+    /// It isn't a number.
+    Invalid,
+    /// There's a number, but what comes after it isn't a suffix.
+    InvalidSuffix,
+    /// It's too big to hold.
+    TooLarge,
+}
+
+impl UnitError {
+    /// Make the error to show for `text`, the way `head` does:
+    /// `invalid number of bytes: '16E': Value too large for defined data type`.
+    ///
+    /// # Arguments
+    ///
+    /// * `what` - What `text` was meant to be, like `invalid number of bytes`.
+    /// * `text` - The text that couldn't be read, from the command line.
+    pub fn to_error(self, what: &str, text: &str) -> UError {
+        match self {
+            UnitError::TooLarge => UError::new(format!("{}: '{}': {}", what, text, self)),
+            _ => UError::new(format!("{}: '{}'", what, text)),
+        }
+    }
+}
+
+impl fmt::Display for UnitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnitError::Invalid => write!(f, "invalid number"),
+            UnitError::InvalidSuffix => write!(f, "invalid suffix"),
+            UnitError::TooLarge => write!(f, "Value too large for defined data type"),
+        }
+    }
+}
+
+impl std::error::Error for UnitError {}
+
+/// The sign in front of a size, for options where it means something, like
+/// `head -n -5` (all but the last 5 lines) or `tail -n +5` (from line 5 on).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sign {
+    Plus,
+    Minus,
+}
+
+/// Rules for reading a size. `SizeParser::new()` takes every suffix but `b`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SizeParser {
+    /// Whether `b` means 512.
+    blocks: bool,
+    /// Whether a number can have a fraction, like `2.5G`.
+    fractions: bool,
+}
+
+impl SizeParser {
+    /// The rules `truncate -s` uses: every suffix but `b`, and no fractions.
+    pub const fn new() -> SizeParser {
+        SizeParser { blocks: false, fractions: false }
+    }
+
+    /// Let `b` mean a block of 512 bytes, like `head`, `split` and `du` do.
+    pub const fn with_blocks(mut self) -> SizeParser {
+        self.blocks = true;
+        self
+    }
+
+    /// Let the number have a fraction, like `2.5G`. The size is rounded up to a
+    /// whole number.
+    pub const fn with_fractions(mut self) -> SizeParser {
+        self.fractions = true;
+        self
+    }
+
+    /// Read a size.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The size, like `10K`.
+    pub fn parse(&self, text: &str) -> Result<u64, UnitError> {
+        let trimmed = text.trim_start_matches(|c: char| c.is_ascii_whitespace());
+        let digits = trimmed.strip_prefix('+').unwrap_or(trimmed);
+        if digits.starts_with(|c: char| c.is_ascii_whitespace() || c == '+' || c == '-') {
+            return Err(UnitError::Invalid);
+        }
+
+        let whole_end = digits.find(|c: char| !c.is_ascii_digit()).unwrap_or(digits.len());
+        let (whole, after_whole) = digits.split_at(whole_end);
+        let (fraction, rest) = match after_whole.strip_prefix('.') {
+            Some(rest) if self.fractions => {
+                let end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+                rest.split_at(end)
+            },
+            _ => ("", after_whole),
+        };
+
+        // a suffix on its own means one of it, but only right at the start
+        let number = if whole.is_empty() && fraction.is_empty() {
+            if digits.len() != text.len() || after_whole.is_empty() || after_whole.starts_with('.') {
+                return Err(UnitError::Invalid);
+            }
+            None
+        } else {
+            Some((whole, fraction))
+        };
+
+        let multiplier = match self.suffix(rest) {
+            Some(multiplier) => multiplier,
+            None if number.is_none() => return Err(UnitError::Invalid),
+            None => return Err(UnitError::InvalidSuffix),
+        };
+
+        match number {
+            None => u64::try_from(multiplier).map_err(|_| UnitError::TooLarge),
+            Some((whole, fraction)) => scale(whole, fraction, multiplier),
+        }
+    }
+
+    /// Read a size that can have a `+` or `-` in front, and say which it had.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The size, like `-5` or `+10K`.
+    pub fn parse_signed(&self, text: &str) -> Result<(Option<Sign>, u64), UnitError> {
+        let (sign, rest) = match text.as_bytes().first() {
+            Some(b'+') => (Some(Sign::Plus), &text[1..]),
+            Some(b'-') => (Some(Sign::Minus), &text[1..]),
+            _ => (None, text),
+        };
+        Ok((sign, self.parse(rest)?))
+    }
+
+    /// Multiplier for a suffix, or `None` if it isn't one. Empty is 1.
+    fn suffix(&self, suffix: &str) -> Option<u128> {
+        let bytes = suffix.as_bytes();
+        let Some(&letter) = bytes.first() else {
+            return Some(1);
+        };
+        if letter == b'b' {
+            return (self.blocks && bytes.len() == 1).then_some(512);
+        }
+
+        let power = PREFIXES.iter().position(|&p| p == letter.to_ascii_uppercase())?;
+        if letter.is_ascii_lowercase() && power > 3 {
+            return None;
+        }
+        let base: u128 = match &bytes[1..] {
+            b"" | b"iB" => 1024,
+            b"B" | b"D" => 1000,
+            _ => return None,
+        };
+        Some(base.pow(power as u32 + 1))
+    }
+}
+
+/// Read a size with the usual rules: every suffix but `b`, and no fractions.
+/// See `SizeParser` for other rules.
+///
+/// ```
+/// use shared::units::parse_size;
+///
+/// assert_eq!(parse_size("10K"), Ok(10240));
+/// assert_eq!(parse_size("1MB"), Ok(1_000_000));
+/// ```
+pub fn parse_size(text: &str) -> Result<u64, UnitError> {
+    SizeParser::new().parse(text)
+}
+
+/// Helper method to multiply a number by the multiplier of its suffix.
+///
+/// # Arguments
+///
+/// * `whole` - Digits of the whole part, maybe empty.
+/// * `fraction` - Digits after the point, maybe empty.
+/// * `multiplier` - What the suffix multiplies by.
+fn scale(whole: &str, fraction: &str, multiplier: u128) -> Result<u64, UnitError> {
+    let whole = whole.bytes().try_fold(0u128, |n, d| {
+        n.checked_mul(10)?.checked_add(u128::from(d - b'0'))
+    }).ok_or(UnitError::TooLarge)?;
+    let size = whole.checked_mul(multiplier).ok_or(UnitError::TooLarge)?;
+
+    // digits past the first 18 can't add a whole unit to anything that fits
+    let kept = fraction.len().min(18);
+    let numerator: u128 = fraction[..kept].parse().unwrap_or(0);
+    let denominator = 10u128.pow(kept as u32);
+    let rest = fraction[kept..].bytes().any(|d| d != b'0');
+    let part = numerator.checked_mul(multiplier).ok_or(UnitError::TooLarge)?;
+    let part = part.div_ceil(denominator) + u128::from(rest && part.is_multiple_of(denominator));
+
+    size.checked_add(part)
+        .and_then(|size| u64::try_from(size).ok())
+        .ok_or(UnitError::TooLarge)
+}
+
+/// How `format_size` scales a size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scale {
+    /// Powers of 1024, with the letters `K`, `M`, `G` and so on, like `ls -h`.
+    Binary,
+    /// Powers of 1000, with the letters `k`, `M`, `G` and so on, like `ls --si`.
+    Decimal,
+}
+
+/// Show a size the way `ls -h` and `du -h` do: `999`, `1.5K`, `10K`, `2.0G`.
+///
+/// Below 10 there's one digit after the point, and from 10 on there are none. The
+/// size is always rounded up, so it's never shown as less than it is.
+///
+/// # Arguments
+///
+/// * `size` - The size, in bytes.
+/// * `scale` - Powers of 1024 or 1000.
+pub fn format_size(size: u64, scale: Scale) -> String {
+    let base: u128 = match scale {
+        Scale::Binary => 1024,
+        Scale::Decimal => 1000,
+    };
+    let size = u128::from(size);
+    if size < base {
+        return size.to_string();
+    }
+
+    let mut power = 1;
+    while size >= base.pow(power + 1) {
+        power += 1;
+    }
+    loop {
+        let unit = base.pow(power);
+        let letter = match (scale, power) {
+            (Scale::Decimal, 1) => 'k',
+            _ => PREFIXES[power as usize - 1] as char,
+        };
+        let tenths = (size * 10).div_ceil(unit);
+        if tenths < 100 {
+            return format!("{}.{}{}", tenths / 10, tenths % 10, letter);
+        }
+        // rounding up can make it a whole unit bigger, like 1023.5K to 1.0M
+        let whole = size.div_ceil(unit);
+        if whole < base || power as usize == PREFIXES.len() {
+            return format!("{}{}", whole, letter);
+        }
+        power += 1;
+    }
+}
+
+/// Read a duration, like `1.5h`. See the module docs for the rules.
+///
+/// A duration too short to hold, like `1e-12`, is made 1 nanosecond, since 0
+/// often means no time limit at all.
+///
+/// # Arguments
+///
+/// * `text` - The duration, like `10`, `0.5s` or `2d`.
+pub fn parse_duration(text: &str) -> Result<Duration, UnitError> {
+    let text = text.trim_start_matches(|c: char| c.is_ascii_whitespace());
+    let (seconds, rest) = read_float(text).ok_or(UnitError::Invalid)?;
+    let multiplier = match rest {
+        "" | "s" => 1.0,
+        "m" => 60.0,
+        "h" => 60.0 * 60.0,
+        "d" => 24.0 * 60.0 * 60.0,
+        _ => return Err(UnitError::InvalidSuffix),
+    };
+    let seconds = seconds * multiplier;
+    if seconds.is_nan() || seconds < 0.0 {
+        return Err(UnitError::Invalid);
+    }
+
+    if seconds >= Duration::MAX.as_secs_f64() {
+        Ok(Duration::MAX)
+    } else if seconds > 0.0 {
+        Ok(Duration::from_secs_f64(seconds).max(Duration::from_nanos(1)))
+    } else {
+        Ok(Duration::ZERO)
+    }
+}
+
+/// Helper method to read a floating point number from the start of `text`, like
+/// `strtod` does. Returns the number and the rest of `text`, or `None` if it doesn't
+/// start with a number.
+fn read_float(text: &str) -> Option<(f64, &str)> {
+    let (negative, unsigned) = match text.as_bytes().first() {
+        Some(b'-') => (true, &text[1..]),
+        Some(b'+') => (false, &text[1..]),
+        _ => (false, text),
+    };
+    let sign = if negative { -1.0 } else { 1.0 };
+
+    let lower = unsigned.get(..8).unwrap_or(unsigned).to_ascii_lowercase();
+    if lower.starts_with("infinity") {
+        return Some((sign * f64::INFINITY, &unsigned[8..]));
+    } else if lower.starts_with("inf") {
+        return Some((sign * f64::INFINITY, &unsigned[3..]));
+    } else if lower.starts_with("nan") {
+        return Some((f64::NAN, &unsigned[3..]));
+    }
+
+    if let Some((value, rest)) = read_hex_float(unsigned) {
+        return Some((sign * value, rest));
+    }
+
+    let bytes = unsigned.as_bytes();
+    let digits = |from: usize| from + bytes[from..].iter().take_while(|b| b.is_ascii_digit()).count();
+    let mut end = digits(0);
+    let mut any_digits = end > 0;
+    if bytes.get(end) == Some(&b'.') {
+        let fraction_end = digits(end + 1);
+        any_digits |= fraction_end > end + 1;
+        end = fraction_end;
+    }
+    if !any_digits {
+        return None;
+    }
+    // the exponent only counts if it has digits
+    if matches!(bytes.get(end), Some(b'e' | b'E')) {
+        let mut from = end + 1;
+        if matches!(bytes.get(from), Some(b'+' | b'-')) {
+            from += 1;
+        }
+        let exponent_end = digits(from);
+        if exponent_end > from {
+            end = exponent_end;
+        }
+    }
+
+    let value: f64 = unsigned[..end].parse().ok()?;
+    Some((sign * value, &unsigned[end..]))
+}
+
+/// Helper method to read a hex floating point number, like `0x1.8p3`, from the
+/// start of `text`. Returns `None` if there isn't one.
+fn read_hex_float(text: &str) -> Option<(f64, &str)> {
+    let bytes = text.as_bytes();
+    if !matches!(bytes, [b'0', b'x' | b'X', ..]) {
+        return None;
+    }
+
+    let mut value = 0.0;
+    let mut exponent: i32 = 0;
+    let mut any_digits = false;
+    let mut point = false;
+    let mut end = 2;
+    while let Some(&b) = bytes.get(end) {
+        if b == b'.' && !point {
+            point = true;
+        } else if let Some(digit) = (b as char).to_digit(16) {
+            value = value * 16.0 + f64::from(digit);
+            any_digits = true;
+            if point {
+                exponent = exponent.saturating_sub(4);
+            }
+        } else {
+            break;
+        }
+        end += 1;
+    }
+    if !any_digits {
+        return None;
+    }
+
+    if matches!(bytes.get(end), Some(b'p' | b'P')) {
+        let mut from = end + 1;
+        let negative = bytes.get(from) == Some(&b'-');
+        if matches!(bytes.get(from), Some(b'+' | b'-')) {
+            from += 1;
+        }
+        let count = bytes[from..].iter().take_while(|b| b.is_ascii_digit()).count();
+        if count > 0 {
+            let power = text[from..from + count].parse::<i32>().unwrap_or(i32::MAX);
+            let power = if negative { -power } else { power };
+            exponent = exponent.saturating_add(power);
+            end = from + count;
+        }
+    }
+
+    // split up the power, so a big one doesn't turn into infinity or 0 early
+    let half = exponent / 2;
+    let value = value * 2f64.powi(half) * 2f64.powi(exponent - half);
+    Some((value, &text[end..]))
+}
+
+/// Show a duration so `parse_duration` reads it back the same: in the biggest of
+/// days, hours or minutes that it's a whole number of, or else in seconds, with
+/// as many digits after the point as it needs. The longest duration is `inf`.
+///
+/// # Arguments
+///
+/// * `duration` - The duration. Only whole units are used, so 90 seconds is `90s`
+///   and 120 is `2m`.
+pub fn format_duration(duration: Duration) -> String {
+    if duration == Duration::MAX {
+        return "inf".to_owned();
+    }
+
+    let seconds = duration.as_secs();
+    let nanos = duration.subsec_nanos();
+    if nanos == 0 {
+        for (unit, letter) in [(24 * 60 * 60, 'd'), (60 * 60, 'h'), (60, 'm')] {
+            if seconds > 0 && seconds.is_multiple_of(unit) {
+                return format!("{}{}", seconds / unit, letter);
+            }
+        }
+        return format!("{}s", seconds);
+    }
+
+    let fraction = format!("{:09}", nanos);
+    format!("{}.{}s", seconds, fraction.trim_end_matches('0'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_numbers() {
+        assert_eq!(parse_size("0"), Ok(0));
+        assert_eq!(parse_size("5"), Ok(5));
+        assert_eq!(parse_size("010"), Ok(10));
+        assert_eq!(parse_size(" 5"), Ok(5));
+        assert_eq!(parse_size("\t5"), Ok(5));
+        assert_eq!(parse_size("+5"), Ok(5));
+        assert_eq!(parse_size(" +5"), Ok(5));
+        assert_eq!(parse_size("00000000000000000000000000001"), Ok(1));
+        assert_eq!(parse_size("18446744073709551615"), Ok(u64::MAX));
+    }
+
+    #[test]
+    fn suffixes() {
+        assert_eq!(parse_size("10K"), Ok(10240));
+        assert_eq!(parse_size("10k"), Ok(10240));
+        assert_eq!(parse_size("1KiB"), Ok(1024));
+        assert_eq!(parse_size("1kiB"), Ok(1024));
+        assert_eq!(parse_size("1KB"), Ok(1000));
+        assert_eq!(parse_size("1kB"), Ok(1000));
+        assert_eq!(parse_size("1KD"), Ok(1000));
+        assert_eq!(parse_size("1m"), Ok(1 << 20));
+        assert_eq!(parse_size("1MB"), Ok(1_000_000));
+        assert_eq!(parse_size("1mB"), Ok(1_000_000));
+        assert_eq!(parse_size("1MiB"), Ok(1 << 20));
+        assert_eq!(parse_size("1g"), Ok(1 << 30));
+        assert_eq!(parse_size("1G"), Ok(1 << 30));
+        assert_eq!(parse_size("1t"), Ok(1 << 40));
+        assert_eq!(parse_size("1T"), Ok(1 << 40));
+        assert_eq!(parse_size("1P"), Ok(1 << 50));
+        assert_eq!(parse_size("1E"), Ok(1 << 60));
+        assert_eq!(parse_size("15E"), Ok(15 << 60));
+        assert_eq!(parse_size("1EB"), Ok(1_000_000_000_000_000_000));
+        assert_eq!(parse_size("0Y"), Ok(0));
+        assert_eq!(parse_size("0R"), Ok(0));
+        assert_eq!(parse_size("0QiB"), Ok(0));
+    }
+
+    #[test]
+    fn suffix_alone() {
+        assert_eq!(parse_size("K"), Ok(1024));
+        assert_eq!(parse_size("KB"), Ok(1000));
+        assert_eq!(parse_size("MiB"), Ok(1 << 20));
+        assert_eq!(parse_size("Z"), Err(UnitError::TooLarge));
+        assert_eq!(parse_size(" K"), Err(UnitError::Invalid));
+        assert_eq!(parse_size("+K"), Err(UnitError::Invalid));
+        assert_eq!(SizeParser::new().with_blocks().parse("b"), Ok(512));
+    }
+
+    #[test]
+    fn blocks() {
+        let parser = SizeParser::new().with_blocks();
+        assert_eq!(parser.parse("2b"), Ok(1024));
+        assert_eq!(parser.parse("2bB"), Err(UnitError::InvalidSuffix));
+        assert_eq!(parser.parse("2biB"), Err(UnitError::InvalidSuffix));
+        assert_eq!(parse_size("2b"), Err(UnitError::InvalidSuffix));
+        assert_eq!(parse_size("b"), Err(UnitError::Invalid));
+    }
+
+    #[test]
+    fn invalid() {
+        for text in ["", " ", "+", "-5", "+-5", "+ 5", "x", ".5", "B", "iB"] {
+            assert_eq!(parse_size(text), Err(UnitError::Invalid), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn invalid_suffix() {
+        let texts = ["1Ki", "1KIB", "1Kib", "1Kd", "1kb", "1B", "1c", "1w", "5x", "5 ", "0x10",
+            "1e3", "1.5K", "2.5G", "1,5K", "1KiBx", "16Ex", "1p", "1e", "1z", "1y", "1KK", "1Kb"];
+        for text in texts {
+            assert_eq!(parse_size(text), Err(UnitError::InvalidSuffix), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn too_large() {
+        for text in ["18446744073709551616", "99999999999999999999", "16E", "1Z", "1Y", "1R", "1Q",
+            "1QB", "340282366920938463463374607431768211456"] {
+            assert_eq!(parse_size(text), Err(UnitError::TooLarge), "{:?}", text);
+        }
+        assert_eq!(parse_size("18EB"), Ok(18_000_000_000_000_000_000));
+        assert_eq!(parse_size("19EB"), Err(UnitError::TooLarge));
+    }
+
+    #[test]
+    fn signed() {
+        let parser = SizeParser::new().with_blocks();
+        assert_eq!(parser.parse_signed("-5"), Ok((Some(Sign::Minus), 5)));
+        assert_eq!(parser.parse_signed("+5K"), Ok((Some(Sign::Plus), 5120)));
+        assert_eq!(parser.parse_signed("5b"), Ok((None, 2560)));
+        assert_eq!(parser.parse_signed("-"), Err(UnitError::Invalid));
+        assert_eq!(parser.parse_signed("--5"), Err(UnitError::Invalid));
+        assert_eq!(parser.parse_signed(""), Err(UnitError::Invalid));
+    }
+
+    #[test]
+    fn fractions() {
+        let parser = SizeParser::new().with_fractions();
+        assert_eq!(parser.parse("2.5G"), Ok(5 << 29));
+        assert_eq!(parser.parse("1.5K"), Ok(1536));
+        assert_eq!(parser.parse(".5K"), Ok(512));
+        assert_eq!(parser.parse("5."), Ok(5));
+        assert_eq!(parser.parse("1.5"), Ok(2));
+        assert_eq!(parser.parse("0.001K"), Ok(2));
+        assert_eq!(parser.parse("1.0000000000000000000001"), Ok(2));
+        assert_eq!(parser.parse("1.0000000000000000000000"), Ok(1));
+        assert_eq!(parser.parse("0.5KB"), Ok(500));
+        assert_eq!(parser.parse("15.9E"), Ok(18331451923248866919));
+        assert_eq!(parser.parse("16.1E"), Err(UnitError::TooLarge));
+        assert_eq!(parser.parse("."), Err(UnitError::Invalid));
+        assert_eq!(parser.parse(".K"), Err(UnitError::Invalid));
+        assert_eq!(parser.parse("1.5.K"), Err(UnitError::InvalidSuffix));
+    }
+
+    #[test]
+    fn error_text() {
+        assert_eq!(UnitError::Invalid.to_error("invalid number of bytes", "1Q").to_string(),
+            "invalid number of bytes: '1Q'");
+        assert_eq!(UnitError::TooLarge.to_error("invalid number of bytes", "16E").to_string(),
+            "invalid number of bytes: '16E': Value too large for defined data type");
+    }
+
+    #[test]
+    fn format_binary() {
+        let cases = [(0, "0"), (1, "1"), (999, "999"), (1000, "1000"), (1023, "1023"),
+            (1024, "1.0K"), (1025, "1.1K"), (1536, "1.5K"), (9999, "9.8K"), (10239, "10K"),
+            (10240, "10K"), (10241, "11K"), (102400, "100K"), (1048575, "1.0M"),
+            (1048576, "1.0M"), (1 << 30, "1.0G"), (1 << 60, "1.0E"), (u64::MAX, "16E")];
+        for (size, text) in cases {
+            assert_eq!(format_size(size, Scale::Binary), text, "{}", size);
+        }
+    }
+
+    #[test]
+    fn format_decimal() {
+        let cases = [(0, "0"), (999, "999"), (1000, "1.0k"), (1023, "1.1k"), (1536, "1.6k"),
+            (9999, "10k"), (10239, "11k"), (102400, "103k"), (999_999, "1.0M"),
+            (1048576, "1.1M"), (1 << 30, "1.1G"), (u64::MAX, "19E")];
+        for (size, text) in cases {
+            assert_eq!(format_size(size, Scale::Decimal), text, "{}", size);
+        }
+    }
+
+    #[test]
+    fn durations() {
+        let cases = [("1", 1.0), ("1.5", 1.5), (".5", 0.5), ("5.", 5.0), ("1s", 1.0), ("1m", 60.0),
+            ("1h", 3600.0), ("1d", 86400.0), ("1.5h", 5400.0), ("1e3", 1000.0), ("1E3", 1000.0),
+            ("1e3s", 1000.0), ("+1", 1.0), (" 1", 1.0), ("0x10", 16.0), ("0x1p3", 8.0),
+            ("0x.8", 0.5), ("0X1.8P1", 3.0), ("1.5e-3m", 1.5e-3 * 60.0), ("0", 0.0), ("-0", 0.0),
+            ("-0.0", 0.0), ("0s", 0.0)];
+        for (text, seconds) in cases {
+            assert_eq!(parse_duration(text), Ok(Duration::from_secs_f64(seconds)), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn endless_durations() {
+        for text in ["inf", "infinity", "INF", "Infinity", "infs", "infd", "1e400", "0x1p99999",
+            "1e20"] {
+            assert_eq!(parse_duration(text), Ok(Duration::MAX), "{:?}", text);
+        }
+        assert_eq!(parse_duration("0.0000000001"), Ok(Duration::from_nanos(1)));
+        assert_eq!(parse_duration("1e-400"), Ok(Duration::ZERO));
+    }
+
+    #[test]
+    fn invalid_durations() {
+        for text in ["", " ", "s", ".", "-1", "-inf", "nan", "NaN", "-", "+", "e3"] {
+            assert_eq!(parse_duration(text), Err(UnitError::Invalid), "{:?}", text);
+        }
+        for text in ["1 ", "1x", "1ss", "1e", "1e+", "0x", "0xg", "1M", "1S", "infx", "1p3",
+            "0x1p", "0x."] {
+            assert_eq!(parse_duration(text), Err(UnitError::InvalidSuffix), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn format_durations() {
+        let cases = [(Duration::ZERO, "0s"), (Duration::from_secs(1), "1s"),
+            (Duration::from_secs(90), "90s"), (Duration::from_secs(120), "2m"),
+            (Duration::from_secs(5400), "90m"), (Duration::from_secs(7200), "2h"),
+            (Duration::from_secs(86400 * 3), "3d"), (Duration::from_millis(1500), "1.5s"),
+            (Duration::from_nanos(1), "0.000000001s"), (Duration::from_millis(60_250), "60.25s"),
+            (Duration::MAX, "inf")];
+        for (duration, text) in cases {
+            assert_eq!(format_duration(duration), text);
+            assert_eq!(parse_duration(text), Ok(duration), "{:?}", text);
+        }
+    }
+}
@@ -0,0 +1,611 @@
+//! Users and groups: names for IDs, IDs for names, and the groups a user is in.
+//!
+//! `Database::system()` asks the C library, so it sees everything the system
+//! knows about, like users from LDAP. `Database::files()` reads files in the
+//! format of `/etc/passwd` and `/etc/group` instead, so tests can use their own.
+//!
+//! `Database::parse_owner()` reads an owner like `chown` takes: `user:group`,
+//! `user`, `:group`, `user:` (for the user's login group), or IDs, like `0:0`.
+//!
+//! The user and group names are shown as UTF-8, even if they aren't. That's rare,
+//! since most systems only allow letters, digits, and a few other characters.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::error::UError;
+
+/// A user, from the password database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct User {
+    pub name: String,
+    pub uid: u32,
+    /// ID of the user's login group.
+    pub gid: u32,
+    /// The user's full name, and maybe other things, separated by commas.
+    pub gecos: String,
+    pub home: PathBuf,
+    pub shell: PathBuf,
+}
+
+/// A group, from the group database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Group {
+    pub name: String,
+    pub gid: u32,
+    /// Names of the users it's a supplementary group for. Users whose login group
+    /// it is don't have to be here.
+    pub members: Vec<String>,
+}
+
+/// Where users and groups are looked up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Database {
+    source: Source,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Source {
+    /// The C library's `getpwnam()` and friends. There are no users or groups
+    /// at all on systems that don't have them.
+    System,
+    /// Files like `/etc/passwd` and `/etc/group`.
+    Files { passwd: PathBuf, group: PathBuf },
+}
+
+/// An owner, from something like `user:group`. Either one can be left out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Owner {
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    /// Whether the user and group were separated with `.`, like `user.group`.
+    /// That still works, but `chown` warns that it should be `:`.
+    pub dot: bool,
+}
+
+/// Why an owner couldn't be read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OwnerError {
+    /// `user:` with a user that isn't in the database, so there's no login group.
+    InvalidSpec,
+    /// The user isn't in the database, and isn't an ID.
+    InvalidUser,
+    /// The group isn't in the database, and isn't an ID.
+    InvalidGroup,
+}
+
+impl OwnerError {
+    /// Make the error to show for `text`, the way `chown` does:
+    /// `invalid user: 'nobody1:wheel'`.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The owner that couldn't be read, from the command line.
+    pub fn to_error(self, text: &str) -> UError {
+        UError::new(format!("{}: '{}'", self, text))
+    }
+}
+
+impl fmt::Display for OwnerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OwnerError::InvalidSpec => write!(f, "invalid spec"),
+            OwnerError::InvalidUser => write!(f, "invalid user"),
+            OwnerError::InvalidGroup => write!(f, "invalid group"),
+        }
+    }
+}
+
+impl std::error::Error for OwnerError {}
+
+impl Database {
+    /// Look up users and groups the way the rest of the system does.
+    pub fn system() -> Database {
+        Database { source: Source::System }
+    }
+
+    /// Look up users and groups in files. A file that can't be read is the same
+    /// as an empty one, like it is for the C library.
+    ///
+    /// # Arguments
+    ///
+    /// * `passwd` - A file like `/etc/passwd`, with lines like
+    ///   `name:password:uid:gid:gecos:home:shell`.
+    /// * `group` - A file like `/etc/group`, with lines like
+    ///   `name:password:gid:user,user`.
+    pub fn files(passwd: impl Into<PathBuf>, group: impl Into<PathBuf>) -> Database {
+        Database { source: Source::Files { passwd: passwd.into(), group: group.into() } }
+    }
+
+    /// Find a user by name.
+    pub fn user_by_name(&self, name: &str) -> Option<User> {
+        match &self.source {
+            Source::System => sys::user_by_name(name),
+            Source::Files { passwd, .. } => read_users(passwd).find(|user| user.name == name),
+        }
+    }
+
+    /// Find a user by ID.
+    pub fn user_by_id(&self, uid: u32) -> Option<User> {
+        match &self.source {
+            Source::System => sys::user_by_id(uid),
+            Source::Files { passwd, .. } => read_users(passwd).find(|user| user.uid == uid),
+        }
+    }
+
+    /// Find a group by name.
+    pub fn group_by_name(&self, name: &str) -> Option<Group> {
+        match &self.source {
+            Source::System => sys::group_by_name(name),
+            Source::Files { group, .. } => read_groups(group).find(|group| group.name == name),
+        }
+    }
+
+    /// Find a group by ID.
+    pub fn group_by_id(&self, gid: u32) -> Option<Group> {
+        match &self.source {
+            Source::System => sys::group_by_id(gid),
+            Source::Files { group, .. } => read_groups(group).find(|group| group.gid == gid),
+        }
+    }
+
+    /// Name of the user with ID `uid`, or the ID itself if there's no such user,
+    /// the way `ls -l` shows owners.
+    pub fn user_name(&self, uid: u32) -> String {
+        self.user_by_id(uid).map_or_else(|| uid.to_string(), |user| user.name)
+    }
+
+    /// Name of the group with ID `gid`, or the ID itself if there's no such group.
+    pub fn group_name(&self, gid: u32) -> String {
+        self.group_by_id(gid).map_or_else(|| gid.to_string(), |group| group.name)
+    }
+
+    /// IDs of all the groups a user is in, like `id -G user` shows: the login
+    /// group first, then the supplementary groups, with no ID twice.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The user's name.
+    /// * `gid` - The user's login group, from the password database.
+    pub fn groups_of(&self, name: &str, gid: u32) -> Vec<u32> {
+        let mut groups = match &self.source {
+            Source::System => sys::groups_of(name, gid),
+            Source::Files { group, .. } => {
+                let members = read_groups(group).filter(|group| group.members.iter().any(|member| member == name));
+                std::iter::once(gid).chain(members.map(|group| group.gid)).collect()
+            },
+        };
+        let mut seen = Vec::with_capacity(groups.len());
+        groups.retain(|gid| {
+            let new = !seen.contains(gid);
+            seen.push(*gid);
+            new
+        });
+        groups
+    }
+
+    /// Read an owner, like `chown` and `chgrp` take.
+    ///
+    /// Names are looked up first, so a user called `1000` is found by name. A
+    /// name that isn't found can be an ID instead. A `+` in front, like `+1000`,
+    /// means it's an ID, without looking for a name.
+    ///
+    /// With no `:`, a `.` can separate the user and group too, like `user.group`,
+    /// but only if the whole thing isn't a user's name, since names can have dots.
+    ///
+    /// # Arguments
+    ///
+    /// * `spec` - `user:group`, `user`, `:group`, `user:`, or IDs, like `0:0`.
+    pub fn parse_owner(&self, spec: &str) -> Result<Owner, OwnerError> {
+        let colon = spec.find(':');
+        let result = self.parse_owner_at(spec, colon);
+        match (colon, result, spec.find('.')) {
+            (None, Err(e), Some(dot)) => {
+                let owner = self.parse_owner_at(spec, Some(dot)).map_err(|_| e)?;
+                Ok(Owner { dot: true, ..owner })
+            },
+            (_, result, _) => result,
+        }
+    }
+
+    /// Helper method to read an owner, with the user and group separated at `at`.
+    ///
+    /// # Arguments
+    ///
+    /// * `spec` - The owner, from the command line.
+    /// * `at` - Where the separator is, or `None` if there's only a user.
+    fn parse_owner_at(&self, spec: &str, at: Option<usize>) -> Result<Owner, OwnerError> {
+        let (user, group) = match at {
+            Some(at) => (&spec[..at], Some(&spec[at + 1..])),
+            None => (spec, None),
+        };
+        let mut owner = Owner::default();
+
+        if !user.is_empty() {
+            match user.strip_prefix('+').map_or_else(|| self.user_by_name(user), |_| None) {
+                Some(found) => {
+                    owner.uid = Some(found.uid);
+                    // `user:` means the user's login group
+                    if group == Some("") {
+                        owner.gid = Some(found.gid);
+                    }
+                },
+                None if group == Some("") => return Err(OwnerError::InvalidSpec),
+                None => owner.uid = Some(parse_id(user).ok_or(OwnerError::InvalidUser)?),
+            }
+        }
+
+        if let Some(group) = group.filter(|group| !group.is_empty()) {
+            let found = group.strip_prefix('+').map_or_else(|| self.group_by_name(group), |_| None);
+            owner.gid = Some(match found {
+                Some(found) => found.gid,
+                None => parse_id(group).ok_or(OwnerError::InvalidGroup)?,
+            });
+        }
+        Ok(owner)
+    }
+}
+
+/// The real and effective IDs of this process.
+#[cfg(unix)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcessIds {
+    pub uid: u32,
+    pub euid: u32,
+    pub gid: u32,
+    pub egid: u32,
+}
+
+/// Get the real and effective IDs of this process.
+#[cfg(unix)]
+pub fn process_ids() -> ProcessIds {
+    // SAFETY: these can't fail, and have no arguments
+    unsafe {
+        ProcessIds { uid: libc::getuid(), euid: libc::geteuid(), gid: libc::getgid(), egid: libc::getegid() }
+    }
+}
+
+/// Get the supplementary group IDs of this process, like `id -G` shows after the
+/// login group.
+#[cfg(unix)]
+pub fn process_groups() -> std::io::Result<Vec<u32>> {
+    loop {
+        // SAFETY: with a size of 0, it only counts the groups
+        let count = unsafe { libc::getgroups(0, std::ptr::null_mut()) };
+        if count < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        let mut groups: Vec<libc::gid_t> = vec![0; count as usize];
+        // SAFETY: the buffer has room for `count` groups
+        let found = unsafe { libc::getgroups(count, groups.as_mut_ptr()) };
+        if found >= 0 {
+            groups.truncate(found as usize);
+            return Ok(groups);
+        }
+        // the list can grow between the two calls, so count again
+        let e = std::io::Error::last_os_error();
+        if e.raw_os_error() != Some(libc::EINVAL) {
+            return Err(e);
+        }
+    }
+}
+
+/// Helper method to read an ID, which can have white space and a `+` in front, like
+/// `strtoul()` allows. `-1` (the biggest ID) isn't one, since it means "don't
+/// change" to `chown()`.
+fn parse_id(text: &str) -> Option<u32> {
+    let text = text.trim_start_matches(|c: char| c.is_ascii_whitespace());
+    let digits = text.strip_prefix('+').unwrap_or(text);
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok().filter(|&id| id != u32::MAX)
+}
+
+/// Helper method to read the users in a file like `/etc/passwd`. Lines that
+/// aren't users, like comments, are skipped.
+fn read_users(path: &Path) -> impl Iterator<Item = User> {
+    read_lines(path).filter_map(|fields| {
+        let [name, _, uid, gid, gecos, home, shell] = fields.try_into().ok()?;
+        Some(User {
+            name,
+            uid: parse_id(&uid)?,
+            gid: parse_id(&gid)?,
+            gecos,
+            home: home.into(),
+            shell: shell.into(),
+        })
+    })
+}
+
+/// Helper method to read the groups in a file like `/etc/group`.
+fn read_groups(path: &Path) -> impl Iterator<Item = Group> {
+    read_lines(path).filter_map(|fields| {
+        let [name, _, gid, members] = fields.try_into().ok()?;
+        Some(Group {
+            name,
+            gid: parse_id(&gid)?,
+            members: members.split(',').filter(|member| !member.is_empty()).map(str::to_owned).collect(),
+        })
+    })
+}
+
+/// Helper method to split the lines of a database file into fields. Blank lines,
+/// comments, and NIS lines starting with `+` or `-` are left out.
+fn read_lines(path: &Path) -> impl Iterator<Item = Vec<String>> {
+    let data = std::fs::read(path).unwrap_or_default();
+    let text = String::from_utf8_lossy(&data).into_owned();
+    let lines: Vec<Vec<String>> = text.lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with(['#', '+', '-']))
+        .map(|line| line.split(':').map(str::to_owned).collect())
+        .collect();
+    lines.into_iter()
+}
+
+#[cfg(unix)]
+mod sys {
+    //! Lookups through the C library. The `_r` functions are used, since the
+    //! others share a buffer, and tests look things up from many threads at once.
+
+    use std::ffi::{CStr, CString};
+    use std::mem::MaybeUninit;
+
+    use super::{Group, User};
+
+    /// Size to start the buffer at. It's doubled when it's too small.
+    const BUFFER_SIZE: usize = 1024;
+
+    /// Helper method to turn a C string from the C library into a `String`.
+    fn from_c(text: *const libc::c_char) -> String {
+        if text.is_null() {
+            return String::new();
+        }
+        // SAFETY: the C library gives a null-terminated string, which lives as
+        // long as the buffer it was given
+        unsafe { CStr::from_ptr(text) }.to_string_lossy().into_owned()
+    }
+
+    /// Helper method to make a name into a C string. Names with a null in them
+    /// can't be in the database.
+    fn to_c(name: &str) -> Option<CString> {
+        CString::new(name).ok()
+    }
+
+    /// Helper method to call one of the `get*_r` functions, with a bigger buffer
+    /// each time it's too small. Returns what it found, or `None` if there's
+    /// nothing, or it failed.
+    fn lookup<T, R>(convert: impl Fn(&T) -> R,
+                    call: impl Fn(*mut T, *mut libc::c_char, usize, *mut *mut T) -> libc::c_int) -> Option<R> {
+        let mut buffer: Vec<libc::c_char> = vec![0; BUFFER_SIZE];
+        loop {
+            let mut entry = MaybeUninit::<T>::uninit();
+            let mut result = std::ptr::null_mut();
+            let error = call(entry.as_mut_ptr(), buffer.as_mut_ptr(), buffer.len(), &mut result);
+            if error == libc::ERANGE && buffer.len() < 1 << 20 {
+                buffer.resize(buffer.len() * 2, 0);
+                continue;
+            }
+            if error != 0 || result.is_null() {
+                return None;
+            }
+            // SAFETY: on success, `result` points at `entry`, which is filled in,
+            // with its strings in `buffer`
+            return Some(convert(unsafe { &*result }));
+        }
+    }
+
+    fn user(entry: &libc::passwd) -> User {
+        User {
+            name: from_c(entry.pw_name),
+            uid: entry.pw_uid,
+            gid: entry.pw_gid,
+            gecos: from_c(entry.pw_gecos),
+            home: from_c(entry.pw_dir).into(),
+            shell: from_c(entry.pw_shell).into(),
+        }
+    }
+
+    fn group(entry: &libc::group) -> Group {
+        let mut members = Vec::new();
+        if !entry.gr_mem.is_null() {
+            // SAFETY: `gr_mem` is a null-terminated list of strings
+            unsafe {
+                let mut member = entry.gr_mem;
+                while !(*member).is_null() {
+                    members.push(from_c(*member));
+                    member = member.add(1);
+                }
+            }
+        }
+        Group { name: from_c(entry.gr_name), gid: entry.gr_gid, members }
+    }
+
+    pub(super) fn user_by_name(name: &str) -> Option<User> {
+        let name = to_c(name)?;
+        // SAFETY: the pointers are all to buffers of the right size
+        lookup(user, |entry, buf, len, result| unsafe { libc::getpwnam_r(name.as_ptr(), entry, buf, len, result) })
+    }
+
+    pub(super) fn user_by_id(uid: u32) -> Option<User> {
+        // SAFETY: the pointers are all to buffers of the right size
+        lookup(user, |entry, buf, len, result| unsafe { libc::getpwuid_r(uid, entry, buf, len, result) })
+    }
+
+    pub(super) fn group_by_name(name: &str) -> Option<Group> {
+        let name = to_c(name)?;
+        // SAFETY: the pointers are all to buffers of the right size
+        lookup(group, |entry, buf, len, result| unsafe { libc::getgrnam_r(name.as_ptr(), entry, buf, len, result) })
+    }
+
+    pub(super) fn group_by_id(gid: u32) -> Option<Group> {
+        // SAFETY: the pointers are all to buffers of the right size
+        lookup(group, |entry, buf, len, result| unsafe { libc::getgrgid_r(gid, entry, buf, len, result) })
+    }
+
+    pub(super) fn groups_of(name: &str, gid: u32) -> Vec<u32> {
+        let Some(name) = to_c(name) else {
+            return vec![gid];
+        };
+        let mut count: libc::c_int = 16;
+        loop {
+            let mut groups: Vec<libc::gid_t> = vec![0; count as usize];
+            let asked = count;
+            // SAFETY: the buffer has room for `count` groups. Some systems take
+            // the groups as `int`, which is the same size.
+            let found = unsafe { libc::getgrouplist(name.as_ptr(), gid as _, groups.as_mut_ptr().cast(), &mut count) };
+            if found >= 0 {
+                groups.truncate(count as usize);
+                return groups;
+            }
+            // too small: it says how big it needs to be, or doesn't, so double it
+            count = count.max(asked * 2);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod sys {
+    //! There's no database to ask, so there are no users or groups.
+
+    use super::{Group, User};
+
+    pub(super) fn user_by_name(_name: &str) -> Option<User> {
+        None
+    }
+
+    pub(super) fn user_by_id(_uid: u32) -> Option<User> {
+        None
+    }
+
+    pub(super) fn group_by_name(_name: &str) -> Option<Group> {
+        None
+    }
+
+    pub(super) fn group_by_id(_gid: u32) -> Option<Group> {
+        None
+    }
+
+    pub(super) fn groups_of(_name: &str, gid: u32) -> Vec<u32> {
+        vec![gid]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper method to get the database in the test fixtures.
+    fn fixtures() -> Database {
+        Database::files("tests/fixtures/passwd", "tests/fixtures/group")
+    }
+
+    #[test]
+    fn users_from_files() {
+        let db = fixtures();
+        let alice = db.user_by_name("alice").unwrap();
+        assert_eq!(alice, User {
+            name: "alice".to_owned(),
+            uid: 1000,
+            gid: 1000,
+            gecos: "Alice Liddell,,,".to_owned(),
+            home: "/home/alice".into(),
+            shell: "/bin/zsh".into(),
+        });
+        assert_eq!(db.user_by_id(1001).unwrap().name, "bob");
+        assert_eq!(db.user_by_id(1042).unwrap().name, "42");
+        // comments, short lines and NIS lines aren't users
+        assert_eq!(db.user_by_name("broken"), None);
+        assert_eq!(db.user_by_name("+nis"), None);
+        assert_eq!(db.user_by_name("nobody"), None);
+        assert_eq!(db.user_name(1002), "first.last");
+        assert_eq!(db.user_name(4242), "4242");
+    }
+
+    #[test]
+    fn groups_from_files() {
+        let db = fixtures();
+        assert_eq!(db.group_by_name("wheel").unwrap(), Group {
+            name: "wheel".to_owned(),
+            gid: 10,
+            members: vec!["alice".to_owned(), "bob".to_owned()],
+        });
+        assert_eq!(db.group_by_id(29).unwrap().members, ["bob", "alice"]);
+        assert_eq!(db.group_name(100), "users");
+        assert_eq!(db.group_name(12), "12");
+
+        // the login group first, and each group once
+        assert_eq!(db.groups_of("alice", 1000), [1000, 100, 10, 29]);
+        assert_eq!(db.groups_of("bob", 100), [100, 10, 29]);
+        assert_eq!(db.groups_of("nobody", 65534), [65534]);
+    }
+
+    #[test]
+    fn missing_files() {
+        let db = Database::files("tests/fixtures/none", "tests/fixtures/none");
+        assert_eq!(db.user_by_id(0), None);
+        assert_eq!(db.group_name(0), "0");
+    }
+
+    #[test]
+    fn owners() {
+        let db = fixtures();
+        let owner = |uid, gid| Ok(Owner { uid, gid, dot: false });
+        let cases = [
+            ("alice", owner(Some(1000), None)),
+            ("alice:wheel", owner(Some(1000), Some(10))),
+            ("alice:", owner(Some(1000), Some(1000))),
+            (":wheel", owner(None, Some(10))),
+            (":", owner(None, None)),
+            ("", owner(None, None)),
+            ("0:0", owner(Some(0), Some(0))),
+            ("4294967294:8", owner(Some(4294967294), Some(8))),
+            (" 5", owner(Some(5), None)),
+            // a name is looked up before a number
+            ("42", owner(Some(1042), None)),
+            ("+42", owner(Some(42), None)),
+            (":7", owner(None, Some(1007))),
+            (":+7", owner(None, Some(7))),
+            ("first.last", owner(Some(1002), None)),
+            ("12345:", Err(OwnerError::InvalidSpec)),
+            ("nobody:", Err(OwnerError::InvalidSpec)),
+            ("nobody", Err(OwnerError::InvalidUser)),
+            ("+alice", Err(OwnerError::InvalidUser)),
+            ("4294967295", Err(OwnerError::InvalidUser)),
+            ("-1", Err(OwnerError::InvalidUser)),
+            ("alice:nobody", Err(OwnerError::InvalidGroup)),
+            ("0:+wheel", Err(OwnerError::InvalidGroup)),
+            // with no colon, a dot works too, but the first error is kept
+            ("bob.nobody", Err(OwnerError::InvalidUser)),
+            ("nobody.wheel", Err(OwnerError::InvalidUser)),
+        ];
+        for (spec, expected) in cases {
+            assert_eq!(db.parse_owner(spec), expected, "{}", spec);
+        }
+        assert_eq!(db.parse_owner("bob.wheel"), Ok(Owner { uid: Some(1001), gid: Some(10), dot: true }));
+        assert_eq!(db.parse_owner("first.last:audio"), Ok(Owner { uid: Some(1002), gid: Some(29), dot: false }));
+        assert_eq!(OwnerError::InvalidGroup.to_error("alice:nobody").to_string(), "invalid group: 'alice:nobody'");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn system_database() {
+        // root is everywhere, and has ID 0
+        let db = Database::system();
+        let root = db.user_by_id(0).unwrap();
+        assert_eq!(db.user_by_name(&root.name), Some(root.clone()));
+        assert_eq!(db.parse_owner(&format!("{}:", root.name)), Ok(Owner { uid: Some(0), gid: Some(root.gid), dot: false }));
+        let group = db.group_by_id(root.gid).unwrap();
+        assert_eq!(db.group_by_name(&group.name).unwrap().gid, root.gid);
+        assert_eq!(db.groups_of(&root.name, root.gid)[0], root.gid);
+        assert_eq!(db.user_by_name("no such user, really"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn this_process() {
+        let ids = process_ids();
+        // SAFETY: it can't fail
+        assert_eq!(ids.euid, unsafe { libc::geteuid() });
+        assert!(process_groups().is_ok());
+    }
+}
@@ -0,0 +1,54 @@
+//! Utilities as library entry points, so they can all be built into one program.
+//!
+//! Each utility crate has a library with a `run()` function that takes the whole
+//! command line, and a `UTILITY` constant that describes it. Its own binary just
+//! calls `run()`. The `coreutils` binary has a list of every `UTILITY`, and picks
+//! one by the name it was run as, or by its first argument.
+
+use std::ffi::OsString;
+use std::process::ExitCode;
+
+/// Entry point of a utility. Takes the command line, starting with the program
+/// name, and returns the exit code.
+pub type Main = fn(Vec<OsString>) -> ExitCode;
+
+/// A utility that can be run from the `coreutils` binary.
+#[derive(Debug, Clone, Copy)]
+pub struct Utility {
+    /// Name it's run as, like `cat`.
+    pub name: &'static str,
+    /// Other names it can be run as, like `zcat`. The utility looks at the program
+    /// name to tell which one was used.
+    pub aliases: &'static [&'static str],
+    /// Entry point.
+    pub main: Main,
+}
+
+impl Utility {
+    /// Check whether the utility is run as `name`, by its own name or an alias.
+    pub fn is_named(&self, name: &str) -> bool {
+        self.name == name || self.aliases.contains(&name)
+    }
+
+    /// Every name the utility can be run as, starting with its own.
+    pub fn names(&self) -> impl Iterator<Item = &'static str> {
+        std::iter::once(self.name).chain(self.aliases.iter().copied())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn succeed(_args: Vec<OsString>) -> ExitCode {
+        ExitCode::SUCCESS
+    }
+
+    #[test]
+    fn names_and_aliases() {
+        let cat = Utility { name: "cat", aliases: &["zcat", "bzcat"], main: succeed };
+        assert!(cat.is_named("cat") && cat.is_named("zcat"));
+        assert!(!cat.is_named("rcat"));
+        assert_eq!(cat.names().collect::<Vec<_>>(), ["cat", "zcat", "bzcat"]);
+    }
+}
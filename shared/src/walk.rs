@@ -0,0 +1,906 @@
+//! Walking directory trees, like `du`, `chmod -R`, `rm -r`, and `ls -R` do.
+//!
+//! `Walk::run()` goes through everything under an operand, depth first, and tells
+//! a `Visitor` about it: `pre()` for each file, and for each directory before
+//! what's in it, and `post()` for each directory after what's in it. Something
+//! that can't be walked goes to `error()`, and walking goes on with the next file,
+//! unless the visitor says to stop.
+//!
+//! Symbolic links are followed like `-H`, `-L`, and `-P` say, and
+//! `one_file_system()` keeps out of directories on other file systems, like
+//! `--one-file-system`. A directory that's also one of the directories it's in,
+//! which only happens through symbolic links or bind mounts, is an error instead
+//! of a loop that never ends.
+//!
+//! Directories are opened relative to the one they're in, with `openat()`, so
+//! paths can be longer than `PATH_MAX`, and a directory that's swapped for a
+//! symbolic link while it's walked isn't followed out of the tree. Each entry has
+//! the directory it's in, as a file descriptor, and its name, so the visitor can
+//! do things to it the same way, like with `unlinkat()`. Only so many directories
+//! are kept open at once. The others are opened again on the way back up, and
+//! checked to be the same directories.
+//!
+//! Files in a directory come in the order the system lists them.
+
+use std::ffi::{CStr, CString, OsStr, OsString};
+use std::fmt;
+use std::io;
+use std::mem::MaybeUninit;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::path::{Path, PathBuf};
+
+use crate::error::{strerror, UError};
+use crate::quote::quote_name;
+use crate::time::Time;
+
+/// The most directories kept open at once, so a deep tree doesn't run out of
+/// file descriptors.
+const MAX_OPEN: usize = 32;
+
+/// Bits of a mode that say what type of file it is.
+const TYPE_BITS: u32 = 0o170000;
+const DIRECTORY: u32 = 0o040000;
+const SYMLINK: u32 = 0o120000;
+
+/// Which symbolic links are followed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Follow {
+    /// None of them, like `-P`.
+    #[default]
+    Never,
+    /// Only the operand, like `-H`.
+    Operands,
+    /// All of them, like `-L`.
+    Always,
+}
+
+/// What to do next, from a `Visitor`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Keep going.
+    Continue,
+    /// Don't go into this directory. It only means something from `pre()` for a
+    /// directory, and then `post()` isn't called for it either.
+    Skip,
+    /// Stop walking altogether.
+    Stop,
+}
+
+/// What's told about the files in a tree, as it's walked.
+pub trait Visitor {
+    /// Called for each file, and for each directory before what's in it.
+    fn pre(&mut self, _entry: &Entry) -> Action {
+        Action::Continue
+    }
+
+    /// Called for each directory after what's in it, unless `pre()` skipped it.
+    fn post(&mut self, _entry: &Entry) -> Action {
+        Action::Continue
+    }
+
+    /// Called for something that can't be walked. Walking goes on afterwards,
+    /// unless this returns `Action::Stop`.
+    fn error(&mut self, error: WalkError) -> Action;
+}
+
+/// What `stat()` says about a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stat {
+    pub dev: u64,
+    pub ino: u64,
+    /// Type and permission bits, like `0o40755`.
+    pub mode: u32,
+    pub nlink: u64,
+    pub uid: u32,
+    pub gid: u32,
+    /// The device it is, for a device file.
+    pub rdev: u64,
+    pub size: u64,
+    /// How many 512-byte blocks it takes up.
+    pub blocks: u64,
+    pub atime: Time,
+    pub mtime: Time,
+    pub ctime: Time,
+}
+
+impl Stat {
+    /// Check whether it's a directory.
+    pub fn is_dir(&self) -> bool {
+        self.mode & TYPE_BITS == DIRECTORY
+    }
+
+    /// Check whether it's a symbolic link, which it can only be if it wasn't
+    /// followed.
+    pub fn is_symlink(&self) -> bool {
+        self.mode & TYPE_BITS == SYMLINK
+    }
+}
+
+impl From<libc::stat> for Stat {
+    // the types of the fields are different from one system to the next
+    #[allow(clippy::unnecessary_cast)]
+    fn from(st: libc::stat) -> Stat {
+        let time = |secs, nanos| Time::new(secs as i64, nanos as u32);
+        Stat {
+            dev: st.st_dev as u64,
+            ino: st.st_ino as u64,
+            mode: st.st_mode as u32,
+            nlink: st.st_nlink as u64,
+            uid: st.st_uid,
+            gid: st.st_gid,
+            rdev: st.st_rdev as u64,
+            size: st.st_size as u64,
+            blocks: st.st_blocks as u64,
+            atime: time(st.st_atime, st.st_atime_nsec),
+            mtime: time(st.st_mtime, st.st_mtime_nsec),
+            ctime: time(st.st_ctime, st.st_ctime_nsec),
+        }
+    }
+}
+
+/// A file in a tree, while the visitor is told about it.
+#[derive(Debug)]
+pub struct Entry<'a> {
+    path: &'a Path,
+    name: &'a OsStr,
+    dir_fd: RawFd,
+    depth: usize,
+    stat: &'a Stat,
+    other_device: bool,
+}
+
+impl Entry<'_> {
+    /// Path to show for it: the operand, with names of directories under it.
+    pub fn path(&self) -> &Path {
+        self.path
+    }
+
+    /// Its name in the directory it's in, or the whole operand at the top.
+    pub fn name(&self) -> &OsStr {
+        self.name
+    }
+
+    /// The directory it's in, for calls like `unlinkat()` with `name()`. It's
+    /// `AT_FDCWD` at the top. It's only open while the visitor is called.
+    pub fn dir_fd(&self) -> RawFd {
+        self.dir_fd
+    }
+
+    /// How far down it is: 0 for the operand, 1 for what's in it, and so on.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// What `stat()` says about it, or `lstat()` if the link isn't followed.
+    pub fn stat(&self) -> &Stat {
+        self.stat
+    }
+
+    /// Check whether it's on a different file system from the operand. With
+    /// `one_file_system()`, a directory like that isn't gone into.
+    pub fn other_device(&self) -> bool {
+        self.other_device
+    }
+}
+
+/// Something in a tree that couldn't be walked.
+#[derive(Debug)]
+pub struct WalkError {
+    /// Path of it, like `Entry::path()`.
+    pub path: PathBuf,
+    /// How far down it is, like `Entry::depth()`.
+    pub depth: usize,
+    pub kind: WalkErrorKind,
+}
+
+/// What went wrong while walking.
+#[derive(Debug)]
+pub enum WalkErrorKind {
+    /// It couldn't be looked at with `stat()`, so it's left out.
+    Stat(io::Error),
+    /// A directory couldn't be opened or read. `pre()` and `post()` are still
+    /// called for it.
+    ReadDir(io::Error),
+    /// A directory is the same as one it's in, at this path. It's left out.
+    Cycle(PathBuf),
+}
+
+impl WalkError {
+    /// Make the error to show, the way most utilities do:
+    /// `cannot read directory 'notes': Permission denied`.
+    pub fn to_error(&self) -> UError {
+        UError::new(self.to_string())
+    }
+}
+
+impl fmt::Display for WalkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = quote_name(&self.path);
+        match &self.kind {
+            WalkErrorKind::Stat(e) => write!(f, "cannot access {}: {}", path, strerror(e)),
+            WalkErrorKind::ReadDir(e) => write!(f, "cannot read directory {}: {}", path, strerror(e)),
+            WalkErrorKind::Cycle(ancestor) => {
+                write!(f, "{} is part of the same file system loop as {}", path, quote_name(ancestor))
+            },
+        }
+    }
+}
+
+impl std::error::Error for WalkError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            WalkErrorKind::Stat(e) | WalkErrorKind::ReadDir(e) => Some(e),
+            WalkErrorKind::Cycle(_) => None,
+        }
+    }
+}
+
+/// How to walk a tree.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Walk {
+    follow: Follow,
+    one_file_system: bool,
+}
+
+impl Walk {
+    /// Walk without following symbolic links, onto any file system.
+    pub fn new() -> Walk {
+        Walk::default()
+    }
+
+    /// Say which symbolic links are followed.
+    pub fn follow(mut self, follow: Follow) -> Walk {
+        self.follow = follow;
+        self
+    }
+
+    /// Say whether to keep out of directories on other file systems than the
+    /// operand. They're still given to `pre()` and `post()`.
+    pub fn one_file_system(mut self, one_file_system: bool) -> Walk {
+        self.one_file_system = one_file_system;
+        self
+    }
+
+    /// Walk everything under one operand. Returns `false` if the visitor stopped
+    /// it.
+    ///
+    /// # Arguments
+    ///
+    /// * `operand` - The file or directory to start at, from the command line.
+    /// * `visitor` - What to tell about each file.
+    pub fn run<V: Visitor + ?Sized>(&self, operand: impl AsRef<Path>, visitor: &mut V) -> bool {
+        let operand = operand.as_ref();
+        let mut walker = Walker { walk: *self, visitor, path: operand.to_owned(), stack: Vec::new(), open: 0, dev: 0 };
+        walker.run(operand.as_os_str()) != Action::Stop
+    }
+}
+
+/// A walk under way.
+struct Walker<'v, V: ?Sized> {
+    walk: Walk,
+    visitor: &'v mut V,
+    /// Path of what's being looked at.
+    path: PathBuf,
+    /// The directories being walked, from the operand down.
+    stack: Vec<Dir>,
+    /// How many of them are open.
+    open: usize,
+    /// The file system the operand is on.
+    dev: u64,
+}
+
+/// A directory being walked.
+struct Dir {
+    /// `None` if it was closed to keep the number open down.
+    fd: Option<OwnedFd>,
+    name: OsString,
+    stat: Stat,
+    /// Whether it was gone into through a symbolic link.
+    followed: bool,
+    other_device: bool,
+    /// Length of its path.
+    len: usize,
+    /// What's left to walk in it.
+    names: std::vec::IntoIter<OsString>,
+}
+
+impl<V: Visitor + ?Sized> Walker<'_, V> {
+    /// Helper method to walk the operand, and everything under it.
+    fn run(&mut self, operand: &OsStr) -> Action {
+        let stat = match stat_at(libc::AT_FDCWD, operand, self.walk.follow != Follow::Never) {
+            Ok(stat) => stat,
+            Err(e) => return self.error(0, WalkErrorKind::Stat(e)),
+        };
+        self.dev = stat.dev;
+        if self.enter(libc::AT_FDCWD, operand, stat, 0) == Action::Stop {
+            return Action::Stop;
+        }
+
+        while !self.stack.is_empty() {
+            let depth = self.stack.len();
+            let action = match self.stack[depth - 1].names.next() {
+                Some(name) => self.next(&name, depth),
+                None => self.leave(),
+            };
+            if action == Action::Stop {
+                return Action::Stop;
+            }
+        }
+        Action::Continue
+    }
+
+    /// Helper method to walk one file in the deepest directory, and go into it if
+    /// it's a directory.
+    fn next(&mut self, name: &OsStr, depth: usize) -> Action {
+        let fd = match self.fd(depth - 1) {
+            Ok(fd) => fd,
+            Err(e) => return self.lost(depth - 1, e),
+        };
+        self.path.push(name);
+        let action = match stat_at(fd, name, self.walk.follow == Follow::Always) {
+            Ok(stat) => self.enter(fd, name, stat, depth),
+            Err(e) => self.error(depth, WalkErrorKind::Stat(e)),
+        };
+        if self.stack.len() == depth {
+            self.truncate(self.stack[depth - 1].len);
+        }
+        action
+    }
+
+    /// Helper method to tell the visitor about a file, and start walking it if it's
+    /// a directory that can be gone into.
+    fn enter(&mut self, dir_fd: RawFd, name: &OsStr, stat: Stat, depth: usize) -> Action {
+        if stat.is_dir() {
+            if let Some(i) = self.stack.iter().position(|dir| dir.stat.dev == stat.dev && dir.stat.ino == stat.ino) {
+                let ancestor = self.dir_path(i);
+                return self.error(depth, WalkErrorKind::Cycle(ancestor));
+            }
+        }
+        let other_device = stat.dev != self.dev;
+        let entry = Entry { path: &self.path, name, dir_fd, depth, stat: &stat, other_device };
+        match self.visitor.pre(&entry) {
+            Action::Continue if stat.is_dir() => {},
+            Action::Stop => return Action::Stop,
+            _ => return Action::Continue,
+        }
+
+        if !(self.walk.one_file_system && other_device) {
+            let followed = match self.walk.follow {
+                Follow::Never => false,
+                Follow::Operands => depth == 0,
+                Follow::Always => true,
+            };
+            let opened = open_dir(dir_fd, name, followed, &stat).and_then(|fd| Ok((read_names(fd.as_raw_fd())?, fd)));
+            match opened {
+                Ok((names, fd)) => {
+                    self.stack.push(Dir {
+                        fd: Some(fd),
+                        name: name.to_owned(),
+                        stat,
+                        followed,
+                        other_device,
+                        len: self.path.as_os_str().len(),
+                        names: names.into_iter(),
+                    });
+                    self.open += 1;
+                    self.limit(self.stack.len() - 1);
+                    return Action::Continue;
+                },
+                Err(e) => {
+                    if self.error(depth, WalkErrorKind::ReadDir(e)) == Action::Stop {
+                        return Action::Stop;
+                    }
+                },
+            }
+        }
+        let entry = Entry { path: &self.path, name, dir_fd, depth, stat: &stat, other_device };
+        self.visitor.post(&entry)
+    }
+
+    /// Helper method to finish the deepest directory, and tell the visitor.
+    fn leave(&mut self) -> Action {
+        let Some(Dir { fd, name, stat, other_device, .. }) = self.stack.pop() else {
+            return Action::Continue;
+        };
+        if fd.is_some() {
+            self.open -= 1;
+        }
+        drop(fd);
+        let depth = self.stack.len();
+        let dir_fd = match depth {
+            0 => libc::AT_FDCWD,
+            _ => match self.fd(depth - 1) {
+                Ok(fd) => fd,
+                Err(e) => return self.lost(depth - 1, e),
+            },
+        };
+        let entry = Entry { path: &self.path, name: &name, dir_fd, depth, stat: &stat, other_device };
+        let action = self.visitor.post(&entry);
+        if depth > 0 {
+            self.truncate(self.stack[depth - 1].len);
+        }
+        action
+    }
+
+    /// Helper method to get a directory being walked, opening it again if it was
+    /// closed. It's opened from the nearest directory it's in that's still open,
+    /// and each one on the way has to be the same as before.
+    fn fd(&mut self, index: usize) -> io::Result<RawFd> {
+        if let Some(fd) = &self.stack[index].fd {
+            return Ok(fd.as_raw_fd());
+        }
+        let start = self.stack[..index].iter().rposition(|dir| dir.fd.is_some());
+        let mut parent = start.and_then(|i| self.stack[i].fd.as_ref()).map_or(libc::AT_FDCWD, AsRawFd::as_raw_fd);
+        let mut fd = None;
+        for dir in &self.stack[start.map_or(0, |i| i + 1)..=index] {
+            let next = open_dir(parent, &dir.name, dir.followed, &dir.stat)?;
+            parent = next.as_raw_fd();
+            fd = Some(next);
+        }
+        self.stack[index].fd = fd;
+        self.open += 1;
+        self.limit(index);
+        Ok(parent)
+    }
+
+    /// Helper method to close directories, the ones nearest the operand first,
+    /// until few enough are open.
+    ///
+    /// # Arguments
+    ///
+    /// * `keep` - Index of a directory to leave open.
+    fn limit(&mut self, keep: usize) {
+        for (i, dir) in self.stack.iter_mut().enumerate() {
+            if self.open <= MAX_OPEN {
+                break;
+            }
+            if i != keep && dir.fd.take().is_some() {
+                self.open -= 1;
+            }
+        }
+    }
+
+    /// Helper method for a directory that couldn't be opened again: what's left
+    /// in it is skipped.
+    fn lost(&mut self, index: usize, e: io::Error) -> Action {
+        self.stack[index].names = Vec::new().into_iter();
+        let path = self.dir_path(index);
+        match self.visitor.error(WalkError { path, depth: index, kind: WalkErrorKind::ReadDir(e) }) {
+            Action::Stop => Action::Stop,
+            _ => Action::Continue,
+        }
+    }
+
+    /// Helper method to tell the visitor about an error with what's being looked at.
+    fn error(&mut self, depth: usize, kind: WalkErrorKind) -> Action {
+        match self.visitor.error(WalkError { path: self.path.clone(), depth, kind }) {
+            Action::Stop => Action::Stop,
+            _ => Action::Continue,
+        }
+    }
+
+    /// Helper method to get the path of a directory being walked.
+    fn dir_path(&self, index: usize) -> PathBuf {
+        let bytes = &self.path.as_os_str().as_bytes()[..self.stack[index].len];
+        PathBuf::from(OsStr::from_bytes(bytes))
+    }
+
+    /// Helper method to cut the path back to `len` bytes, to go back up.
+    fn truncate(&mut self, len: usize) {
+        let mut bytes = std::mem::take(&mut self.path).into_os_string().into_vec();
+        bytes.truncate(len);
+        self.path = PathBuf::from(OsString::from_vec(bytes));
+    }
+}
+
+/// Helper method to make a name into a C string.
+fn c_name(name: &OsStr) -> io::Result<CString> {
+    CString::new(name.as_bytes()).map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))
+}
+
+/// Helper method to look at `name` in the directory `dir`. A link that's followed
+/// to nothing, or to a loop of links, is looked at as a link.
+fn stat_at(dir: RawFd, name: &OsStr, follow: bool) -> io::Result<Stat> {
+    let name = c_name(name)?;
+    let stat = |flags| {
+        let mut st = MaybeUninit::<libc::stat>::uninit();
+        // SAFETY: `name` is null-terminated, and `st` has room for the result
+        if unsafe { libc::fstatat(dir, name.as_ptr(), st.as_mut_ptr(), flags) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: it was filled in
+        Ok(Stat::from(unsafe { st.assume_init() }))
+    };
+    if !follow {
+        return stat(libc::AT_SYMLINK_NOFOLLOW);
+    }
+    stat(0).or_else(|e| match e.raw_os_error() {
+        Some(libc::ENOENT | libc::ELOOP) => stat(libc::AT_SYMLINK_NOFOLLOW).map_err(|_| e),
+        _ => Err(e),
+    })
+}
+
+/// Helper method to open the directory `name` in `dir`. It has to be the same
+/// one `stat` was about, and not something put in its place since.
+fn open_dir(dir: RawFd, name: &OsStr, follow: bool, stat: &Stat) -> io::Result<OwnedFd> {
+    let name = c_name(name)?;
+    let mut flags = libc::O_RDONLY | libc::O_DIRECTORY | libc::O_CLOEXEC;
+    if !follow {
+        flags |= libc::O_NOFOLLOW;
+    }
+    // SAFETY: `name` is null-terminated
+    let fd = unsafe { libc::openat(dir, name.as_ptr(), flags) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: it was just opened, and nothing else has it
+    let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+    let mut st = MaybeUninit::<libc::stat>::uninit();
+    // SAFETY: `st` has room for the result
+    if unsafe { libc::fstat(fd.as_raw_fd(), st.as_mut_ptr()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: it was filled in
+    let now = Stat::from(unsafe { st.assume_init() });
+    if now.dev != stat.dev || now.ino != stat.ino {
+        // like gnulib's fts does
+        return Err(io::Error::from_raw_os_error(libc::ENOENT));
+    }
+    Ok(fd)
+}
+
+/// Helper method to get the names in an open directory, without `.` and `..`.
+fn read_names(fd: RawFd) -> io::Result<Vec<OsString>> {
+    // `closedir()` closes the descriptor it's given, so it gets a copy
+    // SAFETY: this only makes a new descriptor
+    let copy = unsafe { libc::fcntl(fd, libc::F_DUPFD_CLOEXEC, 0) };
+    if copy < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: `copy` is open, and the stream takes it over
+    let dir = unsafe { libc::fdopendir(copy) };
+    if dir.is_null() {
+        let e = io::Error::last_os_error();
+        // SAFETY: the stream didn't take it over
+        unsafe { libc::close(copy) };
+        return Err(e);
+    }
+
+    let mut names = Vec::new();
+    let result = loop {
+        clear_errno();
+        // SAFETY: `dir` is an open stream
+        let entry = unsafe { libc::readdir(dir) };
+        if entry.is_null() {
+            let e = io::Error::last_os_error();
+            break if e.raw_os_error() == Some(0) { Ok(names) } else { Err(e) };
+        }
+        // SAFETY: `d_name` is null-terminated, and lasts until the next `readdir()`
+        let name = unsafe { CStr::from_ptr((*entry).d_name.as_ptr()) }.to_bytes();
+        if name != b"." && name != b".." {
+            names.push(OsStr::from_bytes(name).to_owned());
+        }
+    };
+    // SAFETY: `dir` is an open stream, and isn't used after this
+    unsafe { libc::closedir(dir) };
+    result
+}
+
+/// Helper method to clear `errno`, so `readdir()` getting to the end can be told
+/// from it failing.
+fn clear_errno() {
+    // SAFETY: it's this thread's `errno`
+    #[cfg(any(target_os = "linux", target_os = "emscripten", target_os = "redox"))]
+    unsafe {
+        *libc::__errno_location() = 0;
+    }
+    // SAFETY: it's this thread's `errno`
+    #[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd"))]
+    unsafe {
+        *libc::__error() = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::{symlink, PermissionsExt};
+
+    /// A directory for one test, removed when it's dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> TempDir {
+            let path = std::env::temp_dir().join(format!("shared_walk_{}_{}", std::process::id(), name));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir(&path).unwrap();
+            TempDir(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// Writes down everything it's told, with paths under `root`.
+    struct Record {
+        root: PathBuf,
+        events: Vec<String>,
+        /// Paths to skip, or to stop at.
+        skip: &'static str,
+        stop: &'static str,
+    }
+
+    impl Record {
+        fn new(root: &Path) -> Record {
+            Record { root: root.to_owned(), events: Vec::new(), skip: "", stop: "" }
+        }
+
+        fn name(&self, path: &Path) -> String {
+            match path.strip_prefix(&self.root).unwrap().to_str().unwrap() {
+                "" => ".".to_owned(),
+                name => name.to_owned(),
+            }
+        }
+
+        /// The events, sorted, since the order in a directory isn't known.
+        fn sorted(&self) -> Vec<String> {
+            let mut events = self.events.clone();
+            events.sort();
+            events
+        }
+
+        /// Check that everything in a directory comes after it, and before the end
+        /// of it.
+        fn check_order(&self) {
+            for (i, event) in self.events.iter().enumerate() {
+                let Some(dir) = event.split(' ').nth(2).filter(|_| event.starts_with("post")) else {
+                    continue;
+                };
+                let start = self.events.iter().position(|e| e.starts_with("pre") && e.ends_with(&format!(" d {}", dir))).unwrap();
+                for (j, other) in self.events.iter().enumerate() {
+                    let path = other.rsplit(' ').next().unwrap();
+                    if dir == "." || path.starts_with(&format!("{}/", dir)) {
+                        assert!(start <= j && j <= i, "{} out of order in {:?}", other, self.events);
+                    }
+                }
+            }
+        }
+    }
+
+    impl Visitor for Record {
+        fn pre(&mut self, entry: &Entry) -> Action {
+            let name = self.name(entry.path());
+            let kind = if entry.stat().is_dir() { 'd' } else if entry.stat().is_symlink() { 'l' } else { 'f' };
+            self.events.push(format!("pre {} {} {}", entry.depth(), kind, name));
+            if name == self.skip {
+                Action::Skip
+            } else if name == self.stop {
+                Action::Stop
+            } else {
+                Action::Continue
+            }
+        }
+
+        fn post(&mut self, entry: &Entry) -> Action {
+            self.events.push(format!("post {} {}", entry.depth(), self.name(entry.path())));
+            Action::Continue
+        }
+
+        fn error(&mut self, error: WalkError) -> Action {
+            let kind = match &error.kind {
+                WalkErrorKind::Stat(e) | WalkErrorKind::ReadDir(e) => strerror(e),
+                WalkErrorKind::Cycle(ancestor) => format!("cycle {}", self.name(ancestor)),
+            };
+            self.events.push(format!("error {} {}: {}", error.depth, self.name(&error.path), kind));
+            Action::Continue
+        }
+    }
+
+    /// Helper method to make a small tree with some of everything in it.
+    fn tree(name: &str) -> TempDir {
+        let dir = TempDir::new(name);
+        let top = dir.0.join("top");
+        fs::create_dir_all(top.join("b/d")).unwrap();
+        fs::create_dir(top.join("empty")).unwrap();
+        fs::write(top.join("a"), "a").unwrap();
+        fs::write(top.join("b/c"), "c").unwrap();
+        fs::write(top.join("b/d/e"), "e").unwrap();
+        dir
+    }
+
+    #[test]
+    fn pre_and_post_order() {
+        let dir = tree("order");
+        let top = dir.0.join("top");
+        let mut record = Record::new(&top);
+        assert!(Walk::new().run(&top, &mut record));
+        record.check_order();
+        assert_eq!(record.sorted(), [
+            "post 0 .", "post 1 b", "post 1 empty", "post 2 b/d",
+            "pre 0 d .", "pre 1 d b", "pre 1 d empty", "pre 1 f a", "pre 2 d b/d", "pre 2 f b/c", "pre 3 f b/d/e",
+        ]);
+
+        // a file on its own
+        let mut record = Record::new(&top);
+        assert!(Walk::new().run(top.join("a"), &mut record));
+        assert_eq!(record.events, ["pre 0 f a"]);
+    }
+
+    #[test]
+    fn symlinks() {
+        let dir = tree("symlinks");
+        let top = dir.0.join("top");
+        symlink("b", top.join("link")).unwrap();
+        symlink("nowhere", top.join("dangling")).unwrap();
+        let outer = dir.0.join("outer");
+        symlink("top", &outer).unwrap();
+
+        let walk = |follow| {
+            let mut record = Record::new(&outer);
+            assert!(Walk::new().follow(follow).run(&outer, &mut record));
+            record.check_order();
+            record.sorted()
+        };
+        assert_eq!(walk(Follow::Never), ["pre 0 l ."]);
+
+        let operands = walk(Follow::Operands);
+        assert!(operands.contains(&"pre 1 l link".to_owned()));
+        assert!(operands.contains(&"pre 1 l dangling".to_owned()));
+        assert!(operands.contains(&"post 0 .".to_owned()));
+        assert!(!operands.iter().any(|event| event.contains("link/")));
+
+        // the link is gone into, and a link to nothing is still a link
+        let always = walk(Follow::Always);
+        for event in ["pre 1 d link", "pre 2 f link/c", "pre 3 f link/d/e", "post 1 link", "pre 1 l dangling"] {
+            assert!(always.contains(&event.to_owned()), "{} not in {:?}", event, always);
+        }
+        assert!(!always.iter().any(|event| event.starts_with("error")));
+    }
+
+    #[test]
+    fn cycles() {
+        let dir = tree("cycles");
+        let top = dir.0.join("top");
+        symlink("..", top.join("b/up")).unwrap();
+        symlink(".", top.join("b/d/here")).unwrap();
+
+        let mut record = Record::new(&top);
+        assert!(Walk::new().follow(Follow::Always).run(&top, &mut record));
+        record.check_order();
+        let events = record.sorted();
+        assert!(events.contains(&"error 2 b/up: cycle .".to_owned()), "{:?}", events);
+        assert!(events.contains(&"error 3 b/d/here: cycle b/d".to_owned()), "{:?}", events);
+        assert!(events.contains(&"pre 3 f b/d/e".to_owned()));
+        assert_eq!(events.iter().filter(|event| event.starts_with("pre")).count(), 7);
+
+        // without following them, they're just links
+        let mut record = Record::new(&top);
+        assert!(Walk::new().run(&top, &mut record));
+        assert!(record.events.contains(&"pre 2 l b/up".to_owned()));
+        assert!(!record.events.iter().any(|event| event.starts_with("error")));
+    }
+
+    #[test]
+    fn skip_and_stop() {
+        let dir = tree("skip");
+        let top = dir.0.join("top");
+        let mut record = Record::new(&top);
+        record.skip = "b";
+        assert!(Walk::new().run(&top, &mut record));
+        let events = record.sorted();
+        assert!(events.contains(&"pre 1 d b".to_owned()));
+        assert!(!events.iter().any(|event| event.ends_with(" b") && event.starts_with("post") || event.contains("b/")));
+
+        let mut record = Record::new(&top);
+        record.stop = "b/d";
+        assert!(!Walk::new().run(&top, &mut record));
+        assert_eq!(record.events.last().unwrap(), "pre 2 d b/d");
+        assert!(!record.events.iter().any(|event| event.contains("b/d/") || event == "post 0 ."));
+    }
+
+    #[test]
+    fn errors_continue() {
+        let dir = tree("errors");
+        let top = dir.0.join("top");
+        let mut record = Record::new(&dir.0);
+        assert!(Walk::new().run(dir.0.join("missing"), &mut record));
+        assert_eq!(record.events, ["error 0 missing: No such file or directory"]);
+
+        // root can read anything, so this only fails for everyone else
+        if crate::users::process_ids().euid != 0 {
+            fs::set_permissions(top.join("b"), fs::Permissions::from_mode(0o000)).unwrap();
+            let mut record = Record::new(&top);
+            assert!(Walk::new().run(&top, &mut record));
+            fs::set_permissions(top.join("b"), fs::Permissions::from_mode(0o755)).unwrap();
+            let events = record.sorted();
+            assert!(events.contains(&"error 1 b: Permission denied".to_owned()));
+            assert!(events.contains(&"post 1 b".to_owned()));
+            assert!(events.contains(&"post 1 empty".to_owned()));
+        }
+
+        let error = WalkError { path: "a b".into(), depth: 0, kind: WalkErrorKind::ReadDir(io::Error::from_raw_os_error(libc::EACCES)) };
+        assert_eq!(error.to_error().to_string(), "cannot read directory 'a b': Permission denied");
+        let error = WalkError { path: "x/up".into(), depth: 1, kind: WalkErrorKind::Cycle("x".into()) };
+        assert_eq!(error.to_string(), "x/up is part of the same file system loop as x");
+    }
+
+    /// Removes everything it walks, the way `rm -r` does.
+    struct Remove {
+        dirs: usize,
+        deepest: usize,
+        longest: usize,
+    }
+
+    impl Visitor for Remove {
+        fn pre(&mut self, entry: &Entry) -> Action {
+            self.deepest = self.deepest.max(entry.depth());
+            self.longest = self.longest.max(entry.path().as_os_str().len());
+            Action::Continue
+        }
+
+        fn post(&mut self, entry: &Entry) -> Action {
+            let name = c_name(entry.name()).unwrap();
+            // SAFETY: `name` is null-terminated
+            assert_eq!(unsafe { libc::unlinkat(entry.dir_fd(), name.as_ptr(), libc::AT_REMOVEDIR) }, 0,
+                       "{}", io::Error::last_os_error());
+            self.dirs += 1;
+            Action::Continue
+        }
+
+        fn error(&mut self, error: WalkError) -> Action {
+            panic!("{}", error);
+        }
+    }
+
+    #[test]
+    fn deep_tree() {
+        const DEPTH: usize = 200;
+        let dir = TempDir::new("deep");
+        let top = dir.0.join("top");
+        fs::create_dir(&top).unwrap();
+        // too long for a path, so it's made one directory at a time
+        let name = OsString::from("x".repeat(60));
+        let c = c_name(&name).unwrap();
+        let mut fd = open_dir(libc::AT_FDCWD, top.as_os_str(), false, &stat_at(libc::AT_FDCWD, top.as_os_str(), false).unwrap()).unwrap();
+        for _ in 0..DEPTH {
+            // SAFETY: `c` is null-terminated
+            assert_eq!(unsafe { libc::mkdirat(fd.as_raw_fd(), c.as_ptr(), 0o755) }, 0);
+            let stat = stat_at(fd.as_raw_fd(), &name, false).unwrap();
+            fd = open_dir(fd.as_raw_fd(), &name, false, &stat).unwrap();
+        }
+        drop(fd);
+
+        let mut remove = Remove { dirs: 0, deepest: 0, longest: 0 };
+        assert!(Walk::new().run(&top, &mut remove));
+        assert_eq!(remove.dirs, DEPTH + 1);
+        assert_eq!(remove.deepest, DEPTH);
+        assert!(remove.longest > 4096 && DEPTH > MAX_OPEN);
+        assert!(!top.exists());
+    }
+
+    #[test]
+    fn other_file_system() {
+        let dir = tree("devices");
+        let top = dir.0.join("top");
+        let Ok(proc) = fs::metadata("/proc/self") else {
+            return;
+        };
+        if std::os::unix::fs::MetadataExt::dev(&proc) == std::os::unix::fs::MetadataExt::dev(&fs::metadata(&top).unwrap()) {
+            return;
+        }
+        symlink("/proc/self", top.join("proc")).unwrap();
+
+        let mut record = Record::new(&top);
+        assert!(Walk::new().follow(Follow::Always).one_file_system(true).run(&top, &mut record));
+        let events = record.sorted();
+        assert!(events.contains(&"pre 1 d proc".to_owned()));
+        assert!(events.contains(&"post 1 proc".to_owned()));
+        assert!(!events.iter().any(|event| event.contains("proc/")));
+    }
+}